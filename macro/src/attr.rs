@@ -0,0 +1,316 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::ToTokens;
+use quote::quote;
+use syn::Attribute;
+use syn::Expr;
+use syn::LitByteStr;
+use syn::LitInt;
+use syn::LitStr;
+use syn::Path;
+use syn::Token;
+use syn::Type;
+use syn::WherePredicate;
+use syn::meta::ParseNestedMeta;
+use syn::punctuated::Punctuated;
+
+/// `#[sadby(...)]` attributes of the struct or enum itself.
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+    /// `#[sadby(bound = "...")]`, replaces every inferred bound.
+    pub bound: Option<Vec<WherePredicate>>,
+    /// `#[sadby(version = N)]`, written in front of the encoding.
+    pub version: Option<LitInt>,
+    /// `#[sadby(tagged)]`, every field is written with its `#[sadby(id = N)]` and length.
+    pub tagged: bool,
+    /// `#[sadby(transparent)]`, a single field struct encoded exactly like its field.
+    pub transparent: bool,
+    /// `#[sadby(packed)]`, fixed size fields back to back without any length prefixes.
+    pub packed: bool,
+    /// `#[sadby(endian = "big")]`, byte order of every field without one of its own.
+    pub endian: Option<Endian>,
+    /// `#[sadby(magic = b"...")]`, bytes written in front of everything else and checked on decode.
+    pub magic: Option<LitByteStr>,
+    /// `#[sadby(checksum = "crc32")]`, checksum appended to the payload and verified on decode.
+    pub checksum: Option<Checksum>,
+    /// `#[sadby(validate = "path")]`, check run on the decoded value.
+    pub validate: Option<Path>,
+    /// `#[sadby(crate = "path")]`, where to find sadby when it's re-exported, `::sadby` otherwise.
+    pub krate: Option<Path>,
+    /// `#[sadby(into = "T")]`, encode through `Into<T>`.
+    pub into: Option<Type>,
+    /// `#[sadby(from = "T")]`, decode through `From<T>`.
+    pub from: Option<Type>,
+    /// `#[sadby(try_from = "T")]`, decode through `TryFrom<T>`.
+    pub try_from: Option<Type>,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Checksum {
+    Crc32,
+    Crc32c,
+}
+
+impl ToTokens for Checksum {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        tokens.extend(match self {
+            Checksum::Crc32 => quote! { __sadby::checksum::Checksum::Crc32 },
+            Checksum::Crc32c => quote! { __sadby::checksum::Checksum::Crc32c },
+        });
+    }
+}
+
+/// The type a container is decoded through.
+pub(crate) enum Proxy<'a> {
+    From(&'a Type),
+    TryFrom(&'a Type),
+}
+
+/// `#[sadby(...)]` attributes of a single field.
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    /// `#[sadby(bound = "...")]`, replaces the bounds inferred from this field.
+    pub bound: Option<Vec<WherePredicate>>,
+    /// `#[sadby(since = N)]`, the container version the field was added in.
+    pub since: Option<LitInt>,
+    /// `#[sadby(id = N)]`, the field id in `#[sadby(tagged)]` containers.
+    pub id: Option<LitInt>,
+    /// `#[sadby(bits = N)]`, packs the field into `N` bits shared with its neighbours.
+    pub bits: Option<LitInt>,
+    /// `#[sadby(endian = "big")]`, byte order of the field.
+    pub endian: Option<Endian>,
+    /// `#[sadby(constant = 0xCAFE)]`, value always written in place of the field and checked on
+    /// decode.
+    pub constant: Option<Expr>,
+    /// `#[sadby(validate = "path")]`, check run on the decoded field.
+    pub validate: Option<Path>,
+    /// `#[sadby(varint)]`, writes the integer as a varint instead of at full width.
+    pub varint: Option<Path>,
+    /// `#[sadby(compress)]`, compresses the encoded field.
+    pub compress: Option<Path>,
+    /// `#[sadby(compress(limit = N))]`, largest size the field may decompress to,
+    /// `compress::DEFAULT_LIMIT` otherwise.
+    pub compress_limit: Option<Expr>,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Endian {
+    Little,
+    Big,
+}
+
+impl ToTokens for Endian {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        tokens.extend(match self {
+            Endian::Little => quote! { __sadby::Endian::Little },
+            Endian::Big => quote! { __sadby::Endian::Big },
+        });
+    }
+}
+
+impl ContainerAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut out = Self::default();
+
+        for attr in sadby_attrs(attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("bound") {
+                    out.bound = Some(parse_bound(&meta)?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("version") {
+                    out.version = Some(parse_u8(&meta)?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("tagged") {
+                    out.tagged = true;
+                    return Ok(());
+                }
+                if meta.path.is_ident("transparent") {
+                    out.transparent = true;
+                    return Ok(());
+                }
+                if meta.path.is_ident("packed") {
+                    out.packed = true;
+                    return Ok(());
+                }
+                if meta.path.is_ident("endian") {
+                    out.endian = Some(parse_endian(&meta)?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("magic") {
+                    let lit: LitByteStr = meta.value()?.parse()?;
+                    if lit.value().is_empty() {
+                        return Err(syn::Error::new(lit.span(), "Expected at least one byte"));
+                    }
+                    out.magic = Some(lit);
+                    return Ok(());
+                }
+                if meta.path.is_ident("checksum") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    out.checksum = Some(match lit.value().as_str() {
+                        "crc32" => Checksum::Crc32,
+                        "crc32c" => Checksum::Crc32c,
+                        _ => {
+                            return Err(syn::Error::new(
+                                lit.span(),
+                                r#"Expected "crc32" or "crc32c""#,
+                            ));
+                        }
+                    });
+                    return Ok(());
+                }
+                if meta.path.is_ident("validate") {
+                    out.validate = Some(parse_path(&meta)?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("crate") {
+                    out.krate = Some(parse_path(&meta)?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("into") {
+                    out.into = Some(parse_type(&meta)?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("from") {
+                    out.from = Some(parse_type(&meta)?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("try_from") {
+                    out.try_from = Some(parse_type(&meta)?);
+                    return Ok(());
+                }
+
+                Err(meta.error("Unknown sadby container attribute"))
+            })?;
+        }
+
+        if let (Some(_), Some(try_from)) = (&out.from, &out.try_from) {
+            return Err(syn::Error::new_spanned(
+                try_from,
+                "#[sadby(from)] and #[sadby(try_from)] are mutually exclusive",
+            ));
+        }
+
+        Ok(out)
+    }
+
+    /// Both directions go through proxies, the fields themselves are never encoded.
+    pub(crate) fn proxied(&self) -> bool {
+        self.into.is_some() && self.decode_proxy().is_some()
+    }
+
+    pub(crate) fn decode_proxy(&self) -> Option<Proxy<'_>> {
+        match (&self.from, &self.try_from) {
+            (Some(from), _) => Some(Proxy::From(from)),
+            (None, Some(try_from)) => Some(Proxy::TryFrom(try_from)),
+            (None, None) => None,
+        }
+    }
+}
+
+impl FieldAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut out = Self::default();
+
+        for attr in sadby_attrs(attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("bound") {
+                    out.bound = Some(parse_bound(&meta)?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("since") {
+                    out.since = Some(parse_u8(&meta)?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("id") {
+                    out.id = Some(parse_u8(&meta)?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("bits") {
+                    let lit: LitInt = meta.value()?.parse()?;
+                    let bits = lit.base10_parse::<u32>()?;
+                    if !(1..=64).contains(&bits) {
+                        return Err(syn::Error::new(lit.span(), "Expected 1 to 64 bits"));
+                    }
+                    out.bits = Some(LitInt::new(&format!("{bits}u32"), lit.span()));
+                    return Ok(());
+                }
+                if meta.path.is_ident("endian") {
+                    out.endian = Some(parse_endian(&meta)?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("validate") {
+                    out.validate = Some(parse_path(&meta)?);
+                    return Ok(());
+                }
+                if meta.path.is_ident("varint") {
+                    out.varint = Some(meta.path);
+                    return Ok(());
+                }
+                if meta.path.is_ident("compress") {
+                    if meta.input.peek(syn::token::Paren) {
+                        meta.parse_nested_meta(|inner| {
+                            if inner.path.is_ident("limit") {
+                                out.compress_limit = Some(inner.value()?.parse()?);
+                                return Ok(());
+                            }
+
+                            Err(inner.error("Expected limit"))
+                        })?;
+                    }
+                    out.compress = Some(meta.path);
+                    return Ok(());
+                }
+                if meta.path.is_ident("constant") {
+                    out.constant = Some(meta.value()?.parse()?);
+                    return Ok(());
+                }
+
+                Err(meta.error("Unknown sadby field attribute"))
+            })?;
+        }
+
+        Ok(out)
+    }
+}
+
+fn sadby_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|a| a.path().is_ident("sadby"))
+}
+
+fn parse_bound(meta: &ParseNestedMeta) -> syn::Result<Vec<WherePredicate>> {
+    let lit: LitStr = meta.value()?.parse()?;
+    let predicates = lit.parse_with(Punctuated::<WherePredicate, Token![,]>::parse_terminated)?;
+
+    Ok(predicates.into_iter().collect())
+}
+
+/// Parses `= N` into a `u8` suffixed literal, so it can be compared against a `u8` as is.
+fn parse_u8(meta: &ParseNestedMeta) -> syn::Result<LitInt> {
+    let lit: LitInt = meta.value()?.parse()?;
+    let value = lit.base10_parse::<u8>()?;
+
+    Ok(LitInt::new(&format!("{value}u8"), lit.span()))
+}
+
+fn parse_path(meta: &ParseNestedMeta) -> syn::Result<Path> {
+    let lit: LitStr = meta.value()?.parse()?;
+
+    lit.parse()
+}
+
+fn parse_type(meta: &ParseNestedMeta) -> syn::Result<Type> {
+    let lit: LitStr = meta.value()?.parse()?;
+
+    lit.parse()
+}
+
+fn parse_endian(meta: &ParseNestedMeta) -> syn::Result<Endian> {
+    let lit: LitStr = meta.value()?.parse()?;
+
+    match lit.value().as_str() {
+        "little" => Ok(Endian::Little),
+        "big" => Ok(Endian::Big),
+        _ => Err(syn::Error::new(lit.span(), r#"Expected "little" or "big""#)),
+    }
+}
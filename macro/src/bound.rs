@@ -0,0 +1,189 @@
+use std::collections::HashSet;
+
+use quote::quote;
+use syn::Generics;
+use syn::Ident;
+use syn::WherePredicate;
+use syn::visit::Visit;
+
+use crate::attr::ContainerAttrs;
+use crate::attr::FieldAttrs;
+
+/// Adds `T: Sadby` for every type parameter that shows up in a field, and `T::Assoc: Sadby` for
+/// every associated type of one, unless a `#[sadby(bound = "...")]` says otherwise. Generic fields
+/// that can be missing from the payload, tagged or `since` ones, also need `Field: Default`.
+pub(crate) fn with_bounds(
+    generics: &Generics,
+    container: &ContainerAttrs,
+    data: &syn::Data,
+) -> syn::Result<Generics> {
+    let mut generics = generics.clone();
+
+    let predicates: Vec<WherePredicate> = match &container.bound {
+        Some(bound) => bound.clone(),
+        None if container.proxied() => Vec::new(),
+        None => {
+            let params = generics
+                .type_params()
+                .map(|p| p.ident.clone())
+                .collect::<HashSet<Ident>>();
+
+            let mut predicates = Vec::<WherePredicate>::new();
+            let mut used = Vec::<Ident>::new();
+            let mut associated = Vec::<syn::TypePath>::new();
+            let mut defaults = Vec::<WherePredicate>::new();
+            let mut seen = HashSet::<String>::new();
+
+            for field in fields(data) {
+                let attrs = FieldAttrs::parse(&field.attrs)?;
+                match attrs.bound {
+                    Some(bound) => predicates.extend(bound),
+                    None => {
+                        let mut finder = FindParams {
+                            params: &params,
+                            used: &mut used,
+                            associated: &mut associated,
+                        };
+                        finder.visit_type(&field.ty);
+
+                        let ty = &field.ty;
+                        if (container.tagged || attrs.since.is_some())
+                            && mentions_params(&params, ty)
+                            && seen.insert(quote! { #ty }.to_string())
+                        {
+                            defaults.push(syn::parse_quote! { #ty: ::core::default::Default });
+                        }
+                    }
+                }
+            }
+
+            // Keep the declaration order so the output is stable.
+            for param in generics.type_params() {
+                if used.contains(&param.ident) {
+                    let ident = &param.ident;
+                    predicates.push(syn::parse_quote! { #ident: __sadby::Sadby });
+                }
+            }
+            for ty in associated {
+                predicates.push(syn::parse_quote! { #ty: __sadby::Sadby });
+            }
+            predicates.extend(defaults);
+
+            predicates
+        }
+    };
+
+    if !predicates.is_empty() {
+        let where_clause = generics.make_where_clause();
+        for predicate in predicates {
+            where_clause.predicates.push(predicate);
+        }
+    }
+
+    Ok(generics)
+}
+
+/// Adds `Field: SadbySchema` for every field type that mentions a type parameter to the bounds of
+/// the `Sadby` impl, or for the `#[sadby(into)]` type if the fields aren't encoded.
+pub(crate) fn with_schema_bounds(
+    generics: &Generics,
+    container: &ContainerAttrs,
+    data: &syn::Data,
+) -> Generics {
+    let mut generics = generics.clone();
+
+    let params = generics
+        .type_params()
+        .map(|p| p.ident.clone())
+        .collect::<HashSet<Ident>>();
+    let types = match &container.into {
+        Some(into) => vec![into],
+        None => fields(data).map(|field| &field.ty).collect(),
+    };
+
+    let mut seen = HashSet::<String>::new();
+    let mut predicates = Vec::<WherePredicate>::new();
+    for ty in types {
+        if mentions_params(&params, ty) && seen.insert(quote! { #ty }.to_string()) {
+            predicates.push(syn::parse_quote! { #ty: __sadby::schema::SadbySchema });
+        }
+    }
+
+    if !predicates.is_empty() {
+        let where_clause = generics.make_where_clause();
+        for predicate in predicates {
+            where_clause.predicates.push(predicate);
+        }
+    }
+
+    generics
+}
+
+fn fields(data: &syn::Data) -> Box<dyn Iterator<Item = &syn::Field> + '_> {
+    match data {
+        syn::Data::Struct(s) => Box::new(s.fields.iter()),
+        syn::Data::Enum(e) => Box::new(e.variants.iter().flat_map(|v| v.fields.iter())),
+        syn::Data::Union(u) => Box::new(u.fields.named.iter()),
+    }
+}
+
+/// Whether `ty` mentions any of `params`, associated types of them included.
+fn mentions_params(params: &HashSet<Ident>, ty: &syn::Type) -> bool {
+    let (mut used, mut associated) = (Vec::new(), Vec::new());
+    FindParams {
+        params,
+        used: &mut used,
+        associated: &mut associated,
+    }
+    .visit_type(ty);
+    !used.is_empty() || !associated.is_empty()
+}
+
+struct FindParams<'a> {
+    params: &'a HashSet<Ident>,
+    used: &'a mut Vec<Ident>,
+    /// Paths like `T::Assoc` and `<T as Trait>::Assoc`, which get bounded themselves.
+    associated: &'a mut Vec<syn::TypePath>,
+}
+
+impl FindParams<'_> {
+    fn is_associated(&self, ty: &syn::TypePath) -> bool {
+        match &ty.qself {
+            Some(qself) => mentions_params(self.params, &qself.ty),
+            None => {
+                ty.path.leading_colon.is_none()
+                    && ty.path.segments.len() > 1
+                    && self.params.contains(&ty.path.segments[0].ident)
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for FindParams<'_> {
+    fn visit_type_path(&mut self, ty: &'ast syn::TypePath) {
+        // `PhantomData<T>` never touches a `T`, don't ask for `T: Sadby` because of it.
+        if let Some(last) = ty.path.segments.last()
+            && last.ident == "PhantomData"
+        {
+            return;
+        }
+
+        // Only the associated type is encoded, `T` itself doesn't have to be `Sadby`.
+        if self.is_associated(ty) {
+            if !self.associated.contains(ty) {
+                self.associated.push(ty.clone());
+            }
+            return;
+        }
+
+        if ty.qself.is_none()
+            && let Some(first) = ty.path.segments.first()
+            && self.params.contains(&first.ident)
+            && !self.used.contains(&first.ident)
+        {
+            self.used.push(first.ident.clone());
+        }
+
+        syn::visit::visit_type_path(self, ty);
+    }
+}
@@ -0,0 +1,209 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::DeriveInput;
+use syn::Error;
+use syn::spanned::Spanned;
+
+use crate::LayoutTokens;
+use crate::attr::ContainerAttrs;
+use crate::field::FieldInfo;
+use crate::field::infos;
+use crate::reader_ident;
+use crate::writer_ident;
+
+/// Bodies of `se_described` and `de_described`. Fields are written with their names, or their
+/// ids in `#[sadby(tagged)]` containers, whatever layout the positional encoding uses.
+pub(crate) fn described_tokens(
+    ast: &DeriveInput,
+    container: &ContainerAttrs,
+) -> syn::Result<LayoutTokens> {
+    let writer = writer_ident();
+    let reader = reader_ident();
+
+    match &ast.data {
+        syn::Data::Enum(e) => {
+            let mut tokens_to = Vec::<TokenStream2>::new();
+            let mut tokens_from = Vec::<TokenStream2>::new();
+
+            for variant in e.variants.iter() {
+                let v_ident = &variant.ident;
+                let v_name = v_ident.to_string();
+
+                let fields = infos(&variant.fields, container)?;
+                let pattern = pattern(&variant.fields, &fields);
+
+                let LayoutTokens { to, from } = if fields.is_empty() {
+                    LayoutTokens {
+                        to: quote! { #writer.tag(__sadby::described::Tag::Unit); },
+                        from: quote! { #reader.expect(__sadby::described::Tag::Unit)?; },
+                    }
+                } else {
+                    fields_tokens(&fields, container)
+                };
+                let validate = fields.iter().filter_map(FieldInfo::validate);
+
+                tokens_to.push(quote! {
+                    Self::#v_ident #pattern => {
+                        #writer.name(#v_name);
+                        #to
+                    }
+                });
+                tokens_from.push(quote! {
+                    #v_name => {
+                        #from
+                        #(#validate)*
+
+                        ::core::result::Result::Ok(Self::#v_ident #pattern)
+                    }
+                });
+            }
+
+            Ok(LayoutTokens {
+                to: quote! {
+                    #writer.tag(__sadby::described::Tag::Variant);
+                    #writer.len(unsafe { *(self as *const Self).cast::<u8>() } as usize);
+                    match self {
+                        #(#tokens_to)*
+                    }
+                },
+                from: quote! {
+                    #reader.expect(__sadby::described::Tag::Variant)?;
+                    #reader.len()?;
+                    match #reader.name()? {
+                        #(#tokens_from)*
+                        _ => ::core::result::Result::Err(__sadby::SadbyError::UnexpectedToken),
+                    }
+                },
+            })
+        }
+        syn::Data::Struct(s) => {
+            let fields = infos(&s.fields, container)?;
+            let pattern = pattern(&s.fields, &fields);
+
+            // Transparent containers are described like their one field.
+            let LayoutTokens { to, from } = if container.transparent {
+                let info = &fields[0];
+                let name = &info.name;
+                let se = info.se_described();
+                let de = info.de_described();
+
+                LayoutTokens {
+                    to: se,
+                    from: quote! { let #name = #de; },
+                }
+            } else {
+                fields_tokens(&fields, container)
+            };
+            let validate = fields.iter().filter_map(FieldInfo::validate);
+
+            Ok(LayoutTokens {
+                to: quote! {
+                    let Self #pattern = self;
+                    #to
+                },
+                from: quote! {
+                    #from
+                    #(#validate)*
+
+                    ::core::result::Result::Ok(Self #pattern)
+                },
+            })
+        }
+        _ => Err(Error::new(ast.span(), "Expected Enum or Struct")),
+    }
+}
+
+fn pattern(fields: &syn::Fields, infos: &[FieldInfo]) -> TokenStream2 {
+    let members = infos.iter().map(|info| &info.field.ident);
+    let names = infos.iter().map(|info| &info.name);
+
+    match fields {
+        syn::Fields::Named(_) => quote! { { #(#members: #names, )* } },
+        syn::Fields::Unnamed(_) => quote! { ( #(#names, )* ) },
+        syn::Fields::Unit => quote! {},
+    }
+}
+
+/// A `Tag::Struct` of the fields, or a `Tag::StructIds` in `#[sadby(tagged)]` containers.
+fn fields_tokens(fields: &[FieldInfo], container: &ContainerAttrs) -> LayoutTokens {
+    let writer = writer_ident();
+    let reader = reader_ident();
+
+    let names = fields.iter().map(|info| &info.name).collect::<Vec<_>>();
+    let types = fields.iter().map(|info| &info.field.ty);
+    let count = fields.len();
+    let se = fields.iter().map(FieldInfo::se_described);
+    let de = fields.iter().map(FieldInfo::de_described);
+
+    let (tag, keys, write_key, read_key) = if container.tagged {
+        let ids = fields
+            .iter()
+            .map(|info| {
+                let id = &info.attrs.id;
+                quote! { #id }
+            })
+            .collect::<Vec<_>>();
+        (
+            quote! { StructIds },
+            ids,
+            quote! { byte },
+            quote! { #reader.byte()? },
+        )
+    } else {
+        let labels = fields
+            .iter()
+            .map(|info| {
+                let label = info.display_name();
+                quote! { #label }
+            })
+            .collect::<Vec<_>>();
+        (
+            quote! { Struct },
+            labels,
+            quote! { name },
+            quote! { #reader.name()? },
+        )
+    };
+
+    // Missing fields are only fine if there's a reason for them not to be in the payload.
+    let missing = fields.iter().map(|info| {
+        if container.tagged || info.attrs.since.is_some() {
+            quote! { ::core::default::Default::default() }
+        } else {
+            quote! { return ::core::result::Result::Err(__sadby::SadbyError::UnexpectedToken) }
+        }
+    });
+
+    LayoutTokens {
+        to: quote! {
+            #writer.tag(__sadby::described::Tag::#tag);
+            #writer.len(#count);
+            #(
+                #writer.#write_key(#keys);
+                #se
+            )*
+        },
+        from: quote! {
+            #reader.expect(__sadby::described::Tag::#tag)?;
+            #(
+                let mut #names: ::core::option::Option<#types> = ::core::option::Option::None;
+            )*
+
+            for _ in 0..#reader.len()? {
+                match #read_key {
+                    #(
+                        #keys => #names = ::core::option::Option::Some(#de),
+                    )*
+                    _ => #reader.skip()?,
+                }
+            }
+
+            #(
+                let #names = match #names {
+                    ::core::option::Option::Some(value) => value,
+                    ::core::option::Option::None => #missing,
+                };
+            )*
+        },
+    }
+}
@@ -0,0 +1,212 @@
+use proc_macro2::Span;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::Ident;
+
+use crate::attr::ContainerAttrs;
+use crate::attr::FieldAttrs;
+use crate::endian_ident;
+use crate::handle_field;
+use crate::reader_ident;
+use crate::version_ident;
+use crate::writer_ident;
+
+/// A field together with the name it is bound to in the generated code.
+pub(crate) struct FieldInfo<'a> {
+    pub field: &'a syn::Field,
+    pub name: Ident,
+    pub attrs: FieldAttrs,
+}
+
+impl FieldInfo<'_> {
+    /// Expression encoding the field, its name is bound to a reference of it.
+    pub(crate) fn se(&self) -> TokenStream2 {
+        let name = &self.name;
+        let endian = self.endian();
+
+        let write = |value| {
+            let write = match &self.attrs.varint {
+                Some(_) => quote! { __sadby::varint::se(#value) },
+                None => quote! { __sadby::Sadby::try_se_bytes_endian(#value, #endian)? },
+            };
+            match &self.attrs.compress {
+                Some(_) => quote! { __sadby::compress::se(&#write) },
+                None => write,
+            }
+        };
+
+        match &self.attrs.constant {
+            Some(constant) => {
+                let ty = &self.field.ty;
+                let write = write(quote! { &constant });
+                quote! {{
+                    let _ = #name;
+                    let constant: #ty = #constant;
+                    #write
+                }}
+            }
+            None => write(quote! { #name }),
+        }
+    }
+
+    /// Expression decoding the field out of `bytes`.
+    pub(crate) fn de(&self, bytes: TokenStream2) -> TokenStream2 {
+        // Qualifying the call through `<T as Sadby>` works for every type syntax (generics with
+        // any number of parameters, `<T as Trait>::Assoc`, tuples, arrays, references, ...) so
+        // the type can be passed through untouched.
+        let ty = &self.field.ty;
+        let endian = self.endian();
+        let field = self.display_name();
+        let field_bytes = Ident::new("field_bytes", Span::mixed_site());
+        let value = Ident::new("value", Span::mixed_site());
+        let limit = match &self.attrs.compress_limit {
+            Some(limit) => quote! { #limit },
+            None => quote! { __sadby::compress::DEFAULT_LIMIT },
+        };
+        let decoded = match &self.attrs.compress {
+            Some(_) => quote! {
+                &__sadby::compress::de(#field_bytes, #limit)?
+            },
+            None => quote! { #field_bytes },
+        };
+        let read = match &self.attrs.varint {
+            Some(_) => quote! {{
+                let #value = __sadby::varint::de::<#ty>(#decoded)?;
+                __sadby::trace::value(#decoded, &#value);
+                #value
+            }},
+            None => quote! {
+                __sadby::trace::de::<#ty>(#decoded, #endian).map_err(|e| e.in_field(#field))?
+            },
+        };
+
+        self.check_constant(quote! {{
+            let #field_bytes = #bytes;
+            __sadby::trace::field(#field, #field_bytes);
+            #read
+        }})
+    }
+
+    /// Statement writing the field self-describing, its name is bound to a reference of it.
+    pub(crate) fn se_described(&self) -> TokenStream2 {
+        let name = &self.name;
+        let writer = writer_ident();
+
+        match &self.attrs.constant {
+            Some(constant) => {
+                let ty = &self.field.ty;
+                quote! {{
+                    let _ = #name;
+                    let constant: #ty = #constant;
+                    __sadby::Sadby::se_described(&constant, #writer)?;
+                }}
+            }
+            None => quote! { __sadby::Sadby::se_described(#name, #writer)?; },
+        }
+    }
+
+    /// Expression reading the field self-describing.
+    pub(crate) fn de_described(&self) -> TokenStream2 {
+        let ty = &self.field.ty;
+        let reader = reader_ident();
+        let field = self.display_name();
+
+        self.check_constant(quote! {
+            <#ty as __sadby::Sadby>::de_described(#reader).map_err(|e| e.in_field(#field))?
+        })
+    }
+
+    /// Wraps `read` so a `#[sadby(constant)]` field fails to decode with any other value.
+    fn check_constant(&self, read: TokenStream2) -> TokenStream2 {
+        let Some(constant) = &self.attrs.constant else {
+            return read;
+        };
+
+        let ty = &self.field.ty;
+        let field = self.display_name();
+        quote! {{
+            let value: #ty = #read;
+            let constant: #ty = #constant;
+            if value != constant {
+                return ::core::result::Result::Err(__sadby::SadbyError::BadConstant { field: #field });
+            }
+            value
+        }}
+    }
+
+    /// Statement running the `#[sadby(validate)]` check on the decoded field, bound by name.
+    pub(crate) fn validate(&self) -> Option<TokenStream2> {
+        let validate = self.attrs.validate.as_ref()?;
+        let name = &self.name;
+        let field = self.display_name();
+
+        Some(quote! {
+            if let ::core::result::Result::Err(e) = #validate(&#name) {
+                return ::core::result::Result::Err(__sadby::SadbyError::Invalid {
+                    path: ::std::string::ToString::to_string(#field),
+                    message: ::std::string::ToString::to_string(&e),
+                });
+            }
+        })
+    }
+
+    /// The field as the user knows it, its name or its position in a tuple.
+    pub(crate) fn display_name(&self) -> String {
+        match &self.field.ident {
+            Some(ident) => ident.to_string(),
+            None => self
+                .name
+                .to_string()
+                .trim_start_matches("__field")
+                .to_owned(),
+        }
+    }
+
+    /// The byte order of the field, falling back to the one the container is encoded with.
+    fn endian(&self) -> TokenStream2 {
+        match &self.attrs.endian {
+            Some(endian) => quote! { #endian },
+            None => {
+                let endian = endian_ident();
+                quote! { #endian }
+            }
+        }
+    }
+
+    /// Wraps `read` so fields added after the payload was written take their default instead.
+    pub(crate) fn since(&self, read: TokenStream2) -> TokenStream2 {
+        let Some(since) = &self.attrs.since else {
+            return read;
+        };
+
+        let version = version_ident();
+        quote! {
+            if #version < #since {
+                ::core::default::Default::default()
+            } else {
+                #read
+            }
+        }
+    }
+}
+
+/// The fields of a struct or variant, checked against the container.
+pub(crate) fn infos<'a>(
+    fields: &'a syn::Fields,
+    container: &ContainerAttrs,
+) -> syn::Result<Vec<FieldInfo<'a>>> {
+    match fields {
+        syn::Fields::Named(named) => named
+            .named
+            .iter()
+            .map(|field| handle_field(field, container, None))
+            .collect(),
+        syn::Fields::Unnamed(unnamed) => unnamed
+            .unnamed
+            .iter()
+            .enumerate()
+            .map(|(index, field)| handle_field(field, container, Some(index)))
+            .collect(),
+        syn::Fields::Unit => Ok(Vec::new()),
+    }
+}
@@ -1,9 +1,17 @@
 extern crate proc_macro;
 
+mod attr;
+mod bound;
+mod described;
+mod field;
+mod packed;
+mod positional;
+mod schema;
+mod tagged;
+
 use proc_macro::TokenStream;
 use proc_macro2::Span;
 use proc_macro2::TokenStream as TokenStream2;
-use quote::ToTokens;
 use quote::quote;
 use syn::DeriveInput;
 use syn::Error;
@@ -11,9 +19,23 @@ use syn::Expr;
 use syn::ExprLit;
 use syn::Ident;
 use syn::Lit;
+use syn::Token;
+use syn::ext::IdentExt;
+use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 
-#[proc_macro_derive(Sadby)]
+use crate::attr::ContainerAttrs;
+use crate::attr::FieldAttrs;
+use crate::attr::Proxy;
+use crate::described::described_tokens;
+use crate::field::FieldInfo;
+use crate::packed::packed_size;
+use crate::packed::packed_tokens;
+use crate::positional::positional_tokens;
+use crate::schema::schema_tokens;
+use crate::tagged::tagged_tokens;
+
+#[proc_macro_derive(Sadby, attributes(sadby))]
 pub fn sadb_derive(input: TokenStream) -> TokenStream {
     match sadb_macro(input.into()) {
         Ok(o) => o,
@@ -26,10 +48,246 @@ fn sadb_macro(input: proc_macro2::TokenStream) -> syn::Result<proc_macro2::Token
     let ast: DeriveInput = syn::parse2(input)?;
 
     let ident = &ast.ident;
-    let (impl_generics, type_generics, where_clause) = ast.generics.split_for_impl();
+    let container = ContainerAttrs::parse(&ast.attrs)?;
+    let generics = bound::with_bounds(&ast.generics, &container, &ast.data)?;
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    let schema_generics = bound::with_schema_bounds(&generics, &container, &ast.data);
+    let (schema_impl_generics, _, schema_where_clause) = schema_generics.split_for_impl();
+
+    // Byte order asked for by whoever encodes or decodes this, fields that don't pick their own
+    // follow it.
+    let endian = match &container.endian {
+        Some(endian) => quote! { #endian },
+        None => {
+            let endian = endian_ident();
+            quote! { #endian }
+        }
+    };
+
+    // Both directions going through a proxy leave nothing to generate for the type itself.
+    let (complete_tokens_to, complete_tokens_from, fixed_size) = if container.proxied() {
+        Default::default()
+    } else {
+        data_tokens(&ast, &container)?
+    };
+    let LayoutTokens {
+        to: described_to,
+        from: described_from,
+    } = if container.proxied() {
+        LayoutTokens {
+            to: TokenStream2::new(),
+            from: TokenStream2::new(),
+        }
+    } else {
+        described_tokens(&ast, &container)?
+    };
+
+    let complete_tokens_to = match &container.into {
+        Some(into) => quote! {
+            let wire: #into = ::core::convert::Into::into(::core::clone::Clone::clone(self));
+            __sadby::Sadby::try_se_bytes_endian(&wire, #endian)?
+        },
+        None => complete_tokens_to,
+    };
+    let complete_tokens_from = match container.decode_proxy() {
+        Some(Proxy::From(from)) => quote! {
+            ::core::result::Result::Ok(::core::convert::From::from(
+                __sadby::trace::de::<#from>(input, #endian)?,
+            ))
+        },
+        Some(Proxy::TryFrom(from)) => quote! {
+            <Self as ::core::convert::TryFrom<#from>>::try_from(__sadby::trace::de::<#from>(input, #endian)?)
+                .map_err(|e| __sadby::SadbyError::Custom(::std::string::ToString::to_string(&e)))
+        },
+        None => complete_tokens_from,
+    };
 
-    let (complete_tokens_to, complete_tokens_from): (TokenStream2, TokenStream2) = match &ast.data {
+    let writer = writer_ident();
+    let reader = reader_ident();
+    let described_to = match &container.into {
+        Some(into) => quote! {
+            let wire: #into = ::core::convert::Into::into(::core::clone::Clone::clone(self));
+            __sadby::Sadby::se_described(&wire, #writer)?;
+        },
+        None => described_to,
+    };
+    let described_from = match container.decode_proxy() {
+        Some(Proxy::From(from)) => quote! {
+            ::core::result::Result::Ok(::core::convert::From::from(
+                <#from as __sadby::Sadby>::de_described(#reader)?,
+            ))
+        },
+        Some(Proxy::TryFrom(from)) => quote! {
+            <Self as ::core::convert::TryFrom<#from>>::try_from(<#from as __sadby::Sadby>::de_described(#reader)?)
+                .map_err(|e| __sadby::SadbyError::Custom(::std::string::ToString::to_string(&e)))
+        },
+        None => described_from,
+    };
+
+    let complete_tokens_from = validated(&container, complete_tokens_from);
+    let described_from = validated(&container, described_from);
+
+    // The version goes in front of everything else, decoding strips it before going on. Payloads
+    // of a newer version may have fields this one doesn't know about, they're rejected instead of
+    // read as if they were this one.
+    let (complete_tokens_to, complete_tokens_from, fixed_size) = match &container.version {
+        Some(version) => {
+            let version_ident = version_ident();
+            (
+                quote! {
+                    let mut buf = ::std::vec![#version];
+                    buf.append(&mut { #complete_tokens_to });
+                    buf
+                },
+                quote! {
+                    let #version_ident = *input
+                        .first()
+                        .ok_or(__sadby::SadbyError::UnexpectedToken)?;
+                    if #version_ident > #version {
+                        return ::core::result::Result::Err(__sadby::SadbyError::NewerVersion {
+                            found: #version_ident,
+                            supported: #version,
+                        });
+                    }
+                    let input = &input[1..];
+                    #complete_tokens_from
+                },
+                fixed_size.map(|size| add_fixed_size(size, quote! { 1 })),
+            )
+        }
+        None => (complete_tokens_to, complete_tokens_from, fixed_size),
+    };
+
+    // The checksum covers the version and the payload, and is verified before either is read.
+    let (complete_tokens_to, complete_tokens_from, fixed_size) = match &container.checksum {
+        Some(checksum) => (
+            quote! {
+                let mut buf = { #complete_tokens_to };
+                #checksum.append(&mut buf, #endian);
+                buf
+            },
+            quote! {
+                let input = #checksum.verify(input, #endian)?;
+                #complete_tokens_from
+            },
+            fixed_size
+                .map(|size| add_fixed_size(size, quote! { __sadby::checksum::Checksum::SIZE })),
+        ),
+        None => (complete_tokens_to, complete_tokens_from, fixed_size),
+    };
+
+    // The magic goes in front of the version, so a payload of the wrong type is caught before
+    // anything else is read.
+    let (complete_tokens_to, complete_tokens_from, fixed_size) = match &container.magic {
+        Some(magic) => {
+            let len = magic.value().len();
+            (
+                quote! {
+                    let mut buf = #magic.to_vec();
+                    buf.append(&mut { #complete_tokens_to });
+                    buf
+                },
+                quote! {
+                    if !input.starts_with(#magic) {
+                        return ::core::result::Result::Err(__sadby::SadbyError::BadMagic {
+                            expected: #magic.to_vec(),
+                            found: input[..::core::cmp::Ord::min(input.len(), #len)].to_vec(),
+                        });
+                    }
+                    let input = &input[#len..];
+                    #complete_tokens_from
+                },
+                fixed_size.map(|size| add_fixed_size(size, quote! { #len })),
+            )
+        }
+        None => (complete_tokens_to, complete_tokens_from, fixed_size),
+    };
+
+    let schema = schema_tokens(&ast, &container)?;
+
+    let fixed_size = fixed_size.map(|size| {
+        quote! {
+            const FIXED_SIZE: ::core::option::Option<usize> = #size;
+        }
+    });
+
+    // Without generics the size can be checked right away, instead of when it's first used.
+    let check_fixed_size = (fixed_size.is_some() && ast.generics.params.is_empty()).then(|| {
+        quote! {
+            const _: ::core::option::Option<usize> = <#ident as __sadby::Sadby>::FIXED_SIZE;
+        }
+    });
+
+    let endian_arg = endian_ident();
+
+    // Everything generated goes through this alias, so neither the user's imports nor their own
+    // items named like ours get in the way.
+    let krate = match &container.krate {
+        Some(krate) => quote! { #krate },
+        None => quote! { ::sadby },
+    };
+
+    Ok(quote! {
+        const _: () = {
+        use #krate as __sadby;
+
+        impl #impl_generics __sadby::Sadby for #ident #type_generics #where_clause {
+            #fixed_size
+
+            fn se_bytes(&self) -> ::std::vec::Vec<u8> {
+                match __sadby::Sadby::try_se_bytes(self) {
+                    ::core::result::Result::Ok(buf) => buf,
+                    ::core::result::Result::Err(e) => ::core::panic!("Failed to encode {}: {:?}", ::core::stringify!(#ident), e),
+                }
+            }
+            fn de_bytes(input: &[u8]) -> ::core::result::Result<Self, __sadby::SadbyError> {
+                <Self as __sadby::Sadby>::de_bytes_endian(input, __sadby::Endian::Little)
+            }
+            fn try_se_bytes(&self) -> ::core::result::Result<::std::vec::Vec<u8>, __sadby::SadbyError> {
+                __sadby::Sadby::try_se_bytes_endian(self, __sadby::Endian::Little)
+            }
+            fn try_se_bytes_endian(&self, #endian_arg: __sadby::Endian) -> ::core::result::Result<::std::vec::Vec<u8>, __sadby::SadbyError> {
+                let _ = #endian_arg;
+                ::core::result::Result::Ok({ #complete_tokens_to })
+            }
+            fn de_bytes_endian(input: &[u8], #endian_arg: __sadby::Endian) -> ::core::result::Result<Self, __sadby::SadbyError> {
+                let _ = #endian_arg;
+                #complete_tokens_from
+            }
+            fn se_described(&self, #writer: &mut __sadby::described::Writer) -> ::core::result::Result<(), __sadby::SadbyError> {
+                #described_to
+                ::core::result::Result::Ok(())
+            }
+            fn de_described(#reader: &mut __sadby::described::Reader) -> ::core::result::Result<Self, __sadby::SadbyError> {
+                #described_from
+            }
+        }
+
+        impl #schema_impl_generics __sadby::schema::SadbySchema for #ident #type_generics #schema_where_clause {
+            fn schema() -> __sadby::schema::Schema {
+                #schema
+            }
+        }
+
+        #check_fixed_size
+        };
+    })
+}
+
+/// Encoding statements, decoding statements and the fixed size of the encoding, if any.
+fn data_tokens(
+    ast: &DeriveInput,
+    container: &ContainerAttrs,
+) -> syn::Result<(TokenStream2, TokenStream2, Option<TokenStream2>)> {
+    let tokens = match &ast.data {
         syn::Data::Enum(e) => {
+            if container.transparent || container.packed {
+                return Err(Error::new(
+                    ast.span(),
+                    "#[sadby(transparent)] and #[sadby(packed)] are only supported on structs",
+                ));
+            }
+
             let Some(repr) = ast.attrs.iter().find(|a| {
                 if a.style == syn::AttrStyle::Outer
                     && let syn::Meta::List(l) = &a.meta
@@ -59,35 +317,7 @@ fn sadb_macro(input: proc_macro2::TokenStream) -> syn::Result<proc_macro2::Token
 
             for variant in e.variants.iter() {
                 let v_ident = &variant.ident;
-
-                match &variant.discriminant {
-                    Some((
-                        _,
-                        Expr::Lit(ExprLit {
-                            attrs: _,
-                            lit: Lit::Byte(b),
-                        }),
-                    )) => expression = b.value(),
-                    Some((
-                        _,
-                        Expr::Lit(ExprLit {
-                            attrs: _,
-                            lit: Lit::Char(ch),
-                        }),
-                    )) => expression = ch.value() as u8,
-                    Some((
-                        _,
-                        Expr::Lit(ExprLit {
-                            attrs: _,
-                            lit: Lit::Int(i),
-                        }),
-                    )) => expression = i.base10_digits().parse::<u8>().unwrap(),
-
-                    Some((_, expr)) => {
-                        return Err(Error::new(expr.span(), "Expected int, byte or char"));
-                    }
-                    None => {}
-                }
+                expression = discriminant(variant, expression)?;
 
                 match &variant.fields {
                     syn::Fields::Named(syn::FieldsNamed {
@@ -96,32 +326,24 @@ fn sadb_macro(input: proc_macro2::TokenStream) -> syn::Result<proc_macro2::Token
                     }) => {
                         contains_some = true;
 
-                        let mut local_from = Vec::<TokenStream2>::new();
-                        let mut field_names = Vec::<Ident>::new();
-                        let mut first: TokenStream2 = Default::default();
-
-                        for field in named.iter() {
-                            handle_field(
-                                field,
-                                &mut local_from,
-                                &mut first,
-                                &mut field_names,
-                                None,
-                            )?;
-                        }
+                        let fields = named
+                            .iter()
+                            .map(|field| handle_field(field, container, None))
+                            .collect::<syn::Result<Vec<_>>>()?;
+                        let members = fields.iter().map(|f| &f.field.ident);
+                        let field_names = fields.iter().map(|f| &f.name);
 
-                        let field = quote! { { #(#field_names, )* } };
+                        let field = quote! { { #(#members: #field_names, )* } };
 
                         modify_sadb_tokens_enum(
                             named,
+                            container,
                             v_ident,
                             expression,
                             &mut tokens_from,
                             &mut tokens_to,
-                            &mut local_from,
-                            first,
+                            &fields,
                             field,
-                            &mut field_names,
                         )?;
                     }
                     syn::Fields::Unnamed(syn::FieldsUnnamed {
@@ -130,53 +352,36 @@ fn sadb_macro(input: proc_macro2::TokenStream) -> syn::Result<proc_macro2::Token
                     }) => {
                         contains_some = true;
 
-                        let mut local_from = Vec::<TokenStream2>::new();
-
-                        let mut last_name = 'a';
-                        let mut field_names = Vec::<Ident>::new();
-                        let mut first: TokenStream2 = Default::default();
-
-                        for field in unnamed.iter() {
-                            handle_field(
-                                field,
-                                &mut local_from,
-                                &mut first,
-                                &mut field_names,
-                                Some(&mut last_name),
-                            )?;
-
-                            if last_name == '{' {
-                                return Err(Error::new(
-                                    field.span(),
-                                    format!(
-                                        r#"Why the fuck you have so many unnamed fields in {}???
-                                           Advice: Use freaking named fields or better a struct"#,
-                                        variant.ident
-                                    ),
-                                ));
-                            }
-                        }
+                        let fields = unnamed
+                            .iter()
+                            .enumerate()
+                            .map(|(index, field)| handle_field(field, container, Some(index)))
+                            .collect::<syn::Result<Vec<_>>>()?;
+                        let field_names = fields.iter().map(|f| &f.name);
 
                         let field = quote! { ( #(#field_names, )* ) };
 
                         modify_sadb_tokens_enum(
                             unnamed,
+                            container,
                             v_ident,
                             expression,
                             &mut tokens_from,
                             &mut tokens_to,
-                            &mut local_from,
-                            first,
+                            &fields,
                             field,
-                            &mut field_names,
                         )?;
                     }
                     syn::Fields::Unit => {
+                        let name = v_ident.to_string();
                         tokens_from.push(quote! {
-                            #expression => Ok(Self::#v_ident),
+                            #expression => {
+                                __sadby::trace::variant(#name, input);
+                                ::core::result::Result::Ok(Self::#v_ident)
+                            }
                         });
                         tokens_to.push(quote! {
-                            Self::#v_ident => buf.push(#expression),
+                            Self::#v_ident => {}
                         });
                     }
                 }
@@ -186,7 +391,7 @@ fn sadb_macro(input: proc_macro2::TokenStream) -> syn::Result<proc_macro2::Token
             (
                 if contains_some {
                     quote! {
-                        let mut buf = vec![unsafe { *<*const _>::from(self).cast::<u8>() }];
+                        let mut buf = ::std::vec![unsafe { *(self as *const Self).cast::<u8>() }];
 
                         match self {
                             #(#tokens_to)*
@@ -196,18 +401,44 @@ fn sadb_macro(input: proc_macro2::TokenStream) -> syn::Result<proc_macro2::Token
                     }
                 } else {
                     quote! {
-                        vec![unsafe { *<*const _>::from(self).cast::<u8>() }]
+                        ::std::vec![unsafe { *(self as *const Self).cast::<u8>() }]
                     }
                 },
                 quote! {
-                    match input[0] {
+                    match *input.first().ok_or(__sadby::SadbyError::UnexpectedToken)? {
                         #(#tokens_from)*
-                        _ => Err(SadbyError::UnexpectedToken),
+                        _ => ::core::result::Result::Err(__sadby::SadbyError::UnexpectedToken),
                     }
                 },
+                None,
             )
         }
         syn::Data::Struct(s) => {
+            // A single field is already written without a length prefix, transparent only makes
+            // sure it stays that way.
+            if container.transparent {
+                if s.fields.len() != 1 {
+                    return Err(Error::new(
+                        ast.span(),
+                        "#[sadby(transparent)] needs exactly one field",
+                    ));
+                }
+                if container.tagged || container.packed || container.version.is_some() {
+                    return Err(Error::new(
+                        ast.span(),
+                        "#[sadby(transparent)] can't be combined with tagged, packed or version",
+                    ));
+                }
+            }
+            if container.packed && container.tagged {
+                return Err(Error::new(
+                    ast.span(),
+                    "#[sadby(packed)] can't be combined with tagged",
+                ));
+            }
+
+            let fixed_size;
+
             let mut tokens_to = Vec::<TokenStream2>::new();
             let mut tokens_from = Vec::<TokenStream2>::new();
 
@@ -216,66 +447,44 @@ fn sadb_macro(input: proc_macro2::TokenStream) -> syn::Result<proc_macro2::Token
                     brace_token: _,
                     named,
                 }) => {
-                    let mut local_from = Vec::<TokenStream2>::new();
-                    let mut field_names = Vec::<Ident>::new();
-                    let mut first: TokenStream2 = Default::default();
-
-                    for field in named.iter() {
-                        handle_field(field, &mut local_from, &mut first, &mut field_names, None)?;
-                    }
+                    let fields = named
+                        .iter()
+                        .map(|field| handle_field(field, container, None))
+                        .collect::<syn::Result<Vec<_>>>()?;
+                    let members = fields.iter().map(|f| &f.field.ident);
+                    let field_names = fields.iter().map(|f| &f.name);
 
-                    let field = quote! { { #(#field_names, )* } };
+                    let field = quote! { { #(#members: #field_names, )* } };
 
-                    modify_sadb_tokens_struct(
-                        &named,
+                    fixed_size = modify_sadb_tokens_struct(
+                        named,
+                        container,
                         &mut tokens_from,
                         &mut tokens_to,
-                        &mut local_from,
-                        first,
+                        &fields,
                         field,
-                        &mut field_names,
                     )?;
                 }
                 syn::Fields::Unnamed(syn::FieldsUnnamed {
                     paren_token: _,
                     unnamed,
                 }) => {
-                    let mut local_from = Vec::<TokenStream2>::new();
-                    let mut last_name = 'a';
-                    let mut field_names = Vec::<Ident>::new();
-                    let mut first: TokenStream2 = Default::default();
-
-                    for field in unnamed.iter() {
-                        handle_field(
-                            field,
-                            &mut local_from,
-                            &mut first,
-                            &mut field_names,
-                            Some(&mut last_name),
-                        )?;
-
-                        if last_name == '{' {
-                            return Err(Error::new(
-                                field.span(),
-                                format!(
-                                    r#"Why the fuck you have so many unnamed fields in {}???
-                                       Advice: Use freaking named fields or better a struct"#,
-                                    ident
-                                ),
-                            ));
-                        }
-                    }
+                    let fields = unnamed
+                        .iter()
+                        .enumerate()
+                        .map(|(index, field)| handle_field(field, container, Some(index)))
+                        .collect::<syn::Result<Vec<_>>>()?;
+                    let field_names = fields.iter().map(|f| &f.name);
 
                     let field = quote! { ( #(#field_names, )* ) };
 
-                    modify_sadb_tokens_struct(
-                        &unnamed,
+                    fixed_size = modify_sadb_tokens_struct(
+                        unnamed,
+                        container,
                         &mut tokens_from,
                         &mut tokens_to,
-                        &mut local_from,
-                        first,
+                        &fields,
                         field,
-                        &mut field_names,
                     )?;
                 }
                 syn::Fields::Unit => {
@@ -285,133 +494,202 @@ fn sadb_macro(input: proc_macro2::TokenStream) -> syn::Result<proc_macro2::Token
 
             (
                 quote! {
-                    let mut buf = Vec::<u8>::new();
+                    let mut buf = ::std::vec::Vec::<u8>::new();
 
                     #(#tokens_to)*
 
                     buf
                 },
                 quote! { #(#tokens_from)* },
+                fixed_size,
             )
         }
         _ => return Err(Error::new(ast.span(), "Expected Enum or Struct")),
     };
 
-    Ok(quote! {
-        impl #impl_generics Sadby for #ident #type_generics #where_clause {
-            fn se_bytes(&self) -> Vec<u8> {
-                #complete_tokens_to
-            }
-            fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
-                #complete_tokens_from
-            }
-        }
-    })
+    Ok(tokens)
 }
 
-fn handle_field(
-    field: &syn::Field,
-    local_from: &mut Vec<TokenStream2>,
-    first: &mut TokenStream2,
-    field_names: &mut Vec<Ident>,
-    last_name: Option<&mut char>,
-) -> syn::Result<()> {
-    let ty = match &field.ty {
-        syn::Type::Path(syn::TypePath { qself: None, path }) => {
-            let path_iter = path.segments.clone().into_iter();
-            let path_last_segm = path_iter.clone().last();
-            if let Some(path_segm) = path_last_segm
-                && let syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments {
-                    colon2_token: _,
-                    lt_token: _,
-                    args,
-                    gt_token: _,
-                }) = &path_segm.arguments
-            {
-                let inner_type = match args.first().unwrap() {
-                    syn::GenericArgument::Type(syn::Type::Path(path)) => path.to_token_stream(),
-                    syn::GenericArgument::Type(syn::Type::Tuple(tuple)) => tuple.to_token_stream(),
-                    syn::GenericArgument::Type(syn::Type::Slice(slice)) => slice.to_token_stream(),
-                    syn::GenericArgument::Type(syn::Type::Array(array)) => array.to_token_stream(),
-                    _ => {
-                        return Err(Error::new(
-                            args.span(),
-                            "Expected Type, Tuple, Slice or Array",
-                        ));
-                    }
-                };
+fn handle_field<'a>(
+    field: &'a syn::Field,
+    container: &ContainerAttrs,
+    index: Option<usize>,
+) -> syn::Result<FieldInfo<'a>> {
+    let mut attrs = FieldAttrs::parse(&field.attrs)?;
+    attrs.endian = attrs.endian.or(container.endian);
+
+    if let Some(id) = &attrs.id
+        && !container.tagged
+    {
+        return Err(Error::new(
+            id.span(),
+            "#[sadby(id)] needs #[sadby(tagged)] on the container",
+        ));
+    }
 
-                let p_ident = path_segm.ident;
-                let b_path_len = path_iter.len();
-                let mut b_path_vec = Vec::<TokenStream2>::new();
-                for (i, p) in path_iter.enumerate() {
-                    if i == b_path_len - 1 {
-                        break;
-                    }
-                    b_path_vec.push(p.to_token_stream());
-                }
-                quote! { #(#b_path_vec :: )* #p_ident :: < #inner_type > }
-            } else {
-                path.to_token_stream()
-            }
+    if let Some(since) = &attrs.since {
+        let Some(version) = &container.version else {
+            return Err(Error::new(
+                since.span(),
+                "#[sadby(since)] needs #[sadby(version)] on the container",
+            ));
+        };
+        let container_version = version.base10_parse::<u8>()?;
+        if since.base10_parse::<u8>()? > container_version {
+            return Err(Error::new(
+                since.span(),
+                format!("#[sadby(since)] is newer than the container version {container_version}"),
+            ));
+        }
+    }
+
+    if let Some(compress) = &attrs.compress {
+        if container.packed {
+            return Err(Error::new(
+                compress.span(),
+                "#[sadby(compress)] can't be used in #[sadby(packed)] containers",
+            ));
+        }
+        if attrs.bits.is_some() {
+            return Err(Error::new(
+                compress.span(),
+                "#[sadby(compress)] can't be combined with #[sadby(bits)]",
+            ));
+        }
+    }
+
+    if let Some(varint) = &attrs.varint {
+        if container.packed {
+            return Err(Error::new(
+                varint.span(),
+                "#[sadby(varint)] can't be used in #[sadby(packed)] containers",
+            ));
+        }
+        if attrs.bits.is_some() {
+            return Err(Error::new(
+                varint.span(),
+                "#[sadby(varint)] can't be combined with #[sadby(bits)]",
+            ));
+        }
+    }
+
+    if let Some(bits) = &attrs.bits {
+        if attrs.constant.is_some() {
+            return Err(Error::new(
+                bits.span(),
+                "#[sadby(bits)] can't be combined with #[sadby(constant)]",
+            ));
         }
-        syn::Type::Array(t) => quote! { <#t> },
-        _ => {
+        if container.tagged {
             return Err(Error::new(
-                field.ty.span(),
-                format!(
-                    "WTF are you trying to use in {}",
-                    field.ty.clone().into_token_stream()
-                ),
+                bits.span(),
+                "#[sadby(bits)] can't be used in #[sadby(tagged)] containers",
             ));
         }
+        if attrs.since.is_some() {
+            return Err(Error::new(
+                bits.span(),
+                "#[sadby(bits)] can't be combined with #[sadby(since)]",
+            ));
+        }
+    }
+
+    // Mixed site hygiene keeps these from clashing with anything the user names, the prefix with
+    // the other locals of the generated code, so a field can be named `input` or `endian`.
+    let name = match index {
+        Some(i) => Ident::new(&format!("__field{i}"), Span::mixed_site()),
+        None => Ident::new(
+            &format!("__field_{}", field.ident.as_ref().unwrap().unraw()),
+            Span::mixed_site(),
+        ),
     };
 
-    let name: Ident;
-    match last_name {
-        Some(c) => {
-            let n = *c;
-            *c = ((*c as u8) + 1) as char;
+    Ok(FieldInfo { field, name, attrs })
+}
+
+/// The discriminant of `variant`, `next` unless it's given one explicitly.
+fn discriminant(variant: &syn::Variant, next: u8) -> syn::Result<u8> {
+    match &variant.discriminant {
+        Some((
+            _,
+            Expr::Lit(ExprLit {
+                attrs: _,
+                lit: Lit::Byte(b),
+            }),
+        )) => Ok(b.value()),
+        Some((
+            _,
+            Expr::Lit(ExprLit {
+                attrs: _,
+                lit: Lit::Char(ch),
+            }),
+        )) => Ok(ch.value() as u8),
+        Some((
+            _,
+            Expr::Lit(ExprLit {
+                attrs: _,
+                lit: Lit::Int(i),
+            }),
+        )) => Ok(i.base10_digits().parse::<u8>().unwrap()),
+
+        Some((_, expr)) => Err(Error::new(expr.span(), "Expected int, byte or char")),
+        None => Ok(next),
+    }
+}
 
-            name = Ident::new(n.encode_utf8(&mut [0u8; 1]), Span::call_site());
+/// Runs the container's `#[sadby(validate)]` check on the value `from` decodes, whichever way it
+/// was decoded.
+fn validated(container: &ContainerAttrs, from: TokenStream2) -> TokenStream2 {
+    let Some(validate) = &container.validate else {
+        return from;
+    };
 
-            if field_names.is_empty() {
-                *first = quote! {
-                    ( #ty::de_bytes(&input[1..])? )
-                };
-            }
+    quote! {
+        let value: Self = { #from }?;
+        if let ::core::result::Result::Err(e) = #validate(&value) {
+            return ::core::result::Result::Err(__sadby::SadbyError::Invalid {
+                path: ::std::string::String::new(),
+                message: ::std::string::ToString::to_string(&e),
+            });
         }
-        None => {
-            name = field.ident.clone().unwrap();
+        ::core::result::Result::Ok(value)
+    }
+}
 
-            if field_names.is_empty() {
-                *first = quote! {
-                    { #name : #ty::de_bytes(&input[1..])? }
-                }
-            }
+/// Adds `extra` bytes to a fixed size expression, no fixed size stays that way.
+fn add_fixed_size(size: TokenStream2, extra: TokenStream2) -> TokenStream2 {
+    quote! {
+        match #size {
+            ::core::option::Option::Some(size) => ::core::option::Option::Some(#extra + size),
+            ::core::option::Option::None => ::core::option::Option::None,
         }
-    };
+    }
+}
 
-    // TODO: make it smarter. Make it able to hardcode current and next values for types with
-    //       always stable* size (like [u8; 2], f32, u64 etc.)
-    local_from.push(quote! {
-        let #name = #ty::de_bytes(&input[current..=next])?;
-    });
-    local_from.push(quote! {
-        current = next + 1;
-        next = current + input[current] as usize;
-        current += 1;
-        //dbg!(&current, &next);
-    });
+/// The byte order argument of the generated `*_endian` methods.
+pub(crate) fn endian_ident() -> Ident {
+    Ident::new("endian", Span::mixed_site())
+}
+
+/// The writer argument of the generated `se_described`.
+pub(crate) fn writer_ident() -> Ident {
+    Ident::new("writer", Span::mixed_site())
+}
 
-    field_names.push(name);
+/// The reader argument of the generated `de_described`.
+pub(crate) fn reader_ident() -> Ident {
+    Ident::new("reader", Span::mixed_site())
+}
 
-    Ok(())
+/// The local holding the version a `#[sadby(version)]` payload was written with.
+pub(crate) fn version_ident() -> Ident {
+    Ident::new("version", Span::mixed_site())
 }
 
 #[allow(clippy::too_many_arguments)]
-fn modify_sadb_tokens_enum<T: Spanned>(
-    un_named_field: &T,
+fn modify_sadb_tokens_enum(
+    un_named_field: &Punctuated<syn::Field, Token![,]>,
+    container: &ContainerAttrs,
     v_ident: &syn::Ident,
 
     expression: u8,
@@ -419,103 +697,109 @@ fn modify_sadb_tokens_enum<T: Spanned>(
     tokens_from: &mut Vec<TokenStream2>,
     tokens_to: &mut Vec<TokenStream2>,
 
-    local_from: &mut Vec<TokenStream2>,
-    first: TokenStream2,
-
+    fields: &[FieldInfo],
     field: TokenStream2,
-    field_names: &mut Vec<Ident>,
 ) -> syn::Result<()> {
-    if field_names.is_empty() {
+    if fields.is_empty() {
         return Err(Error::new(
             un_named_field.span(),
             format!("Put something in '{}'", v_ident),
         ));
     }
 
-    if field_names.len() == 1 {
-        tokens_to.push(quote! {
-            Self::#v_ident #field => {
-                #(
-                    buf.append(&mut #field_names.se_bytes());
-                )*
-            }
-        });
-        tokens_from.push(quote! {
-            #expression => Ok( Self::#v_ident #first ),
-        });
+    let LayoutTokens { to, from } = if container.tagged {
+        tagged_tokens(fields, 1)?
     } else {
-        let _ = local_from.pop();
-        tokens_to.push(quote! {
-            Self::#v_ident #field => {
-                #(
-                    let mut #field_names = #field_names.se_bytes();
-                    buf.push(#field_names.len() as u8);
-                    buf.append(&mut #field_names);
-                )*
-            }
-        });
-        tokens_from.push(quote! {
-            #expression => {
-                let mut current = 2usize;
-                let mut next = 1 + input[1] as usize;
+        positional_tokens(fields, 1, container)?
+    };
+
+    tokens_to.push(quote! {
+        Self::#v_ident #field => {
+            #to
+        }
+    });
+    let validate = fields.iter().filter_map(FieldInfo::validate);
 
-                #(#local_from)*
+    let name = v_ident.to_string();
 
-                Ok(Self::#v_ident #field )
-            }
-        });
-    };
+    tokens_from.push(quote! {
+        #expression => {
+            __sadby::trace::variant(#name, input);
+            #from
+            #(#validate)*
+
+            ::core::result::Result::Ok(Self::#v_ident #field )
+        }
+    });
 
     Ok(())
 }
 
-#[allow(clippy::too_many_arguments)]
-fn modify_sadb_tokens_struct<T: Spanned>(
-    un_named_field: &T,
+fn modify_sadb_tokens_struct(
+    un_named_field: &Punctuated<syn::Field, Token![,]>,
+    container: &ContainerAttrs,
 
     tokens_from: &mut Vec<TokenStream2>,
     tokens_to: &mut Vec<TokenStream2>,
 
-    local_from: &mut Vec<TokenStream2>,
-    first: TokenStream2,
-
+    fields: &[FieldInfo],
     field: TokenStream2,
-    field_names: &mut Vec<Ident>,
-) -> syn::Result<()> {
-    if field_names.is_empty() {
+) -> syn::Result<Option<TokenStream2>> {
+    if fields.is_empty() {
         return Err(Error::new(
             un_named_field.span(),
             "Put something in the struct",
         ));
     }
 
-    if field_names.len() == 1 {
-        tokens_to.push(quote! {
-            #(
-                buf.append(&mut self.#field_names.se_bytes());
-            )*
-        });
-        tokens_from.push(quote! {
-            Ok( Self #first )
-        });
+    let LayoutTokens { to, from } = if container.tagged {
+        tagged_tokens(fields, 0)?
+    } else if container.packed {
+        packed_tokens(fields, 0)?
     } else {
-        let _ = local_from.pop();
-        tokens_to.push(quote! {
-            #(
-                let mut #field_names = self.#field_names.se_bytes();
-                buf.push(#field_names.len() as u8);
-                buf.append(&mut #field_names);
-            )*
-        });
-        tokens_from.push(quote! {
-            let mut current = 1usize;
-            let mut next = input[0] as usize;
-
-            #(#local_from)*
-
-            Ok(Self #field )
-        });
+        positional_tokens(fields, 0, container)?
     };
 
-    Ok(())
+    let fixed_size = if container.packed {
+        let size = packed_size(fields);
+        Some(quote! { ::core::option::Option::Some(#size) })
+    } else if container.transparent {
+        // Varints and compressed fields vary in size whatever the type, bit fields take as many
+        // bytes as their bits need.
+        let info = &fields[0];
+        if info.attrs.varint.is_some() || info.attrs.compress.is_some() {
+            None
+        } else if info.attrs.bits.is_some() {
+            let size = packed_size(fields);
+            Some(quote! { ::core::option::Option::Some(#size) })
+        } else {
+            let ty = &info.field.ty;
+            Some(quote! { <#ty as __sadby::Sadby>::FIXED_SIZE })
+        }
+    } else {
+        None
+    };
+
+    tokens_to.push(quote! {
+        let Self #field = self;
+        #to
+    });
+    let validate = fields.iter().filter_map(FieldInfo::validate);
+
+    tokens_from.push(quote! {
+        #from
+        #(#validate)*
+
+        ::core::result::Result::Ok(Self #field )
+    });
+
+    Ok(fixed_size)
+}
+
+/// Statements encoding and decoding a list of fields in one of the layouts.
+pub(crate) struct LayoutTokens {
+    /// Statements appending every field to `buf`, the fields are bound to references by name.
+    pub to: TokenStream2,
+    /// Statements binding every field by name, decoded from `input`.
+    pub from: TokenStream2,
 }
@@ -0,0 +1,91 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use crate::LayoutTokens;
+use crate::field::FieldInfo;
+use crate::positional::Unit;
+use crate::positional::units;
+
+impl Unit<'_, '_> {
+    /// Const expression with the encoded size of the unit, failing to compile without one.
+    pub(crate) fn fixed_size(&self) -> TokenStream2 {
+        match self {
+            Unit::Field(info) => {
+                let ty = &info.field.ty;
+                let message = format!(
+                    "#[sadby(packed)] field `{}` has no fixed size",
+                    quote! { #ty }
+                );
+
+                quote! {
+                    match <#ty as __sadby::Sadby>::FIXED_SIZE {
+                        ::core::option::Option::Some(size) => size,
+                        ::core::option::Option::None => ::core::panic!(#message),
+                    }
+                }
+            }
+            Unit::Bits(group) => {
+                let bits = group
+                    .iter()
+                    .map(|info| info.attrs.bits.as_ref().unwrap().base10_parse::<usize>())
+                    .sum::<syn::Result<usize>>()
+                    .unwrap();
+                let size = bits.div_ceil(8);
+
+                quote! { #size }
+            }
+        }
+    }
+}
+
+/// Const expression with the encoded size of all the fields.
+pub(crate) fn packed_size(fields: &[FieldInfo]) -> TokenStream2 {
+    let sizes = units(fields)
+        .iter()
+        .map(Unit::fixed_size)
+        .collect::<Vec<_>>();
+
+    quote! { 0usize #( + #sizes )* }
+}
+
+/// The `#[sadby(packed)]` layout: fields back to back without any framing.
+pub(crate) fn packed_tokens(fields: &[FieldInfo], offset: usize) -> syn::Result<LayoutTokens> {
+    let units = units(fields);
+
+    let to = units.iter().map(|unit| {
+        let se = unit.se();
+
+        quote! {
+            buf.append(&mut #se);
+        }
+    });
+    let from = units.iter().map(|unit| {
+        let pattern = unit.pattern();
+        let size = unit.fixed_size();
+        let de = unit.de(quote! {
+            input
+                .get(current..next)
+                .ok_or(__sadby::SadbyError::UnexpectedToken)?
+        });
+        let read = unit.since(quote! {{
+            let next = current + const { #size };
+            let value = #de;
+            current = next;
+            value
+        }});
+
+        quote! {
+            let #pattern = #read;
+        }
+    });
+
+    Ok(LayoutTokens {
+        to: quote! { #(#to)* },
+        from: quote! {
+            let mut current = #offset;
+
+            #(#from)*
+            let _ = current;
+        },
+    })
+}
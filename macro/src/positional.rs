@@ -0,0 +1,170 @@
+use proc_macro2::Span;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::Ident;
+
+use crate::LayoutTokens;
+use crate::attr::ContainerAttrs;
+use crate::field::FieldInfo;
+
+/// What ends up between two length prefixes.
+pub(crate) enum Unit<'a, 'b> {
+    Field(&'a FieldInfo<'b>),
+    /// Neighbouring `#[sadby(bits = N)]` fields sharing one bit stream.
+    Bits(Vec<&'a FieldInfo<'b>>),
+}
+
+impl Unit<'_, '_> {
+    /// Expression encoding the unit.
+    pub(crate) fn se(&self) -> TokenStream2 {
+        match self {
+            Unit::Field(info) => info.se(),
+            Unit::Bits(group) => {
+                let names = group.iter().map(|info| &info.name);
+                let bits = group.iter().map(|info| &info.attrs.bits);
+                let types = group.iter().map(|info| &info.field.ty);
+
+                quote! {{
+                    let mut writer = __sadby::bits::BitWriter::new();
+                    #(
+                        writer.write::<#bits, #types>(#names)?;
+                    )*
+                    writer.finish()
+                }}
+            }
+        }
+    }
+
+    /// Pattern binding the unit's fields by name.
+    pub(crate) fn pattern(&self) -> TokenStream2 {
+        match self {
+            Unit::Field(info) => {
+                let name = &info.name;
+                quote! { #name }
+            }
+            Unit::Bits(group) => {
+                let names = group.iter().map(|info| &info.name);
+                quote! { ( #(#names, )* ) }
+            }
+        }
+    }
+
+    /// Expression decoding the unit out of `bytes`.
+    pub(crate) fn de(&self, bytes: TokenStream2) -> TokenStream2 {
+        match self {
+            Unit::Field(info) => info.de(bytes),
+            Unit::Bits(group) => {
+                let bits = group.iter().map(|info| &info.attrs.bits);
+                let types = group.iter().map(|info| &info.field.ty);
+                let fields = group.iter().map(|info| info.display_name());
+                let unit_bytes = Ident::new("unit_bytes", Span::mixed_site());
+
+                quote! {{
+                    let #unit_bytes = #bytes;
+                    let mut reader = __sadby::bits::BitReader::new(#unit_bytes);
+                    ( #({
+                        __sadby::trace::field(#fields, #unit_bytes);
+                        reader.read::<#bits, #types>()?
+                    }, )* )
+                }}
+            }
+        }
+    }
+
+    /// Wraps `read` in the `#[sadby(since)]` check of the field.
+    pub(crate) fn since(&self, read: TokenStream2) -> TokenStream2 {
+        match self {
+            Unit::Field(info) => info.since(read),
+            Unit::Bits(_) => read,
+        }
+    }
+}
+
+/// Groups neighbouring `#[sadby(bits = N)]` fields together.
+pub(crate) fn units<'a, 'b>(fields: &'a [FieldInfo<'b>]) -> Vec<Unit<'a, 'b>> {
+    let mut units = Vec::<Unit>::new();
+    for info in fields {
+        match (info.attrs.bits.is_some(), units.last_mut()) {
+            (true, Some(Unit::Bits(group))) => group.push(info),
+            (true, _) => units.push(Unit::Bits(vec![info])),
+            (false, _) => units.push(Unit::Field(info)),
+        }
+    }
+
+    units
+}
+
+/// The default layout: fields in declaration order, each prefixed with its length, unless it's
+/// the only one in a container without a version.
+pub(crate) fn positional_tokens(
+    fields: &[FieldInfo],
+    offset: usize,
+    container: &ContainerAttrs,
+) -> syn::Result<LayoutTokens> {
+    let units = units(fields);
+
+    // A lone unit takes up the whole input, there's nothing to separate it from. Versioned
+    // containers prefix it anyway, so adding a field later doesn't change how it's framed.
+    if let [unit] = units.as_slice()
+        && container.version.is_none()
+    {
+        let se = unit.se();
+        let pattern = unit.pattern();
+        let read = unit.since(unit.de(quote! { &input[#offset..] }));
+
+        return Ok(LayoutTokens {
+            to: quote! {
+                buf.append(&mut #se);
+            },
+            from: quote! {
+                let #pattern = #read;
+            },
+        });
+    }
+
+    // TODO: make it smarter. Make it able to hardcode current and next values for types with
+    //       always stable* size (like [u8; 2], f32, u64 etc.)
+    let to = units.iter().map(|unit| {
+        let se = unit.se();
+
+        quote! {{
+            let mut bytes = #se;
+            let len = <u8 as ::core::convert::TryFrom<usize>>::try_from(bytes.len())
+                .map_err(|_| __sadby::SadbyError::OutOfRange)?;
+            buf.push(len);
+            buf.append(&mut bytes);
+        }}
+    });
+    let from = units.iter().map(|unit| {
+        let pattern = unit.pattern();
+        let de = unit.de(quote! {
+            input
+                .get(current + 1..=next)
+                .ok_or(__sadby::SadbyError::UnexpectedToken)?
+        });
+        let read = unit.since(quote! {{
+            let next = current
+                + *input
+                    .get(current)
+                    .ok_or(__sadby::SadbyError::UnexpectedToken)? as usize;
+            __sadby::trace::length(input, current);
+            let value = #de;
+            current = next + 1;
+            value
+        }});
+
+        quote! {
+            let #pattern = #read;
+        }
+    });
+
+    Ok(LayoutTokens {
+        to: quote! { #(#to)* },
+        from: quote! {
+            let mut current = #offset;
+
+            #(#from)*
+            let _ = current;
+        },
+    })
+}
@@ -0,0 +1,160 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::DeriveInput;
+use syn::Error;
+use syn::spanned::Spanned;
+
+use crate::attr::ContainerAttrs;
+use crate::discriminant;
+use crate::field::FieldInfo;
+use crate::field::infos;
+
+/// Body of `SadbySchema::schema`.
+pub(crate) fn schema_tokens(
+    ast: &DeriveInput,
+    container: &ContainerAttrs,
+) -> syn::Result<TokenStream2> {
+    let name = ast.ident.to_string();
+
+    let magic = option(
+        container
+            .magic
+            .as_ref()
+            .map(|magic| quote! { #magic.to_vec() }),
+    );
+    let version = option(
+        container
+            .version
+            .as_ref()
+            .map(|version| quote! { #version }),
+    );
+    let checksum = option(
+        container
+            .checksum
+            .as_ref()
+            .map(|checksum| quote! { #checksum }),
+    );
+
+    let body = match (&container.into, &ast.data) {
+        (Some(into), _) => quote! {
+            __sadby::schema::Body::Proxy(::std::boxed::Box::new(
+                <#into as __sadby::schema::SadbySchema>::schema(),
+            ))
+        },
+        (None, syn::Data::Enum(e)) => {
+            let layout = if container.tagged {
+                quote! { Tagged }
+            } else {
+                quote! { Positional }
+            };
+
+            let mut variants = Vec::<TokenStream2>::new();
+            let mut expression = 0u8;
+            for variant in e.variants.iter() {
+                expression = discriminant(variant, expression)?;
+
+                let v_name = variant.ident.to_string();
+                let fields = infos(&variant.fields, container)?;
+                let fields = fields.iter().map(field_tokens);
+
+                variants.push(quote! {
+                    __sadby::schema::Variant {
+                        name: ::std::string::ToString::to_string(#v_name),
+                        discriminant: #expression,
+                        fields: ::std::vec![#(#fields),*],
+                    }
+                });
+                expression = expression.wrapping_add(1);
+            }
+
+            quote! {
+                __sadby::schema::Body::Enum {
+                    layout: __sadby::schema::Layout::#layout,
+                    variants: ::std::vec![#(#variants),*],
+                }
+            }
+        }
+        (None, syn::Data::Struct(s)) => {
+            let layout = if container.transparent {
+                quote! { Transparent }
+            } else if container.packed {
+                quote! { Packed }
+            } else if container.tagged {
+                quote! { Tagged }
+            } else {
+                quote! { Positional }
+            };
+
+            let fields = infos(&s.fields, container)?;
+            let fields = fields.iter().map(field_tokens);
+
+            quote! {
+                __sadby::schema::Body::Struct {
+                    layout: __sadby::schema::Layout::#layout,
+                    fields: ::std::vec![#(#fields),*],
+                }
+            }
+        }
+        _ => return Err(Error::new(ast.span(), "Expected Enum or Struct")),
+    };
+
+    let schema = quote! {
+        __sadby::schema::container::<Self>(#name, || __sadby::schema::Container {
+            name: ::std::string::ToString::to_string(#name),
+            magic: #magic,
+            version: #version,
+            checksum: #checksum,
+            body: #body,
+        })
+    };
+
+    // Fields already carry the container's byte order, this covers the checksum and the proxy.
+    Ok(match &container.endian {
+        Some(endian) => quote! { #schema.endian(#endian) },
+        None => schema,
+    })
+}
+
+/// A `schema::Field`, with the schema of its type wrapped in whatever its attributes change about
+/// the encoding.
+fn field_tokens(info: &FieldInfo) -> TokenStream2 {
+    let ty = &info.field.ty;
+    let name = info.display_name();
+    let attrs = &info.attrs;
+
+    let mut schema = quote! { <#ty as __sadby::schema::SadbySchema>::schema() };
+    // Bit fields and varints don't care about the byte order.
+    if attrs.varint.is_some() {
+        schema = quote! { #schema.varint() };
+    } else if let Some(endian) = &attrs.endian
+        && attrs.bits.is_none()
+    {
+        schema = quote! { #schema.endian(#endian) };
+    }
+    if attrs.compress.is_some() {
+        schema = quote! { #schema.compressed() };
+    }
+
+    let id = option(attrs.id.as_ref().map(|id| quote! { #id }));
+    let since = option(attrs.since.as_ref().map(|since| quote! { #since }));
+    let bits = option(attrs.bits.as_ref().map(|bits| quote! { #bits }));
+    let constant = attrs.constant.is_some();
+
+    quote! {
+        __sadby::schema::Field {
+            name: ::std::string::ToString::to_string(#name),
+            id: #id,
+            since: #since,
+            bits: #bits,
+            constant: #constant,
+            schema: #schema,
+        }
+    }
+}
+
+fn option(value: Option<TokenStream2>) -> TokenStream2 {
+    match value {
+        Some(value) => quote! { ::core::option::Option::Some(#value) },
+        None => quote! { ::core::option::Option::None },
+    }
+}
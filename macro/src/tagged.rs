@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use quote::quote;
+use syn::Error;
+
+use crate::LayoutTokens;
+use crate::field::FieldInfo;
+
+/// The `#[sadby(tagged)]` layout, every field is written as `[id, len, bytes..]`.
+pub(crate) fn tagged_tokens(fields: &[FieldInfo], offset: usize) -> syn::Result<LayoutTokens> {
+    let mut ids = Vec::<&syn::LitInt>::new();
+    let mut seen = HashMap::<u8, &syn::Field>::new();
+
+    for info in fields {
+        let Some(id) = &info.attrs.id else {
+            return Err(Error::new_spanned(
+                info.field,
+                "Every field of a #[sadby(tagged)] container needs #[sadby(id = N)]",
+            ));
+        };
+
+        let value = id.base10_parse::<u8>()?;
+        if let Some(previous) = seen.insert(value, info.field) {
+            let mut error = Error::new(id.span(), format!("Duplicate #[sadby(id = {value})]"));
+            error.combine(Error::new_spanned(previous, "First used here"));
+            return Err(error);
+        }
+
+        ids.push(id);
+    }
+
+    let field_names = fields.iter().map(|info| &info.name).collect::<Vec<_>>();
+    let types = fields.iter().map(|info| &info.field.ty);
+    let se = fields.iter().map(|info| info.se());
+    let de = fields.iter().map(|info| info.de(quote! { bytes }));
+
+    let to = quote! {
+        #({
+            let mut bytes = #se;
+            let len = <u8 as ::core::convert::TryFrom<usize>>::try_from(bytes.len())
+                .map_err(|_| __sadby::SadbyError::OutOfRange)?;
+            buf.push(#ids);
+            buf.push(len);
+            buf.append(&mut bytes);
+        })*
+    };
+
+    // Unknown ids come from newer peers and are skipped, missing ones from older peers and are
+    // left at their defaults.
+    let from = quote! {
+        #(
+            let mut #field_names: ::core::option::Option<#types> = ::core::option::Option::None;
+        )*
+
+        let mut current = #offset;
+        while current < input.len() {
+            // A message cut short fails like any other malformed input, newer peers included.
+            let len = *input
+                .get(current + 1)
+                .ok_or(__sadby::SadbyError::UnexpectedToken)?;
+            let next = current + 1 + len as usize;
+            __sadby::trace::length(input, current + 1);
+            let bytes = input
+                .get(current + 2..=next)
+                .ok_or(__sadby::SadbyError::UnexpectedToken)?;
+
+            match input[current] {
+                #(
+                    #ids => #field_names = ::core::option::Option::Some(#de),
+                )*
+                _ => {}
+            }
+
+            current = next + 1;
+        }
+
+        #(
+            let #field_names = #field_names.unwrap_or_default();
+        )*
+    };
+
+    Ok(LayoutTokens { to, from })
+}
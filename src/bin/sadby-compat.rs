@@ -0,0 +1,45 @@
+//! Compares two schemas exported with `Schema::to_json`, e.g. a checked in snapshot and the
+//! current one, and lists the changes that break reading payloads of one with the other.
+//!
+//! Exits with 0 when they're compatible, 1 when they aren't and 2 when they couldn't be read.
+
+use std::process::ExitCode;
+
+use sadby::SadbyError;
+use sadby::schema::Schema;
+use sadby::schema::compat;
+
+fn read(path: &str) -> Result<Schema, String> {
+    let input = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+    Schema::from_json(&input).map_err(|e| match e {
+        SadbyError::Custom(message) => format!("{path}: {message}"),
+        e => format!("{path}: {e:?}"),
+    })
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let [old, new] = args.as_slice() else {
+        eprintln!("Usage: sadby-compat OLD.json NEW.json");
+        return ExitCode::from(2);
+    };
+
+    let (old, new) = match (read(old), read(new)) {
+        (Ok(old), Ok(new)) => (old, new),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let breaks = compat::check(&old, &new);
+    if breaks.is_empty() {
+        println!("compatible");
+        return ExitCode::SUCCESS;
+    }
+
+    for b in &breaks {
+        println!("{b}");
+    }
+    ExitCode::from(1)
+}
@@ -0,0 +1,217 @@
+//! Reads and writes payloads with a schema exported with `Schema::to_json`, for looking into
+//! captured payloads without the Rust types.
+//!
+//! Bytes are read from and written to files as they are, or as hex text with `--hex`. Input is
+//! read from stdin when no file is given or it's `-`. Exits with 0 on success, 1 when the payload
+//! or document doesn't fit the schema and 2 on any other error.
+
+use std::io::{Read, Write};
+use std::process::ExitCode;
+
+use sadby::SadbyError;
+use sadby::schema::{Annotation, Schema};
+
+const USAGE: &str = "\
+Usage: sadby [--hex] COMMAND
+
+Commands:
+  encode SCHEMA.json [DOCUMENT.json]   Encodes a JSON document into bytes
+  decode SCHEMA.json [PAYLOAD]         Decodes bytes into a JSON document
+  dump SCHEMA.json [PAYLOAD]           Prints an annotated hexdump of the bytes
+  convert FROM.json TO.json [PAYLOAD]  Decodes bytes with one schema and encodes them with another
+
+Options:
+  --hex  Read and write bytes as hex text instead of raw";
+
+/// Failures, with the exit code they end with.
+enum Failure {
+    Usage,
+    Io(String),
+    Payload(SadbyError),
+}
+
+fn message(e: &SadbyError) -> String {
+    match e {
+        SadbyError::Custom(message) => message.clone(),
+        e => format!("{e:?}"),
+    }
+}
+
+fn schema(path: &str) -> Result<Schema, Failure> {
+    let input = std::fs::read_to_string(path).map_err(|e| Failure::Io(format!("{path}: {e}")))?;
+    Schema::from_json(&input).map_err(|e| Failure::Io(format!("{path}: {}", message(&e))))
+}
+
+fn input(path: Option<&String>) -> Result<Vec<u8>, Failure> {
+    match path.map(String::as_str) {
+        None | Some("-") => {
+            let mut input = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut input)
+                .map_err(|e| Failure::Io(format!("stdin: {e}")))?;
+            Ok(input)
+        }
+        Some(path) => std::fs::read(path).map_err(|e| Failure::Io(format!("{path}: {e}"))),
+    }
+}
+
+/// The payload in the input, as hex text if `hex`, ignoring whitespace.
+fn payload(path: Option<&String>, hex: bool) -> Result<Vec<u8>, Failure> {
+    let input = input(path)?;
+    if !hex {
+        return Ok(input);
+    }
+
+    let digits = input
+        .into_iter()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect::<Vec<_>>();
+    if !digits.len().is_multiple_of(2) {
+        return Err(Failure::Io("odd number of hex digits".to_owned()));
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| Failure::Io(format!("bad hex `{}`", String::from_utf8_lossy(pair))))
+        })
+        .collect()
+}
+
+fn write_payload(payload: &[u8], hex: bool) -> Result<(), Failure> {
+    match hex {
+        true => print(&hex_bytes(payload, "")),
+        false => std::io::stdout()
+            .write_all(payload)
+            .map_err(|e| Failure::Io(format!("stdout: {e}"))),
+    }
+}
+
+/// Like `println!`, without panicking when stdout is closed, e.g. piped into `head`.
+fn print(text: &str) -> Result<(), Failure> {
+    writeln!(std::io::stdout(), "{text}").map_err(|e| Failure::Io(format!("stdout: {e}")))
+}
+
+fn hex_bytes(bytes: &[u8], separator: &str) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// One line per annotation, indented by how deep it's nested, with bytes nothing claimed marked.
+fn dump(payload: &[u8], annotations: &[Annotation]) -> Vec<String> {
+    // Bytes nothing was read from, e.g. past where decoding failed. Annotations are sorted, so
+    // one with none nested in it ends before the next starts.
+    let mut covered = vec![false; payload.len()];
+    for (i, annotation) in annotations.iter().enumerate() {
+        let innermost = annotations
+            .get(i + 1)
+            .is_none_or(|next| next.range.start >= annotation.range.end);
+        if innermost {
+            covered[annotation.range.clone()].fill(true);
+        }
+    }
+    let mut lines = annotations
+        .iter()
+        .map(|a| (a.range.clone(), Some(a)))
+        .collect::<Vec<_>>();
+    let mut start = 0;
+    while start < payload.len() {
+        let end = (start..payload.len())
+            .find(|&i| covered[i] != covered[start])
+            .unwrap_or(payload.len());
+        if !covered[start] {
+            lines.push((start..end, None));
+        }
+        start = end;
+    }
+    lines.sort_by_key(|(range, _)| (range.start, std::cmp::Reverse(range.end)));
+
+    let mut open = Vec::<usize>::new();
+    lines
+        .into_iter()
+        .map(|(range, annotation)| {
+            open.retain(|&end| end > range.start);
+            let indent = "  ".repeat(open.len());
+            open.push(range.end);
+
+            let bytes = &payload[range.clone()];
+            let hex = match bytes.len() {
+                0..=16 => hex_bytes(bytes, " "),
+                len => format!("{} .. ({len} bytes)", hex_bytes(&bytes[..10], " ")),
+            };
+            let what = match annotation {
+                Some(a) if a.path.is_empty() => a.note.clone(),
+                Some(a) => format!("{}: {}", a.path, a.note),
+                None => "unread".to_owned(),
+            };
+            format!("{:06x}  {hex:<47}  {indent}{what}", range.start)
+        })
+        .collect()
+}
+
+fn run(args: &[String]) -> Result<(), Failure> {
+    let hex = args.iter().any(|arg| arg == "--hex");
+    let args = args
+        .iter()
+        .filter(|arg| *arg != "--hex")
+        .collect::<Vec<_>>();
+
+    match args.as_slice() {
+        [command, path, rest @ ..] if *command == "encode" && rest.len() <= 1 => {
+            let schema = schema(path)?;
+            let document = input(rest.first().copied())?;
+            let document = String::from_utf8(document)
+                .map_err(|_| Failure::Io("the document isn't UTF-8".to_owned()))?;
+            let value = schema
+                .value_from_json(&document)
+                .map_err(Failure::Payload)?;
+            write_payload(&schema.encode(&value).map_err(Failure::Payload)?, hex)
+        }
+        [command, path, rest @ ..] if *command == "decode" && rest.len() <= 1 => {
+            let schema = schema(path)?;
+            let value = schema
+                .decode(&payload(rest.first().copied(), hex)?)
+                .map_err(Failure::Payload)?;
+            print(&schema.value_to_json(&value))
+        }
+        [command, path, rest @ ..] if *command == "dump" && rest.len() <= 1 => {
+            let schema = schema(path)?;
+            let payload = payload(rest.first().copied(), hex)?;
+            let (annotations, value) = schema.annotate(&payload);
+            print(&dump(&payload, &annotations).join("\n"))?;
+            value.map(drop).map_err(Failure::Payload)
+        }
+        [command, from, to, rest @ ..] if *command == "convert" && rest.len() <= 1 => {
+            let (from, to) = (schema(from)?, schema(to)?);
+            let value = from
+                .decode(&payload(rest.first().copied(), hex)?)
+                .map_err(Failure::Payload)?;
+            write_payload(&to.encode(&value).map_err(Failure::Payload)?, hex)
+        }
+        _ => Err(Failure::Usage),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Failure::Usage) => {
+            eprintln!("{USAGE}");
+            ExitCode::from(2)
+        }
+        Err(Failure::Io(e)) => {
+            eprintln!("{e}");
+            ExitCode::from(2)
+        }
+        Err(Failure::Payload(e)) => {
+            eprintln!("error: {}", message(&e));
+            ExitCode::from(1)
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Bit streams behind `#[sadby(bits = N)]` fields. Values are written least significant bit
+//! first, starting at the least significant bit of each byte.
+
+use super::*;
+
+/// Types that can be packed into a handful of bits.
+pub trait Bits: Sized {
+    /// Width of the type, no field of it can be packed into more than this.
+    const BITS: u32;
+
+    /// The value as two's complement, only the low `width` bits are kept.
+    fn to_raw(&self) -> u64;
+    /// Inverse of `to_raw`, `raw` never has more than the low `width` bits set.
+    fn from_raw(raw: u64, width: u32) -> Result<Self, SadbyError>;
+    /// Whether the value survives being packed into `width` bits.
+    fn fits(&self, width: u32) -> bool;
+}
+
+macro_rules! sadby_bits_unsigned {
+    ($( $type:ty ),*) => {
+        $(
+            impl Bits for $type {
+                const BITS: u32 = <$type>::BITS;
+
+                fn to_raw(&self) -> u64 {
+                    *self as u64
+                }
+                fn from_raw(raw: u64, _width: u32) -> Result<Self, SadbyError> {
+                    Self::try_from(raw).map_err(|_| SadbyError::OutOfRange)
+                }
+                fn fits(&self, width: u32) -> bool {
+                    width >= Self::BITS || (*self as u64) >> width == 0
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! sadby_bits_signed {
+    ($( $type:ty ),*) => {
+        $(
+            impl Bits for $type {
+                const BITS: u32 = <$type>::BITS;
+
+                fn to_raw(&self) -> u64 {
+                    *self as i64 as u64
+                }
+                fn from_raw(raw: u64, width: u32) -> Result<Self, SadbyError> {
+                    // Sign extend from `width` bits.
+                    let shift = 64 - width;
+                    Self::try_from(((raw << shift) as i64) >> shift).map_err(|_| SadbyError::OutOfRange)
+                }
+                fn fits(&self, width: u32) -> bool {
+                    let value = *self as i64;
+                    width >= Self::BITS || (value >> (width - 1) == 0 || value >> (width - 1) == -1)
+                }
+            }
+        )*
+    };
+}
+
+sadby_bits_unsigned!(u8, u16, u32, u64, usize);
+sadby_bits_signed!(i8, i16, i32, i64, isize);
+
+impl Bits for bool {
+    const BITS: u32 = 1;
+
+    fn to_raw(&self) -> u64 {
+        *self as u64
+    }
+    fn from_raw(raw: u64, _width: u32) -> Result<Self, SadbyError> {
+        match raw {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SadbyError::OutOfRange),
+        }
+    }
+    fn fits(&self, _width: u32) -> bool {
+        true
+    }
+}
+
+#[derive(Default)]
+pub struct BitWriter {
+    buf: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends the low `N` bits of `value`, failing if it doesn't fit in them.
+    pub fn write<const N: u32, T: Bits>(&mut self, value: &T) -> Result<(), SadbyError> {
+        const {
+            assert!(
+                N > 0 && N <= T::BITS,
+                "#[sadby(bits)] is wider than the field type"
+            )
+        };
+
+        if !value.fits(N) {
+            return Err(SadbyError::OutOfRange);
+        }
+
+        let raw = value.to_raw();
+        for i in 0..N {
+            if self.len.is_multiple_of(8) {
+                self.buf.push(0);
+            }
+            if (raw >> i) & 1 == 1 {
+                *self.buf.last_mut().unwrap() |= 1 << (self.len % 8);
+            }
+            self.len += 1;
+        }
+
+        Ok(())
+    }
+
+    /// The written bits, the last byte is padded with zeroes.
+    pub fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+pub struct BitReader<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(input: &'a [u8]) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    /// Reads the next `N` bits as a `T`.
+    pub fn read<const N: u32, T: Bits>(&mut self) -> Result<T, SadbyError> {
+        const {
+            assert!(
+                N > 0 && N <= T::BITS,
+                "#[sadby(bits)] is wider than the field type"
+            )
+        };
+
+        let first = self.pos / 8;
+        let mut raw = 0u64;
+        for i in 0..N {
+            let byte = self
+                .input
+                .get(self.pos / 8)
+                .ok_or(SadbyError::UnexpectedToken)?;
+            raw |= (((byte >> (self.pos % 8)) & 1) as u64) << i;
+            self.pos += 1;
+        }
+
+        let width = N as usize + 2;
+        trace::value(
+            &self.input[first..self.pos.div_ceil(8)],
+            &format_args!("{raw:#0width$b}"),
+        );
+        T::from_raw(raw, N)
+    }
+}
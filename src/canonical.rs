@@ -0,0 +1,48 @@
+//! Canonical encoding, for values that get hashed or signed. Every value has exactly one
+//! encoding: maps and hash sets are sorted by the encoding of their keys, NaNs are written as the
+//! standard quiet NaN and varints take as few bytes as they can. Everything else already leaves
+//! no choice.
+//!
+//! The encoders produce the canonical form except for NaNs, which they write bit for bit, only
+//! `to_bytes` normalizes them. The decoders are lenient, `from_bytes` is the strict one, it only
+//! accepts the bytes `to_bytes` would give for the decoded value.
+
+use super::*;
+
+use std::cell::Cell;
+
+thread_local! {
+    /// Set while `to_bytes` encodes, checked by the impls normalizing their value.
+    static CANONICAL: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Puts the previous mode back when dropped, also when encoding panics.
+struct Restore(bool);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        CANONICAL.set(self.0);
+    }
+}
+
+/// Whether the value being encoded should be normalized, i.e. it's encoded by `to_bytes`.
+pub(crate) fn active() -> bool {
+    CANONICAL.get()
+}
+
+/// Encodes `value` in its canonical form.
+pub fn to_bytes<T: Sadby>(value: &T) -> Result<Vec<u8>, SadbyError> {
+    let _restore = Restore(CANONICAL.replace(true));
+    value.try_se_bytes()
+}
+
+/// Decodes `input`, failing with `SadbyError::NonCanonical` unless it's the canonical encoding of
+/// the value it decodes to.
+pub fn from_bytes<T: Sadby>(input: &[u8]) -> Result<T, SadbyError> {
+    let value = T::de_bytes(input)?;
+    if to_bytes(&value)? != input {
+        return Err(SadbyError::NonCanonical);
+    }
+
+    Ok(value)
+}
@@ -0,0 +1,170 @@
+//! Checksums behind `Checked<T, A>` and `#[sadby(checksum = "...")]`. The checksum goes after the
+//! bytes it covers, in the byte order the value is encoded with.
+
+use super::*;
+
+use std::marker::PhantomData;
+
+/// Checksum algorithms a payload can be protected with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Checksum {
+    /// CRC-32 (ISO-HDLC), as used by zip, png and ethernet.
+    Crc32,
+    /// CRC-32C (Castagnoli), as used by iSCSI, ext4 and SCTP.
+    Crc32c,
+}
+
+impl Checksum {
+    /// Size of the checksum in bytes.
+    pub const SIZE: usize = 4;
+
+    pub fn compute(self, data: &[u8]) -> u32 {
+        let table = match self {
+            Checksum::Crc32 => &CRC32_TABLE,
+            Checksum::Crc32c => &CRC32C_TABLE,
+        };
+
+        !data.iter().fold(!0u32, |crc, &byte| {
+            table[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+        })
+    }
+
+    /// Appends the checksum of everything in `buf`.
+    pub fn append(self, buf: &mut Vec<u8>, endian: Endian) {
+        let crc = self.compute(buf);
+
+        buf.extend_from_slice(&match endian {
+            Endian::Little => crc.to_le_bytes(),
+            Endian::Big => crc.to_be_bytes(),
+        });
+    }
+
+    /// Verifies the checksum at the end of `input` and returns the bytes it covers.
+    pub fn verify(self, input: &[u8], endian: Endian) -> Result<&[u8], SadbyError> {
+        let Some(split) = input.len().checked_sub(Self::SIZE) else {
+            return Err(SadbyError::UnexpectedToken);
+        };
+        let (data, crc) = input.split_at(split);
+        let crc = crc.try_into().unwrap();
+
+        let expected = match endian {
+            Endian::Little => u32::from_le_bytes(crc),
+            Endian::Big => u32::from_be_bytes(crc),
+        };
+        let found = self.compute(data);
+        if expected != found {
+            return Err(SadbyError::BadChecksum { expected, found });
+        }
+
+        Ok(data)
+    }
+}
+
+const CRC32_TABLE: [u32; 256] = crc_table(0xedb8_8320);
+const CRC32C_TABLE: [u32; 256] = crc_table(0x82f6_3b78);
+
+/// Lookup table of a reflected CRC-32 with the given (reversed) polynomial.
+const fn crc_table(poly: u32) -> [u32; 256] {
+    let mut table = [0; 256];
+
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ poly
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+}
+
+/// Algorithm of a `Checked<T, A>`.
+pub trait Algorithm {
+    const CHECKSUM: Checksum;
+}
+
+/// `Checksum::Crc32` as a type, the default algorithm of `Checked`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Crc32;
+
+impl Algorithm for Crc32 {
+    const CHECKSUM: Checksum = Checksum::Crc32;
+}
+
+/// `Checksum::Crc32c` as a type, for `Checked<T, Crc32c>`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Crc32c;
+
+impl Algorithm for Crc32c {
+    const CHECKSUM: Checksum = Checksum::Crc32c;
+}
+
+/// Encodes `T` followed by its checksum, CRC-32 unless `A` says otherwise. Decoding fails with
+/// `SadbyError::BadChecksum` if the bytes got corrupted on the way.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Checked<T, A = Crc32>(pub T, pub PhantomData<A>);
+
+impl<T, A> Checked<T, A> {
+    pub const fn new(value: T) -> Self {
+        Self(value, PhantomData)
+    }
+}
+
+impl<T, A> From<T> for Checked<T, A> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Sadby, A: Algorithm> Sadby for Checked<T, A> {
+    const FIXED_SIZE: Option<usize> = match T::FIXED_SIZE {
+        Some(size) => Some(size + Checksum::SIZE),
+        None => None,
+    };
+
+    fn se_bytes(&self) -> Vec<u8> {
+        match self.try_se_bytes() {
+            Ok(buf) => buf,
+            Err(e) => panic!("Failed to encode: {e:?}"),
+        }
+    }
+    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
+        Self::de_bytes_endian(input, Endian::Little)
+    }
+    fn try_se_bytes_endian(&self, endian: Endian) -> Result<Vec<u8>, SadbyError> {
+        let mut buf = self.0.try_se_bytes_endian(endian)?;
+        A::CHECKSUM.append(&mut buf, endian);
+        Ok(buf)
+    }
+    fn de_bytes_endian(input: &[u8], endian: Endian) -> Result<Self, SadbyError> {
+        let input = A::CHECKSUM.verify(input, endian)?;
+        Ok(Self::new(T::de_bytes_endian(input, endian)?))
+    }
+    fn try_se_bytes(&self) -> Result<Vec<u8>, SadbyError> {
+        self.try_se_bytes_endian(Endian::Little)
+    }
+    // Only the value is described, not how it's protected on the wire.
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        self.0.se_described(writer)
+    }
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        Ok(Self::new(T::de_described(reader)?))
+    }
+}
+
+impl<T: schema::SadbySchema, A: Algorithm> schema::SadbySchema for Checked<T, A> {
+    fn schema() -> schema::Schema {
+        schema::Schema::new::<Self>(schema::Kind::Checked {
+            checksum: A::CHECKSUM,
+            inner: Box::new(T::schema()),
+        })
+    }
+}
@@ -0,0 +1,202 @@
+//! Compression behind `Compressed<T>` and `#[sadby(compress)]`. The payload is the size of the
+//! uncompressed bytes as a varint, followed by the bytes in an LZ77 format close to LZ4 blocks.
+//!
+//! Every sequence starts with a token byte, literal count in the high nibble and match length
+//! minus `MIN_MATCH` in the low one, either going on in bytes of 255 plus a final smaller one
+//! when the nibble is 15. The literals follow, then the distance back to the match as a little
+//! endian u16. The last sequence has no match, it ends when the input does.
+
+use super::*;
+
+/// Largest decompressed size accepted unless asked otherwise, 64 MiB.
+pub const DEFAULT_LIMIT: usize = 64 << 20;
+
+const MIN_MATCH: usize = 4;
+const MAX_OFFSET: usize = u16::MAX as usize;
+const HASH_BITS: u32 = 12;
+
+/// Compresses `raw`, with the header giving its size.
+pub fn se(raw: &[u8]) -> Vec<u8> {
+    let mut buf = varint::se(&raw.len());
+    compress(raw, &mut buf);
+    buf
+}
+
+/// Decompresses bytes written by `se`, refusing anything that would grow past `limit` bytes.
+pub fn de(input: &[u8], limit: usize) -> Result<Vec<u8>, SadbyError> {
+    let (size, input) = varint::take::<usize>(input)?;
+    if size > limit {
+        return Err(SadbyError::TooLarge { size, limit });
+    }
+
+    decompress(input, size)
+}
+
+fn compress(input: &[u8], out: &mut Vec<u8>) {
+    // Last position + 1 each hash of four bytes was seen at, 0 if never.
+    let mut table = vec![0usize; 1 << HASH_BITS];
+
+    let mut anchor = 0;
+    let mut pos = 0;
+    while pos + MIN_MATCH <= input.len() {
+        let key = u32::from_le_bytes(input[pos..pos + MIN_MATCH].try_into().unwrap());
+        let hash = (key.wrapping_mul(2_654_435_761) >> (u32::BITS - HASH_BITS)) as usize;
+        let candidate = std::mem::replace(&mut table[hash], pos + 1);
+
+        if let Some(start) = candidate.checked_sub(1)
+            && pos - start <= MAX_OFFSET
+            && input[start..start + MIN_MATCH] == input[pos..pos + MIN_MATCH]
+        {
+            let mut len = MIN_MATCH;
+            while pos + len < input.len() && input[start + len] == input[pos + len] {
+                len += 1;
+            }
+
+            write_sequence(out, &input[anchor..pos], Some((pos - start, len)));
+            pos += len;
+            anchor = pos;
+        } else {
+            pos += 1;
+        }
+    }
+
+    write_sequence(out, &input[anchor..], None);
+}
+
+fn write_sequence(out: &mut Vec<u8>, literals: &[u8], matched: Option<(usize, usize)>) {
+    let match_len = matched.map_or(0, |(_, len)| len - MIN_MATCH);
+    out.push((literals.len().min(15) as u8) << 4 | match_len.min(15) as u8);
+
+    write_len(out, literals.len());
+    out.extend_from_slice(literals);
+
+    if let Some((offset, _)) = matched {
+        out.extend_from_slice(&(offset as u16).to_le_bytes());
+        write_len(out, match_len);
+    }
+}
+
+/// The rest of a length that didn't fit its nibble.
+fn write_len(out: &mut Vec<u8>, len: usize) {
+    let Some(mut rest) = len.checked_sub(15) else {
+        return;
+    };
+
+    while rest >= 255 {
+        out.push(255);
+        rest -= 255;
+    }
+    out.push(rest as u8);
+}
+
+fn decompress(input: &[u8], size: usize) -> Result<Vec<u8>, SadbyError> {
+    // The size is only a claim until the data backs it up, a few bytes shouldn't get to reserve
+    // the whole limit. Anything past the input's own size grows as it's written.
+    let mut out = Vec::with_capacity(size.min(input.len()));
+
+    let mut pos = 0;
+    loop {
+        let token = *input.get(pos).ok_or(SadbyError::UnexpectedToken)?;
+        pos += 1;
+
+        let literals = read_len(input, &mut pos, (token >> 4) as usize)?;
+        let end = pos
+            .checked_add(literals)
+            .filter(|&end| end <= input.len() && out.len() + literals <= size)
+            .ok_or(SadbyError::UnexpectedToken)?;
+        out.extend_from_slice(&input[pos..end]);
+        pos = end;
+
+        if pos == input.len() {
+            break;
+        }
+
+        let offset = input
+            .get(pos..pos + 2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+            .ok_or(SadbyError::UnexpectedToken)?;
+        pos += 2;
+        let len = read_len(input, &mut pos, (token & 0xf) as usize)? + MIN_MATCH;
+
+        if offset == 0 || offset > out.len() || len > size - out.len() {
+            return Err(SadbyError::UnexpectedToken);
+        }
+        // Byte by byte, the match may overlap the bytes it's copying.
+        let start = out.len() - offset;
+        for i in start..start + len {
+            out.push(out[i]);
+        }
+    }
+
+    if out.len() != size {
+        return Err(SadbyError::UnexpectedToken);
+    }
+
+    Ok(out)
+}
+
+fn read_len(input: &[u8], pos: &mut usize, nibble: usize) -> Result<usize, SadbyError> {
+    let mut len = nibble;
+    if nibble == 15 {
+        loop {
+            let byte = *input.get(*pos).ok_or(SadbyError::UnexpectedToken)?;
+            *pos += 1;
+            len = len
+                .checked_add(byte as usize)
+                .ok_or(SadbyError::UnexpectedToken)?;
+            if byte != 255 {
+                break;
+            }
+        }
+    }
+
+    Ok(len)
+}
+
+/// Encodes `T` compressed. Decoding refuses payloads claiming to be bigger than `DEFAULT_LIMIT`
+/// bytes once decompressed, so a few bytes can't make it allocate gigabytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Compressed<T>(pub T);
+
+impl<T> From<T> for Compressed<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Sadby> Sadby for Compressed<T> {
+    fn se_bytes(&self) -> Vec<u8> {
+        match self.try_se_bytes() {
+            Ok(buf) => buf,
+            Err(e) => panic!("Failed to encode: {e:?}"),
+        }
+    }
+    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
+        Self::de_bytes_endian(input, Endian::Little)
+    }
+    fn try_se_bytes(&self) -> Result<Vec<u8>, SadbyError> {
+        self.try_se_bytes_endian(Endian::Little)
+    }
+    fn try_se_bytes_endian(&self, endian: Endian) -> Result<Vec<u8>, SadbyError> {
+        Ok(se(&self.0.try_se_bytes_endian(endian)?))
+    }
+    fn de_bytes_endian(input: &[u8], endian: Endian) -> Result<Self, SadbyError> {
+        Ok(Self(T::de_bytes_endian(
+            &de(input, DEFAULT_LIMIT)?,
+            endian,
+        )?))
+    }
+    // Only the value is described, not how it's compressed on the wire.
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        self.0.se_described(writer)
+    }
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        Ok(Self(T::de_described(reader)?))
+    }
+}
+
+impl<T: schema::SadbySchema> schema::SadbySchema for Compressed<T> {
+    fn schema() -> schema::Schema {
+        T::schema().compressed()
+    }
+}
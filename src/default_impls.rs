@@ -3,15 +3,46 @@ mod uuid;
 
 pub use super::*;
 
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use std::slice::SliceIndex;
+
+use crate::schema::{Kind, SadbySchema, Schema};
+
 macro_rules! sadby_ints {
     ($( $type:ty ),*) => {
         $(
             impl Sadby for $type {
+                const FIXED_SIZE: Option<usize> = Some(std::mem::size_of::<$type>());
+
                 fn se_bytes(&self) -> Vec<u8> {
-                    self.to_le_bytes().into()
+                    self.normalize().to_le_bytes().into()
                 }
                 fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
-                    Ok(Self::from_le_bytes(<[u8; const { std::mem::size_of::<$type>() }]>::de_bytes(&input[0..const { std::mem::size_of::<$type>() }])?))
+                    Self::de_bytes_endian(input, Endian::Little)
+                }
+                fn try_se_bytes_endian(&self, endian: Endian) -> Result<Vec<u8>, SadbyError> {
+                    Ok(match endian {
+                        Endian::Little => self.normalize().to_le_bytes().into(),
+                        Endian::Big => self.normalize().to_be_bytes().into(),
+                    })
+                }
+                fn de_bytes_endian(input: &[u8], endian: Endian) -> Result<Self, SadbyError> {
+                    let bytes = get(input, 0..const { std::mem::size_of::<$type>() })?;
+                    let value = match endian {
+                        Endian::Little => Self::from_le_bytes(bytes.try_into().unwrap()),
+                        Endian::Big => Self::from_be_bytes(bytes.try_into().unwrap()),
+                    };
+                    trace::value(bytes, &value);
+                    Ok(value)
+                }
+                fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+                    Scalar::write(self, writer);
+                    Ok(())
+                }
+                fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+                    Scalar::read(reader)
                 }
             }
         )*
@@ -20,191 +51,584 @@ macro_rules! sadby_ints {
 
 sadby_ints!(u16, u32, u64, u128, usize, i16, i32, i64, i128, isize, f32, f64);
 
+/// Picks one encoding for values with several, so equal values encode the same in `canonical`.
+trait Normalize: Copy {
+    fn normalize(self) -> Self {
+        self
+    }
+}
+
+impl Normalize for u16 {}
+impl Normalize for u32 {}
+impl Normalize for u64 {}
+impl Normalize for u128 {}
+impl Normalize for usize {}
+impl Normalize for i16 {}
+impl Normalize for i32 {}
+impl Normalize for i64 {}
+impl Normalize for i128 {}
+impl Normalize for isize {}
+// Canonical encoding writes every NaN payload as the standard quiet NaN.
+impl Normalize for f32 {
+    fn normalize(self) -> Self {
+        if self.is_nan() && canonical::active() {
+            f32::NAN
+        } else {
+            self
+        }
+    }
+}
+impl Normalize for f64 {
+    fn normalize(self) -> Self {
+        if self.is_nan() && canonical::active() {
+            f64::NAN
+        } else {
+            self
+        }
+    }
+}
+
+/// Numbers in the described encoding.
+trait Scalar: Sized {
+    fn write(&self, writer: &mut described::Writer);
+    fn read(reader: &mut described::Reader) -> Result<Self, SadbyError>;
+}
+
+macro_rules! sadby_scalar_varints {
+    ($( $type:ty => $tag:ident ),*) => {
+        $(
+            impl Scalar for $type {
+                fn write(&self, writer: &mut described::Writer) {
+                    writer.tag(described::Tag::$tag);
+                    writer.varint(self);
+                }
+                fn read(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+                    reader.expect(described::Tag::$tag)?;
+                    reader.varint()
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! sadby_scalar_floats {
+    ($( $type:ty => $tag:ident ),*) => {
+        $(
+            impl Scalar for $type {
+                fn write(&self, writer: &mut described::Writer) {
+                    writer.tag(described::Tag::$tag);
+                    writer.raw(&self.to_le_bytes());
+                }
+                fn read(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+                    reader.expect(described::Tag::$tag)?;
+                    let bytes = reader.raw(std::mem::size_of::<$type>())?;
+                    Ok(Self::from_le_bytes(bytes.try_into().unwrap()))
+                }
+            }
+        )*
+    };
+}
+
+// Pointer sized integers are described as 64 bit ones, so payloads don't depend on the platform.
+sadby_scalar_varints!(
+    u16 => U16, u32 => U32, u64 => U64, u128 => U128, usize => U64,
+    i16 => I16, i32 => I32, i64 => I64, i128 => I128, isize => I64
+);
+sadby_scalar_floats!(f32 => F32, f64 => F64);
+
 impl Sadby for u8 {
+    const FIXED_SIZE: Option<usize> = Some(1);
+
     fn se_bytes(&self) -> Vec<u8> {
         vec![*self]
     }
     fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
-        Ok(input[0])
+        let bytes = get(input, ..1)?;
+        trace::value(bytes, &bytes[0]);
+        Ok(bytes[0])
+    }
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        writer.tag(described::Tag::U8);
+        writer.byte(*self);
+        Ok(())
+    }
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        reader.expect(described::Tag::U8)?;
+        reader.byte()
     }
 }
 impl Sadby for i8 {
+    const FIXED_SIZE: Option<usize> = Some(1);
+
     fn se_bytes(&self) -> Vec<u8> {
         vec![self.to_le_bytes()[0]]
     }
     fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
-        Ok(Self::from_le_bytes([input[0]]))
+        let bytes = get(input, ..1)?;
+        let value = Self::from_le_bytes([bytes[0]]);
+        trace::value(bytes, &value);
+        Ok(value)
     }
-}
-impl Sadby for char {
-    fn se_bytes(&self) -> Vec<u8> {
-        vec![*self as u8]
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        writer.tag(described::Tag::I8);
+        writer.byte(self.to_le_bytes()[0]);
+        Ok(())
     }
-    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
-        Ok(input[0] as Self)
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        reader.expect(described::Tag::I8)?;
+        Ok(Self::from_le_bytes([reader.byte()?]))
     }
 }
-/*
- * Waiting for:
- * - https://github.com/rust-lang/rust/issues/42721
- * - https://github.com/rust-lang/rust/issues/31844
-impl<const N: usize, T: Sadby + Default + Copy> SerDeBytes for [T; N] {
+impl Sadby for char {
+    const FIXED_SIZE: Option<usize> = Some(1);
+
     fn se_bytes(&self) -> Vec<u8> {
-        let mut buf = Vec::<u8>::new();
-        for item in self {
-            buf.append(&mut item.to_bytes());
-        }
-        buf
+        vec![*self as u8]
     }
     fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
-        let mut slice: [T; N] = [const { T::default() }; N];
-        slice.copy_from_slice(&Vec::<T>::de_bytes(&input)?);
-        Ok(slice)
+        let bytes = get(input, ..1)?;
+        trace::value(bytes, &(bytes[0] as Self));
+        Ok(bytes[0] as Self)
     }
-}
-impl<const N: usize, T: Sadby + Default + Clone + !Copy> SerDeBytes for [T; N] {
-    fn se_bytes(&self) -> Vec<u8> {
-        let mut buf = Vec::<u8>::new();
-        for item in self {
-            buf.append(&mut item.to_bytes());
-        }
-        buf
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        writer.tag(described::Tag::Char);
+        writer.varint(&(*self as u32));
+        Ok(())
     }
-    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
-        let mut slice: [T; N] = [const { T::default() }; N];
-        slice.clone_from_slice(&Vec::<T>::de_bytes(&input)?);
-        Ok(slice)
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        reader.expect(described::Tag::Char)?;
+        char::from_u32(reader.varint()?).ok_or(SadbyError::UnexpectedToken)
     }
 }
-*/
+// Fixed size items are written back to back, anything else gets a length prefix like in `Vec<T>`.
+// Keeps `[u8; N]` as raw bytes and `[f32; N]` as plain floats.
+impl<T: Sadby, const N: usize> Sadby for [T; N] {
+    const FIXED_SIZE: Option<usize> = match T::FIXED_SIZE {
+        Some(size) => Some(size * N),
+        None => None,
+    };
 
-impl<const N: usize> Sadby for [u8; N] {
     fn se_bytes(&self) -> Vec<u8> {
-        let mut buf = Vec::<u8>::new();
-        buf.extend_from_slice(self);
-        buf
+        unwrap_se(self.try_se_bytes())
     }
     fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
-        let mut slice: [u8; N] = [0u8; N];
-        slice.copy_from_slice(&input[0..N]);
-        Ok(slice)
-    }
-}
-impl<const N: usize> Sadby for [char; N] {
-    fn se_bytes(&self) -> Vec<u8> {
-        self.iter().map(|c| *c as u8).collect::<Vec<u8>>()
+        Self::de_bytes_endian(input, Endian::Little)
     }
-    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
-        let mut slice = ['\u{0}'; N];
-        slice.copy_from_slice(&input.iter().map(|b| *b as char).collect::<Vec<char>>());
-
-        Ok(slice)
+    fn try_se_bytes(&self) -> Result<Vec<u8>, SadbyError> {
+        self.try_se_bytes_endian(Endian::Little)
     }
-}
-impl<const N: usize> Sadby for [f32; N] {
-    fn se_bytes(&self) -> Vec<u8> {
-        let mut buf = Vec::<u8>::new();
-        for float in self {
-            buf.extend_from_slice(&float.to_le_bytes());
+    fn try_se_bytes_endian(&self, endian: Endian) -> Result<Vec<u8>, SadbyError> {
+        match T::FIXED_SIZE {
+            Some(_) => {
+                let mut buf = Vec::<u8>::new();
+                for item in self {
+                    buf.append(&mut item.try_se_bytes_endian(endian)?);
+                }
+                Ok(buf)
+            }
+            None => se_items(self.iter(), endian),
         }
-        buf
     }
-    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
-        let mut slice = [0.; N];
-        let mut buf = Vec::<f32>::new();
-
-        let mut current = 0;
-        let mut next = const { std::mem::size_of::<f32>() };
-
-        while next <= (const { std::mem::size_of::<f32>() * N }) {
-            buf.push(f32::de_bytes(&input[current..next])?);
-            current = next;
-            next += const { std::mem::size_of::<f32>() };
-        }
+    fn de_bytes_endian(input: &[u8], endian: Endian) -> Result<Self, SadbyError> {
+        let items = match T::FIXED_SIZE {
+            Some(0) => (0..N)
+                .map(|_| trace::de::<T>(&[], endian))
+                .collect::<Result<Vec<T>, SadbyError>>()?,
+            Some(size) => get(input, ..size * N)?
+                .chunks(size)
+                .map(|chunk| trace::de::<T>(chunk, endian))
+                .collect::<Result<Vec<T>, SadbyError>>()?,
+            None => Vec::<T>::de_bytes_endian(input, endian)?,
+        };
 
-        slice.copy_from_slice(&buf);
-
-        Ok(slice)
+        items.try_into().map_err(|_| SadbyError::UnexpectedToken)
+    }
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        se_described_items(self.iter(), writer)
+    }
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        de_described_items::<T>(reader)?
+            .try_into()
+            .map_err(|_| SadbyError::UnexpectedToken)
     }
 }
 impl<T: Sadby> Sadby for Option<T> {
     fn se_bytes(&self) -> Vec<u8> {
+        unwrap_se(self.try_se_bytes())
+    }
+    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
+        Self::de_bytes_endian(input, Endian::Little)
+    }
+    fn try_se_bytes(&self) -> Result<Vec<u8>, SadbyError> {
+        self.try_se_bytes_endian(Endian::Little)
+    }
+    fn try_se_bytes_endian(&self, endian: Endian) -> Result<Vec<u8>, SadbyError> {
         let mut buf = Vec::<u8>::new();
         match self {
             Some(s) => {
                 buf.push(b'S');
-                buf.extend_from_slice(&s.se_bytes());
+                buf.append(&mut s.try_se_bytes_endian(endian)?);
             }
             None => buf.push(b'N'),
         }
 
-        buf
+        Ok(buf)
     }
-    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
-        match input[0] {
-            b'S' => Ok(Some(T::de_bytes(&input[1..])?)),
+    fn de_bytes_endian(input: &[u8], endian: Endian) -> Result<Self, SadbyError> {
+        match get(input, ..1)?[0] {
+            b'S' => Ok(Some(trace::de::<T>(&input[1..], endian)?)),
             b'N' => Ok(None),
             _ => Err(SadbyError::UnexpectedToken),
         }
     }
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        match self {
+            Some(s) => {
+                writer.tag(described::Tag::Some);
+                s.se_described(writer)
+            }
+            None => {
+                writer.tag(described::Tag::None);
+                Ok(())
+            }
+        }
+    }
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        match reader.tag()? {
+            described::Tag::Some => Ok(Some(T::de_described(reader)?)),
+            described::Tag::None => Ok(None),
+            _ => Err(SadbyError::UnexpectedToken),
+        }
+    }
 }
 
 impl<T: Sadby> Sadby for Vec<T> {
     fn se_bytes(&self) -> Vec<u8> {
-        let mut buf = Vec::<u8>::new();
-
-        for item in self {
-            let mut v_i = item.se_bytes();
-
-            buf.push(v_i.len() as u8);
-            buf.append(&mut v_i);
-        }
-
-        buf
+        unwrap_se(self.try_se_bytes())
     }
     fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
+        Self::de_bytes_endian(input, Endian::Little)
+    }
+    fn try_se_bytes(&self) -> Result<Vec<u8>, SadbyError> {
+        self.try_se_bytes_endian(Endian::Little)
+    }
+    fn try_se_bytes_endian(&self, endian: Endian) -> Result<Vec<u8>, SadbyError> {
+        se_items(self.iter(), endian)
+    }
+    fn de_bytes_endian(input: &[u8], endian: Endian) -> Result<Self, SadbyError> {
         let mut output = Vec::<T>::new();
 
-        #[allow(unused_assignments)]
         let mut current = 0usize;
-        let mut next: isize = -1;
 
-        while next as usize <= (input.len() - 1) {
-            current = unsafe { (next + 1).try_into().unwrap_unchecked() };
-            next = current as isize + input[current] as isize;
-            current += 1;
+        while current < input.len() {
+            let next = current + input[current] as usize;
+            trace::length(input, current);
+
+            output.push(trace::de::<T>(get(input, current + 1..=next)?, endian)?);
 
-            output.push(T::de_bytes(
-                &input[current..=unsafe { next.try_into().unwrap_unchecked() }],
-            )?);
+            current = next + 1;
         }
 
         Ok(output)
     }
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        se_described_items(self.iter(), writer)
+    }
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        de_described_items(reader)
+    }
 }
 impl<T: Sadby> Sadby for Box<[T]> {
     fn se_bytes(&self) -> Vec<u8> {
-        let mut buf = Vec::<u8>::new();
+        unwrap_se(self.try_se_bytes())
+    }
+    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
+        Self::de_bytes_endian(input, Endian::Little)
+    }
+    fn try_se_bytes(&self) -> Result<Vec<u8>, SadbyError> {
+        self.try_se_bytes_endian(Endian::Little)
+    }
+    fn try_se_bytes_endian(&self, endian: Endian) -> Result<Vec<u8>, SadbyError> {
+        se_items(self.iter(), endian)
+    }
+    fn de_bytes_endian(input: &[u8], endian: Endian) -> Result<Self, SadbyError> {
+        Ok(Vec::<T>::de_bytes_endian(input, endian)?.into())
+    }
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        se_described_items(self.iter(), writer)
+    }
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        Ok(de_described_items(reader)?.into())
+    }
+}
+impl<K: Sadby + Eq + Hash, V: Sadby, S: BuildHasher + Default> Sadby for HashMap<K, V, S> {
+    fn se_bytes(&self) -> Vec<u8> {
+        unwrap_se(self.try_se_bytes())
+    }
+    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
+        Self::de_bytes_endian(input, Endian::Little)
+    }
+    fn try_se_bytes(&self) -> Result<Vec<u8>, SadbyError> {
+        self.try_se_bytes_endian(Endian::Little)
+    }
+    fn try_se_bytes_endian(&self, endian: Endian) -> Result<Vec<u8>, SadbyError> {
+        se_entries(sorted_entries(self.iter(), endian)?.into_iter(), endian)
+    }
+    fn de_bytes_endian(input: &[u8], endian: Endian) -> Result<Self, SadbyError> {
+        Ok(Vec::<(K, V)>::de_bytes_endian(input, endian)?
+            .into_iter()
+            .collect())
+    }
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        se_described_entries(
+            sorted_entries(self.iter(), Endian::Little)?.into_iter(),
+            writer,
+        )
+    }
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        Ok(de_described_entries(reader)?.into_iter().collect())
+    }
+}
+impl<K: Sadby + Ord, V: Sadby> Sadby for BTreeMap<K, V> {
+    fn se_bytes(&self) -> Vec<u8> {
+        unwrap_se(self.try_se_bytes())
+    }
+    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
+        Self::de_bytes_endian(input, Endian::Little)
+    }
+    fn try_se_bytes(&self) -> Result<Vec<u8>, SadbyError> {
+        self.try_se_bytes_endian(Endian::Little)
+    }
+    fn try_se_bytes_endian(&self, endian: Endian) -> Result<Vec<u8>, SadbyError> {
+        se_entries(sorted_entries(self.iter(), endian)?.into_iter(), endian)
+    }
+    fn de_bytes_endian(input: &[u8], endian: Endian) -> Result<Self, SadbyError> {
+        Ok(Vec::<(K, V)>::de_bytes_endian(input, endian)?
+            .into_iter()
+            .collect())
+    }
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        se_described_entries(
+            sorted_entries(self.iter(), Endian::Little)?.into_iter(),
+            writer,
+        )
+    }
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        Ok(de_described_entries(reader)?.into_iter().collect())
+    }
+}
+impl<T: Sadby + Eq + Hash, S: BuildHasher + Default> Sadby for HashSet<T, S> {
+    fn se_bytes(&self) -> Vec<u8> {
+        unwrap_se(self.try_se_bytes())
+    }
+    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
+        Self::de_bytes_endian(input, Endian::Little)
+    }
+    fn try_se_bytes(&self) -> Result<Vec<u8>, SadbyError> {
+        self.try_se_bytes_endian(Endian::Little)
+    }
+    fn try_se_bytes_endian(&self, endian: Endian) -> Result<Vec<u8>, SadbyError> {
+        se_items(sorted_items(self.iter(), endian)?.into_iter(), endian)
+    }
+    fn de_bytes_endian(input: &[u8], endian: Endian) -> Result<Self, SadbyError> {
+        Ok(Vec::<T>::de_bytes_endian(input, endian)?
+            .into_iter()
+            .collect())
+    }
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        se_described_items(
+            sorted_items(self.iter(), Endian::Little)?.into_iter(),
+            writer,
+        )
+    }
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        Ok(de_described_items(reader)?.into_iter().collect())
+    }
+}
+impl<T: Sadby + Ord> Sadby for BTreeSet<T> {
+    fn se_bytes(&self) -> Vec<u8> {
+        unwrap_se(self.try_se_bytes())
+    }
+    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
+        Self::de_bytes_endian(input, Endian::Little)
+    }
+    fn try_se_bytes(&self) -> Result<Vec<u8>, SadbyError> {
+        self.try_se_bytes_endian(Endian::Little)
+    }
+    fn try_se_bytes_endian(&self, endian: Endian) -> Result<Vec<u8>, SadbyError> {
+        se_items(self.iter(), endian)
+    }
+    fn de_bytes_endian(input: &[u8], endian: Endian) -> Result<Self, SadbyError> {
+        Ok(Vec::<T>::de_bytes_endian(input, endian)?
+            .into_iter()
+            .collect())
+    }
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        se_described_items(self.iter(), writer)
+    }
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        Ok(de_described_items(reader)?.into_iter().collect())
+    }
+}
 
-        for item in self {
-            let mut v_i = item.se_bytes();
+/// The length prefix of `len` bytes, they don't fit one past 255.
+fn prefix(len: usize) -> Result<u8, SadbyError> {
+    u8::try_from(len).map_err(|_| SadbyError::OutOfRange)
+}
+/// Same layout as `Vec<T>`.
+fn se_items<'a, T: Sadby + 'a>(
+    items: impl Iterator<Item = &'a T>,
+    endian: Endian,
+) -> Result<Vec<u8>, SadbyError> {
+    let mut buf = Vec::<u8>::new();
 
-            buf.push(v_i.len() as u8);
-            buf.append(&mut v_i);
-        }
+    for item in items {
+        let mut v_i = item.try_se_bytes_endian(endian)?;
 
-        buf
+        buf.push(prefix(v_i.len())?);
+        buf.append(&mut v_i);
     }
-    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
-        Ok(Vec::<T>::de_bytes(input)?.into())
+
+    Ok(buf)
+}
+/// Same layout as `Vec<(K, V)>`.
+fn se_entries<'a, K: Sadby + 'a, V: Sadby + 'a>(
+    entries: impl Iterator<Item = (&'a K, &'a V)>,
+    endian: Endian,
+) -> Result<Vec<u8>, SadbyError> {
+    let mut buf = Vec::<u8>::new();
+
+    for (key, value) in entries {
+        let mut k = key.try_se_bytes_endian(endian)?;
+        let mut v = value.try_se_bytes_endian(endian)?;
+
+        buf.push(prefix(1 + k.len() + v.len())?);
+        buf.push(prefix(k.len())?);
+        buf.append(&mut k);
+        buf.append(&mut v);
+    }
+
+    Ok(buf)
+}
+/// Items of a hash set sorted by their encoding, so the order doesn't depend on the hasher.
+fn sorted_items<'a, T: Sadby + 'a>(
+    items: impl Iterator<Item = &'a T>,
+    endian: Endian,
+) -> Result<Vec<&'a T>, SadbyError> {
+    let mut items = items
+        .map(|item| Ok((item.try_se_bytes_endian(endian)?, item)))
+        .collect::<Result<Vec<_>, SadbyError>>()?;
+    items.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    Ok(items.into_iter().map(|(_, item)| item).collect())
+}
+/// Entries of a map sorted by the encoding of their key, which isn't the order `Ord` gives, e.g.
+/// for little endian integers.
+fn sorted_entries<'a, K: Sadby + 'a, V: Sadby + 'a>(
+    entries: impl Iterator<Item = (&'a K, &'a V)>,
+    endian: Endian,
+) -> Result<Vec<(&'a K, &'a V)>, SadbyError> {
+    let mut entries = entries
+        .map(|(key, value)| Ok((key.try_se_bytes_endian(endian)?, (key, value))))
+        .collect::<Result<Vec<_>, SadbyError>>()?;
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    Ok(entries.into_iter().map(|(_, entry)| entry).collect())
+}
+/// `Tag::Seq` of every item.
+fn se_described_items<'a, T: Sadby + 'a>(
+    items: impl ExactSizeIterator<Item = &'a T>,
+    writer: &mut described::Writer,
+) -> Result<(), SadbyError> {
+    writer.tag(described::Tag::Seq);
+    writer.len(items.len());
+    for item in items {
+        item.se_described(writer)?;
+    }
+
+    Ok(())
+}
+fn de_described_items<T: Sadby>(reader: &mut described::Reader) -> Result<Vec<T>, SadbyError> {
+    reader.expect(described::Tag::Seq)?;
+    (0..reader.len()?)
+        .map(|_| T::de_described(reader))
+        .collect()
+}
+/// `Tag::Map` of every entry.
+fn se_described_entries<'a, K: Sadby + 'a, V: Sadby + 'a>(
+    entries: impl ExactSizeIterator<Item = (&'a K, &'a V)>,
+    writer: &mut described::Writer,
+) -> Result<(), SadbyError> {
+    writer.tag(described::Tag::Map);
+    writer.len(entries.len());
+    for (key, value) in entries {
+        key.se_described(writer)?;
+        value.se_described(writer)?;
+    }
+
+    Ok(())
+}
+fn de_described_entries<K: Sadby, V: Sadby>(
+    reader: &mut described::Reader,
+) -> Result<Vec<(K, V)>, SadbyError> {
+    reader.expect(described::Tag::Map)?;
+    (0..reader.len()?)
+        .map(|_| Ok((K::de_described(reader)?, V::de_described(reader)?)))
+        .collect()
+}
+/// `input[range]`, failing like any other malformed input when it's too short.
+fn get<R: SliceIndex<[u8], Output = [u8]>>(input: &[u8], range: R) -> Result<&[u8], SadbyError> {
+    input.get(range).ok_or(SadbyError::UnexpectedToken)
+}
+/// `se_bytes` of types that can only fail to encode because of what they hold.
+fn unwrap_se(result: Result<Vec<u8>, SadbyError>) -> Vec<u8> {
+    match result {
+        Ok(buf) => buf,
+        Err(e) => panic!("Failed to encode: {e:?}"),
+    }
+}
+impl<T: ?Sized> Sadby for PhantomData<T> {
+    const FIXED_SIZE: Option<usize> = Some(0);
+
+    fn se_bytes(&self) -> Vec<u8> {
+        Vec::new()
+    }
+    fn de_bytes(_input: &[u8]) -> Result<Self, SadbyError> {
+        Ok(PhantomData)
+    }
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        writer.tag(described::Tag::Unit);
+        Ok(())
+    }
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        reader.expect(described::Tag::Unit)?;
+        Ok(PhantomData)
     }
 }
 impl Sadby for bool {
+    const FIXED_SIZE: Option<usize> = Some(1);
+
     fn se_bytes(&self) -> Vec<u8> {
         vec![*self as u8]
     }
     fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
-        match input[0] {
+        let bytes = get(input, ..1)?;
+        let value = match bytes[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(SadbyError::UnexpectedToken),
+        };
+        trace::value(bytes, &value);
+        Ok(value)
+    }
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        writer.tag(described::Tag::Bool);
+        writer.byte(*self as u8);
+        Ok(())
+    }
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        reader.expect(described::Tag::Bool)?;
+        match reader.byte()? {
             0 => Ok(false),
             1 => Ok(true),
             _ => Err(SadbyError::UnexpectedToken),
@@ -216,23 +640,210 @@ impl Sadby for String {
         self.as_bytes().to_vec()
     }
     fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
-        Ok(input.iter().map(|b| *b as char).collect::<String>())
+        let value = String::from_utf8(input.to_vec()).map_err(|_| SadbyError::UnexpectedToken)?;
+        trace::value(input, &value);
+        Ok(value)
+    }
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        writer.tag(described::Tag::Str);
+        writer.name(self);
+        Ok(())
+    }
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        reader.expect(described::Tag::Str)?;
+        Ok(reader.name()?.to_owned())
     }
 }
-impl<T: Sadby, Y: Sadby> Sadby for (T, Y) {
-    fn se_bytes(&self) -> Vec<u8> {
-        let mut buf = Vec::new();
-        let mut i = self.0.se_bytes();
-        buf.push(i.len() as u8);
-        buf.append(&mut i);
-        buf.append(&mut self.1.se_bytes());
-        buf
+
+// Every element but the last is prefixed with its length, the last one takes the rest of the input.
+macro_rules! sadby_tuples {
+    ($( ( $( $head:ident $h_idx:tt ),* ; $last:ident $l_idx:tt ) ),*) => {
+        $(
+            impl<$( $head: Sadby, )* $last: Sadby> Sadby for ($( $head, )* $last,) {
+                // Every element plus the length prefixes in front of all but the last one.
+                const FIXED_SIZE: Option<usize> = {
+                    #[allow(unused_mut)]
+                    let mut size = Some(0);
+                    $(
+                        size = match (size, $head::FIXED_SIZE) {
+                            (Some(size), Some(item)) => Some(size + 1 + item),
+                            _ => None,
+                        };
+                    )*
+                    match (size, $last::FIXED_SIZE) {
+                        (Some(size), Some(item)) => Some(size + item),
+                        _ => None,
+                    }
+                };
+
+                fn se_bytes(&self) -> Vec<u8> {
+                    unwrap_se(self.try_se_bytes())
+                }
+                fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
+                    Self::de_bytes_endian(input, Endian::Little)
+                }
+                fn try_se_bytes(&self) -> Result<Vec<u8>, SadbyError> {
+                    self.try_se_bytes_endian(Endian::Little)
+                }
+                fn try_se_bytes_endian(&self, endian: Endian) -> Result<Vec<u8>, SadbyError> {
+                    let mut buf = Vec::new();
+                    $(
+                        let mut i = self.$h_idx.try_se_bytes_endian(endian)?;
+                        buf.push(prefix(i.len())?);
+                        buf.append(&mut i);
+                    )*
+                    buf.append(&mut self.$l_idx.try_se_bytes_endian(endian)?);
+                    Ok(buf)
+                }
+                fn de_bytes_endian(input: &[u8], endian: Endian) -> Result<Self, SadbyError> {
+                    #[allow(unused_mut)]
+                    let mut current = 0usize;
+                    Ok((
+                        $({
+                            let len = get(input, current..=current)?[0];
+                            let next = current + len as usize;
+                            trace::length(input, current);
+                            let item = trace::de::<$head>(get(input, current + 1..=next)?, endian)?;
+                            current = next + 1;
+                            item
+                        },)*
+                        trace::de::<$last>(&input[current..], endian)?,
+                    ))
+                }
+                fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+                    writer.tag(described::Tag::Tuple);
+                    writer.len([$( stringify!($head), )* stringify!($last)].len());
+                    $(
+                        self.$h_idx.se_described(writer)?;
+                    )*
+                    self.$l_idx.se_described(writer)
+                }
+                fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+                    reader.expect(described::Tag::Tuple)?;
+                    if reader.len()? != [$( stringify!($head), )* stringify!($last)].len() {
+                        return Err(SadbyError::UnexpectedToken);
+                    }
+                    Ok((
+                        $( $head::de_described(reader)?, )*
+                        $last::de_described(reader)?,
+                    ))
+                }
+            }
+            impl<$( $head: SadbySchema, )* $last: SadbySchema> SadbySchema for ($( $head, )* $last,) {
+                fn schema() -> Schema {
+                    Schema::new::<Self>(Kind::Tuple(vec![$( $head::schema(), )* $last::schema()]))
+                }
+            }
+        )*
+    };
+}
+
+sadby_tuples!(
+    (; A 0),
+    (A 0; B 1),
+    (A 0, B 1; C 2),
+    (A 0, B 1, C 2; D 3),
+    (A 0, B 1, C 2, D 3; E 4),
+    (A 0, B 1, C 2, D 3, E 4; F 5),
+    (A 0, B 1, C 2, D 3, E 4, F 5; G 6),
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6; H 7),
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7; I 8),
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8; J 9),
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9; K 10),
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10; L 11)
+);
+
+macro_rules! sadby_schema_numbers {
+    ($( $type:ty => $kind:ident $({ signed: $signed:literal })? ),*) => {
+        $(
+            impl SadbySchema for $type {
+                fn schema() -> Schema {
+                    Schema::new::<Self>(Kind::$kind {
+                        size: std::mem::size_of::<$type>(),
+                        $( signed: $signed, )?
+                    })
+                }
+            }
+        )*
+    };
+}
+
+sadby_schema_numbers!(
+    u8 => Int { signed: false }, u16 => Int { signed: false }, u32 => Int { signed: false },
+    u64 => Int { signed: false }, u128 => Int { signed: false }, usize => Int { signed: false },
+    i8 => Int { signed: true }, i16 => Int { signed: true }, i32 => Int { signed: true },
+    i64 => Int { signed: true }, i128 => Int { signed: true }, isize => Int { signed: true },
+    f32 => Float, f64 => Float
+);
+
+impl SadbySchema for bool {
+    fn schema() -> Schema {
+        Schema::new::<Self>(Kind::Bool)
     }
-    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
-        let middle = input[0] as usize;
-        Ok((
-            T::de_bytes(&input[..middle])?,
-            Y::de_bytes(&input[middle..])?,
-        ))
+}
+impl SadbySchema for char {
+    fn schema() -> Schema {
+        Schema::new::<Self>(Kind::Char)
+    }
+}
+impl SadbySchema for String {
+    fn schema() -> Schema {
+        Schema::new::<Self>(Kind::Str)
+    }
+}
+impl<T: ?Sized> SadbySchema for PhantomData<T> {
+    fn schema() -> Schema {
+        Schema::new::<Self>(Kind::Unit)
+    }
+}
+impl<T: SadbySchema, const N: usize> SadbySchema for [T; N] {
+    fn schema() -> Schema {
+        Schema::new::<Self>(Kind::Array {
+            item: Box::new(T::schema()),
+            len: N,
+        })
+    }
+}
+impl<T: SadbySchema> SadbySchema for Option<T> {
+    fn schema() -> Schema {
+        Schema::new::<Self>(Kind::Option(Box::new(T::schema())))
+    }
+}
+impl<T: SadbySchema> SadbySchema for Vec<T> {
+    fn schema() -> Schema {
+        Schema::new::<Self>(Kind::Seq(Box::new(T::schema())))
+    }
+}
+impl<T: SadbySchema> SadbySchema for Box<[T]> {
+    fn schema() -> Schema {
+        Schema::new::<Self>(Kind::Seq(Box::new(T::schema())))
+    }
+}
+impl<T: SadbySchema + Eq + Hash, S: BuildHasher + Default> SadbySchema for HashSet<T, S> {
+    fn schema() -> Schema {
+        Schema::new::<Self>(Kind::Seq(Box::new(T::schema())))
+    }
+}
+impl<T: SadbySchema + Ord> SadbySchema for BTreeSet<T> {
+    fn schema() -> Schema {
+        Schema::new::<Self>(Kind::Seq(Box::new(T::schema())))
+    }
+}
+impl<K: SadbySchema + Eq + Hash, V: SadbySchema, S: BuildHasher + Default> SadbySchema
+    for HashMap<K, V, S>
+{
+    fn schema() -> Schema {
+        Schema::new::<Self>(Kind::Map {
+            key: Box::new(K::schema()),
+            value: Box::new(V::schema()),
+        })
+    }
+}
+impl<K: SadbySchema + Ord, V: SadbySchema> SadbySchema for BTreeMap<K, V> {
+    fn schema() -> Schema {
+        Schema::new::<Self>(Kind::Map {
+            key: Box::new(K::schema()),
+            value: Box::new(V::schema()),
+        })
     }
 }
@@ -3,10 +3,43 @@ use super::*;
 use ::uuid::Uuid;
 
 impl Sadby for Uuid {
+    const FIXED_SIZE: Option<usize> = Some(16);
+
     fn se_bytes(&self) -> Vec<u8> {
         self.to_bytes_le().into()
     }
     fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
-        Ok(Uuid::from_bytes_le(<[u8; 16]>::de_bytes(&input[0..16])?))
+        Self::de_bytes_endian(input, Endian::Little)
+    }
+    fn try_se_bytes_endian(&self, endian: Endian) -> Result<Vec<u8>, SadbyError> {
+        Ok(match endian {
+            Endian::Little => self.to_bytes_le().into(),
+            Endian::Big => self.as_bytes().to_vec(),
+        })
+    }
+    fn de_bytes_endian(input: &[u8], endian: Endian) -> Result<Self, SadbyError> {
+        let bytes = get(input, 0..16)?;
+        let value = match endian {
+            Endian::Little => Uuid::from_bytes_le(bytes.try_into().unwrap()),
+            Endian::Big => Uuid::from_bytes(bytes.try_into().unwrap()),
+        };
+        trace::value(bytes, &value);
+        Ok(value)
+    }
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        writer.tag(described::Tag::Bytes);
+        writer.len(16);
+        writer.raw(self.as_bytes());
+        Ok(())
+    }
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        reader.expect(described::Tag::Bytes)?;
+        let len = reader.len()?;
+        Uuid::from_slice(reader.raw(len)?).map_err(|_| SadbyError::UnexpectedToken)
+    }
+}
+impl SadbySchema for Uuid {
+    fn schema() -> Schema {
+        Schema::new::<Self>(Kind::Uuid)
     }
 }
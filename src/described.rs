@@ -0,0 +1,267 @@
+//! Self-describing encoding, every value starts with a `Tag` saying what it is, so payloads can be
+//! inspected and transcoded without the Rust type. Produced by the same `Sadby` impls as the
+//! positional encoding, through `Sadby::se_described` and `Sadby::de_described`.
+//!
+//! After the tag:
+//! - `U8` and `I8`: the byte
+//! - other integers: LEB128, zigzag encoded first if signed
+//! - floats: little endian, bit for bit
+//! - `Bool`: one byte, 0 or 1
+//! - `Char`: the code point as a varint
+//! - `Str` and `Bytes`: varint length, then the bytes
+//! - `Seq` and `Tuple`: varint count, then the items
+//! - `Map`: varint count, then every key followed by its value
+//! - `Struct`: varint count, then every field as a name (like a `Str` without tag) and a value
+//! - `StructIds`: like `Struct`, with fields named by a single byte id, see `#[sadby(tagged)]`
+//! - `Variant`: the discriminant as a varint, the variant name, then a `Struct` or `Unit`
+//! - `Unit`, `None`: nothing, `Some`: the value
+//!
+//! Decoding a struct matches fields by name, fields it doesn't know are skipped and ones missing
+//! from the payload take their default if they have `#[sadby(since)]`.
+
+use super::*;
+
+macro_rules! sadby_tags {
+    ($( $(#[$meta:meta])* $tag:ident = $value:literal ),* $(,)?) => {
+        /// What the value after it is, see the module docs for the layout of each.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[repr(u8)]
+        pub enum Tag {
+            $( $(#[$meta])* $tag = $value, )*
+        }
+
+        impl TryFrom<u8> for Tag {
+            type Error = SadbyError;
+
+            fn try_from(value: u8) -> Result<Self, SadbyError> {
+                match value {
+                    $( $value => Ok(Tag::$tag), )*
+                    _ => Err(SadbyError::UnexpectedToken),
+                }
+            }
+        }
+    };
+}
+
+sadby_tags!(
+    Unit = 0,
+    Bool = 1,
+    U8 = 2,
+    U16 = 3,
+    U32 = 4,
+    U64 = 5,
+    U128 = 6,
+    I8 = 7,
+    I16 = 8,
+    I32 = 9,
+    I64 = 10,
+    I128 = 11,
+    F32 = 12,
+    F64 = 13,
+    Char = 14,
+    Str = 15,
+    /// Raw bytes, also what types without a described encoding of their own fall back to.
+    Bytes = 16,
+    None = 17,
+    Some = 18,
+    Seq = 19,
+    Tuple = 20,
+    Map = 21,
+    Struct = 22,
+    StructIds = 23,
+    Variant = 24,
+);
+
+/// Deepest nesting `Reader::skip` and `Value` go through.
+pub(crate) const MAX_DEPTH: usize = 128;
+
+/// Encodes `value` self-describing.
+pub fn to_bytes<T: Sadby>(value: &T) -> Result<Vec<u8>, SadbyError> {
+    let mut writer = Writer::new();
+    value.se_described(&mut writer)?;
+    Ok(writer.finish())
+}
+
+/// Decodes a value encoded by `to_bytes`, failing if anything is left after it.
+pub fn from_bytes<T: Sadby>(input: &[u8]) -> Result<T, SadbyError> {
+    let mut reader = Reader::new(input);
+    let value = T::de_described(&mut reader)?;
+    if !reader.is_empty() {
+        return Err(SadbyError::UnexpectedToken);
+    }
+
+    Ok(value)
+}
+
+/// Where `se_described` writes to.
+#[derive(Debug, Default)]
+pub struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+
+    pub fn tag(&mut self, tag: Tag) {
+        self.buf.push(tag as u8);
+    }
+
+    /// A count, length or discriminant.
+    pub fn len(&mut self, len: usize) {
+        self.buf.append(&mut varint::se(&len));
+    }
+
+    /// A field or variant name.
+    pub fn name(&mut self, name: &str) {
+        self.len(name.len());
+        self.buf.extend_from_slice(name.as_bytes());
+    }
+
+    pub fn byte(&mut self, byte: u8) {
+        self.buf.push(byte);
+    }
+
+    pub fn raw(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    pub fn varint<T: varint::VarintInt>(&mut self, value: &T) {
+        self.buf.append(&mut varint::se(value));
+    }
+}
+
+/// Where `de_described` reads from.
+#[derive(Clone, Debug)]
+pub struct Reader<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(input: &'a [u8]) -> Self {
+        Self { input }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.input.is_empty()
+    }
+
+    pub fn tag(&mut self) -> Result<Tag, SadbyError> {
+        Tag::try_from(self.byte()?)
+    }
+
+    /// The next tag, without reading it.
+    pub fn peek(&self) -> Result<Tag, SadbyError> {
+        self.clone().tag()
+    }
+
+    /// Reads a tag, failing unless it's `tag`.
+    pub fn expect(&mut self, tag: Tag) -> Result<(), SadbyError> {
+        if self.tag()? != tag {
+            return Err(SadbyError::UnexpectedToken);
+        }
+
+        Ok(())
+    }
+
+    pub fn len(&mut self) -> Result<usize, SadbyError> {
+        self.varint()
+    }
+
+    pub fn name(&mut self) -> Result<&'a str, SadbyError> {
+        let len = self.len()?;
+        std::str::from_utf8(self.raw(len)?).map_err(|_| SadbyError::UnexpectedToken)
+    }
+
+    pub fn byte(&mut self) -> Result<u8, SadbyError> {
+        Ok(self.raw(1)?[0])
+    }
+
+    pub fn raw(&mut self, len: usize) -> Result<&'a [u8], SadbyError> {
+        if len > self.input.len() {
+            return Err(SadbyError::UnexpectedToken);
+        }
+
+        let (bytes, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(bytes)
+    }
+
+    pub fn varint<T: varint::VarintInt>(&mut self) -> Result<T, SadbyError> {
+        let (value, rest) = varint::take(self.input)?;
+        self.input = rest;
+        Ok(value)
+    }
+
+    /// Skips a whole value, whatever it is.
+    pub fn skip(&mut self) -> Result<(), SadbyError> {
+        self.skip_nested(0)
+    }
+
+    fn skip_nested(&mut self, depth: usize) -> Result<(), SadbyError> {
+        // Nothing stops a payload from nesting deep enough to overflow the stack otherwise.
+        if depth > MAX_DEPTH {
+            return Err(SadbyError::UnexpectedToken);
+        }
+
+        let depth = depth + 1;
+        match self.tag()? {
+            Tag::Unit | Tag::None => {}
+            Tag::Bool | Tag::U8 | Tag::I8 => {
+                self.raw(1)?;
+            }
+            Tag::U16 | Tag::U32 | Tag::U64 | Tag::U128 | Tag::Char => {
+                self.varint::<u128>()?;
+            }
+            Tag::I16 | Tag::I32 | Tag::I64 | Tag::I128 => {
+                self.varint::<i128>()?;
+            }
+            Tag::F32 => {
+                self.raw(4)?;
+            }
+            Tag::F64 => {
+                self.raw(8)?;
+            }
+            Tag::Str | Tag::Bytes => {
+                let len = self.len()?;
+                self.raw(len)?;
+            }
+            Tag::Some => self.skip_nested(depth)?,
+            Tag::Seq | Tag::Tuple => {
+                for _ in 0..self.len()? {
+                    self.skip_nested(depth)?;
+                }
+            }
+            Tag::Map => {
+                for _ in 0..self.len()? {
+                    self.skip_nested(depth)?;
+                    self.skip_nested(depth)?;
+                }
+            }
+            Tag::Struct => {
+                for _ in 0..self.len()? {
+                    self.name()?;
+                    self.skip_nested(depth)?;
+                }
+            }
+            Tag::StructIds => {
+                for _ in 0..self.len()? {
+                    self.byte()?;
+                    self.skip_nested(depth)?;
+                }
+            }
+            Tag::Variant => {
+                self.len()?;
+                self.name()?;
+                self.skip_nested(depth)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+//! Byte order of integers, floats and uuids. Little endian unless asked otherwise.
+
+use super::*;
+
+/// Byte order picked at runtime, see `Sadby::try_se_bytes_endian`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Endian {
+    #[default]
+    Little,
+    Big,
+}
+
+macro_rules! sadby_endian_wrappers {
+    ($( $(#[$meta:meta])* $wrapper:ident => $endian:expr ),*) => {
+        $(
+            $(#[$meta])*
+            #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            pub struct $wrapper<T>(pub T);
+
+            impl<T> From<T> for $wrapper<T> {
+                fn from(value: T) -> Self {
+                    Self(value)
+                }
+            }
+
+            impl<T: Sadby> Sadby for $wrapper<T> {
+                const FIXED_SIZE: Option<usize> = T::FIXED_SIZE;
+
+                fn se_bytes(&self) -> Vec<u8> {
+                    match self.try_se_bytes() {
+                        Ok(buf) => buf,
+                        Err(e) => panic!("Failed to encode: {e:?}"),
+                    }
+                }
+                fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
+                    Ok(Self(T::de_bytes_endian(input, $endian)?))
+                }
+                fn try_se_bytes(&self) -> Result<Vec<u8>, SadbyError> {
+                    self.0.try_se_bytes_endian($endian)
+                }
+                // The wrapper decides the byte order, whatever the outside asks for.
+                fn try_se_bytes_endian(&self, _endian: Endian) -> Result<Vec<u8>, SadbyError> {
+                    self.try_se_bytes()
+                }
+                fn de_bytes_endian(input: &[u8], _endian: Endian) -> Result<Self, SadbyError> {
+                    Self::de_bytes(input)
+                }
+                fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+                    self.0.se_described(writer)
+                }
+                fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+                    Ok(Self(T::de_described(reader)?))
+                }
+            }
+
+            impl<T: schema::SadbySchema> schema::SadbySchema for $wrapper<T> {
+                fn schema() -> schema::Schema {
+                    T::schema().endian($endian)
+                }
+            }
+        )*
+    };
+}
+
+sadby_endian_wrappers!(
+    /// Encodes `T` big endian, e.g. `BigEndian<u32>` or `BigEndian<Vec<u16>>`.
+    BigEndian => Endian::Big,
+    /// Encodes `T` little endian, the default, whatever the surrounding container asks for.
+    LittleEndian => Endian::Little
+);
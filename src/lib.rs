@@ -1,13 +1,123 @@
+pub mod bits;
+pub mod canonical;
+pub mod checksum;
+pub mod compress;
 mod default_impls;
+pub mod described;
+mod endian;
+pub mod schema;
+#[cfg(feature = "serde")]
+pub mod serde;
+pub mod trace;
+pub mod value;
+pub mod varint;
 
+#[cfg(feature = "serde")]
+pub use self::serde::SerdeAsSadby;
+pub use checksum::Checked;
+pub use compress::Compressed;
+pub use endian::{BigEndian, Endian, LittleEndian};
 pub use sadby_macro::Sadby;
+pub use schema::SadbySchema;
+pub use value::Value;
+pub use varint::Varint;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum SadbyError {
     UnexpectedToken,
+    /// A value doesn't fit the space it's given, e.g. a `#[sadby(bits = N)]` field or more than
+    /// 255 bytes behind a length prefix.
+    OutOfRange,
+    /// Raised by user code, e.g. a failed `#[sadby(try_from = "...")]` conversion.
+    Custom(String),
+    /// The input doesn't start with the container's `#[sadby(magic = ...)]` bytes.
+    BadMagic {
+        expected: Vec<u8>,
+        found: Vec<u8>,
+    },
+    /// A `#[sadby(constant = ...)]` field was read with a different value.
+    BadConstant {
+        field: &'static str,
+    },
+    /// The checksum stored with the payload doesn't match the one computed over it.
+    BadChecksum {
+        expected: u32,
+        found: u32,
+    },
+    /// A `#[sadby(validate = "...")]` check failed. `path` leads to the offending field through
+    /// the fields it's nested in, e.g. `header.len`, and is empty for a container check.
+    Invalid {
+        path: String,
+        message: String,
+    },
+    /// A compressed payload claims to decompress to more than the limit allows.
+    TooLarge {
+        size: usize,
+        limit: usize,
+    },
+    /// The input decodes fine, but isn't the canonical encoding of the value, see `canonical`.
+    NonCanonical,
+    /// The payload was written by a newer `#[sadby(version)]` of the container than this one.
+    NewerVersion {
+        found: u8,
+        supported: u8,
+    },
+}
+
+impl SadbyError {
+    /// Prefixes the path of `Invalid` errors with the field they were decoded as part of.
+    pub fn in_field(self, field: &str) -> Self {
+        match self {
+            SadbyError::Invalid { path, message } if path.is_empty() => SadbyError::Invalid {
+                path: field.to_owned(),
+                message,
+            },
+            SadbyError::Invalid { path, message } => SadbyError::Invalid {
+                path: format!("{field}.{path}"),
+                message,
+            },
+            e => e,
+        }
+    }
 }
 
 pub trait Sadby: Sized {
+    /// Size of the encoding if it's the same for every value, required by `#[sadby(packed)]`.
+    const FIXED_SIZE: Option<usize> = None;
+
     fn se_bytes(&self) -> Vec<u8>;
     fn de_bytes(input: &[u8]) -> Result<Self, SadbyError>;
+
+    /// Like `se_bytes`, but reports values that can't be encoded instead of panicking.
+    fn try_se_bytes(&self) -> Result<Vec<u8>, SadbyError> {
+        Ok(self.se_bytes())
+    }
+
+    /// Like `try_se_bytes`, with integers, floats and uuids in the given byte order. Types with
+    /// a byte order of their own ignore it.
+    fn try_se_bytes_endian(&self, endian: Endian) -> Result<Vec<u8>, SadbyError> {
+        let _ = endian;
+        self.try_se_bytes()
+    }
+    /// Like `de_bytes`, with integers, floats and uuids in the given byte order.
+    fn de_bytes_endian(input: &[u8], endian: Endian) -> Result<Self, SadbyError> {
+        let _ = endian;
+        Self::de_bytes(input)
+    }
+
+    /// Writes the value self-describing, see `described`. Types without a described encoding of
+    /// their own are written as their positional encoding in a `Tag::Bytes`.
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        let bytes = self.try_se_bytes()?;
+        writer.tag(described::Tag::Bytes);
+        writer.len(bytes.len());
+        writer.raw(&bytes);
+        Ok(())
+    }
+    /// Reads a value written by `se_described`.
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        reader.expect(described::Tag::Bytes)?;
+        let len = reader.len()?;
+        Self::de_bytes(reader.raw(len)?)
+    }
 }
@@ -0,0 +1,534 @@
+//! Runtime description of the positional wire layout of a type, see `SadbySchema`. Meant for
+//! generating documentation and for tooling that handles payloads without the Rust types.
+//!
+//! `Schema::to_json` exports it as JSON, every object with its keys in a fixed order, so the text
+//! only changes when the layout does.
+
+use super::*;
+
+use std::cell::RefCell;
+
+use json::Json;
+
+pub mod compat;
+mod convert;
+mod decode;
+mod encode;
+mod json;
+
+pub use decode::Annotation;
+
+/// Types that can describe their own wire layout, implemented by `#[derive(Sadby)]`.
+pub trait SadbySchema: Sadby {
+    fn schema() -> Schema;
+}
+
+/// The layout of one type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schema {
+    /// Size of every encoding of the type, see `Sadby::FIXED_SIZE`.
+    pub fixed_size: Option<usize>,
+    pub kind: Kind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// Nothing at all, e.g. `PhantomData<T>`.
+    Unit,
+    /// One byte, 0 or 1.
+    Bool,
+    /// `size` bytes, little endian unless wrapped in `Kind::Endian`.
+    Int {
+        size: usize,
+        signed: bool,
+    },
+    Float {
+        size: usize,
+    },
+    /// One byte, the low byte of the code point.
+    Char,
+    /// UTF-8 taking up the rest of the input.
+    Str,
+    /// 16 bytes, in RFC 4122 order big endian and in the mixed Microsoft one little endian.
+    Uuid,
+    /// `b'S'` followed by the value, or `b'N'`.
+    Option(Box<Schema>),
+    /// Fixed size items back to back, any other item prefixed with its length like in `Seq`.
+    Array {
+        item: Box<Schema>,
+        len: usize,
+    },
+    /// Items up to the end of the input, each prefixed with its length as a byte.
+    Seq(Box<Schema>),
+    /// Laid out like a `Seq` of `(key, value)` tuples.
+    Map {
+        key: Box<Schema>,
+        value: Box<Schema>,
+    },
+    /// Items in order, each but the last prefixed with its length as a byte.
+    Tuple(Vec<Schema>),
+    /// LEB128, zigzag encoded first if signed, see `varint`.
+    Varint(Box<Schema>),
+    /// Compressed, see `compress`.
+    Compressed(Box<Schema>),
+    /// Followed by its checksum, see `checksum`.
+    Checked {
+        checksum: checksum::Checksum,
+        inner: Box<Schema>,
+    },
+    /// Encoded in the given byte order, whatever the surrounding container asks for.
+    Endian {
+        endian: Endian,
+        inner: Box<Schema>,
+    },
+    /// A `Value`, in the described encoding.
+    Described,
+    /// Bytes only the type knows the layout of, e.g. a `SerdeAsSadby<T>`.
+    Opaque,
+    /// A struct or enum deriving `Sadby`.
+    Container(Box<Container>),
+    /// The closest container around it with this name, for recursive types.
+    Ref(String),
+}
+
+/// A struct or enum deriving `Sadby`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Container {
+    pub name: String,
+    /// `#[sadby(magic)]`, written in front of everything else.
+    pub magic: Option<Vec<u8>>,
+    /// `#[sadby(version)]`, written after the magic as a byte.
+    pub version: Option<u8>,
+    /// `#[sadby(checksum)]`, appended to everything but the magic.
+    pub checksum: Option<checksum::Checksum>,
+    pub body: Body,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Body {
+    Struct {
+        layout: Layout,
+        fields: Vec<Field>,
+    },
+    /// A discriminant byte, followed by the fields of the variant it picks.
+    Enum {
+        layout: Layout,
+        variants: Vec<Variant>,
+    },
+    /// Encoded as another type, see `#[sadby(into)]`.
+    Proxy(Box<Schema>),
+}
+
+/// How the fields of a container are laid out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Layout {
+    /// Fields in order, each prefixed with its length as a byte, unless there's only one and the
+    /// container has no version.
+    Positional,
+    /// Fields written as their id, their length as a byte and the bytes, see `#[sadby(tagged)]`.
+    Tagged,
+    /// Fields back to back, see `#[sadby(packed)]`.
+    Packed,
+    /// Exactly like the one field, see `#[sadby(transparent)]`.
+    Transparent,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Field {
+    /// Its name, or its position in a tuple.
+    pub name: String,
+    /// `#[sadby(id)]`
+    pub id: Option<u8>,
+    /// `#[sadby(since)]`
+    pub since: Option<u8>,
+    /// `#[sadby(bits)]`, neighbouring bit fields share the bytes they're packed into.
+    pub bits: Option<u32>,
+    /// `#[sadby(constant)]`
+    pub constant: bool,
+    pub schema: Schema,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Variant {
+    pub name: String,
+    pub discriminant: u8,
+    pub fields: Vec<Field>,
+}
+
+impl Schema {
+    /// A schema of `T`, taking its fixed size from `T::FIXED_SIZE`.
+    pub fn new<T: Sadby>(kind: Kind) -> Self {
+        Self {
+            fixed_size: T::FIXED_SIZE,
+            kind,
+        }
+    }
+
+    /// The layout of a `#[sadby(varint)]` field of this type.
+    pub fn varint(self) -> Self {
+        Self {
+            fixed_size: None,
+            kind: Kind::Varint(Box::new(self)),
+        }
+    }
+
+    /// The layout of a `#[sadby(compress)]` field of this type.
+    pub fn compressed(self) -> Self {
+        Self {
+            fixed_size: None,
+            kind: Kind::Compressed(Box::new(self)),
+        }
+    }
+
+    /// The layout of this type in a field or container with a `#[sadby(endian)]`.
+    pub fn endian(self, endian: Endian) -> Self {
+        Self {
+            fixed_size: self.fixed_size,
+            kind: Kind::Endian {
+                endian,
+                inner: Box::new(self),
+            },
+        }
+    }
+
+    /// The schema as pretty printed JSON.
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        Json::from(self).write(&mut out, 0);
+        out
+    }
+
+    /// Reads back a schema exported by `to_json`, e.g. from a snapshot.
+    pub fn from_json(input: &str) -> Result<Self, SadbyError> {
+        Json::parse(input)
+            .and_then(|json| Schema::try_from(&json))
+            .map_err(|e| SadbyError::Custom(format!("Bad schema: {e}")))
+    }
+}
+
+impl Kind {
+    /// What the kind is called in the JSON export.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kind::Unit => "unit",
+            Kind::Bool => "bool",
+            Kind::Int { .. } => "int",
+            Kind::Float { .. } => "float",
+            Kind::Char => "char",
+            Kind::Str => "str",
+            Kind::Uuid => "uuid",
+            Kind::Option(_) => "option",
+            Kind::Array { .. } => "array",
+            Kind::Seq(_) => "seq",
+            Kind::Map { .. } => "map",
+            Kind::Tuple(_) => "tuple",
+            Kind::Varint(_) => "varint",
+            Kind::Compressed(_) => "compressed",
+            Kind::Checked { .. } => "checked",
+            Kind::Endian { .. } => "endian",
+            Kind::Described => "described",
+            Kind::Opaque => "opaque",
+            Kind::Container(container) => container.body.name(),
+            Kind::Ref(_) => "ref",
+        }
+    }
+}
+
+impl Body {
+    /// What the container kind is called in the JSON export.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Body::Struct { .. } => "struct",
+            Body::Enum { .. } => "enum",
+            Body::Proxy(_) => "proxy",
+        }
+    }
+}
+
+thread_local! {
+    /// Type names of the containers whose schema is being built, innermost last.
+    static BUILDING: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Schema of the container `T` called `name`, built by `build` unless it's already being built
+/// further out, in which case it's a `Kind::Ref` to it instead.
+pub fn container<T: Sadby>(name: &str, build: impl FnOnce() -> Container) -> Schema {
+    struct Building;
+    impl Drop for Building {
+        fn drop(&mut self) {
+            BUILDING.with_borrow_mut(|building| building.pop());
+        }
+    }
+
+    let key = std::any::type_name::<T>();
+    if BUILDING.with_borrow(|building| building.contains(&key)) {
+        return Schema::new::<T>(Kind::Ref(name.to_owned()));
+    }
+
+    BUILDING.with_borrow_mut(|building| building.push(key));
+    let _building = Building;
+    Schema::new::<T>(Kind::Container(Box::new(build())))
+}
+
+impl From<&Schema> for Json {
+    fn from(schema: &Schema) -> Self {
+        let json = |schema: &Schema| Json::from(schema);
+
+        let mut rest = match &schema.kind {
+            Kind::Unit
+            | Kind::Bool
+            | Kind::Char
+            | Kind::Str
+            | Kind::Uuid
+            | Kind::Described
+            | Kind::Opaque => vec![],
+            Kind::Int { size, signed } => vec![
+                ("size", Json::Num(size.to_string())),
+                ("signed", Json::Bool(*signed)),
+            ],
+            Kind::Float { size } => vec![("size", Json::Num(size.to_string()))],
+            Kind::Option(some) => vec![("some", json(some))],
+            Kind::Array { item, len } => {
+                vec![("len", Json::Num(len.to_string())), ("item", json(item))]
+            }
+            Kind::Seq(item) => vec![("item", json(item))],
+            Kind::Map { key, value } => vec![("key", json(key)), ("value", json(value))],
+            Kind::Tuple(items) => {
+                vec![("items", Json::Arr(items.iter().map(Json::from).collect()))]
+            }
+            Kind::Varint(inner) => vec![("inner", json(inner))],
+            Kind::Compressed(inner) => vec![("inner", json(inner))],
+            Kind::Checked { checksum, inner } => vec![
+                ("checksum", name_json(&CHECKSUMS, checksum)),
+                ("inner", json(inner)),
+            ],
+            Kind::Endian { endian, inner } => vec![
+                ("endian", name_json(&ENDIANS, endian)),
+                ("inner", json(inner)),
+            ],
+            Kind::Container(container) => {
+                let mut rest = vec![
+                    ("name", Json::Str(container.name.clone())),
+                    (
+                        "magic",
+                        Json::opt(container.magic.as_ref(), |magic| {
+                            Json::Str(magic.iter().map(|byte| format!("{byte:02x}")).collect())
+                        }),
+                    ),
+                    (
+                        "version",
+                        Json::opt(container.version, |version| Json::Num(version.to_string())),
+                    ),
+                    (
+                        "checksum",
+                        Json::opt(container.checksum, |checksum| {
+                            name_json(&CHECKSUMS, &checksum)
+                        }),
+                    ),
+                ];
+                match &container.body {
+                    Body::Struct { layout, fields } => {
+                        rest.push(("layout", name_json(&LAYOUTS, layout)));
+                        rest.push(("fields", fields_json(fields)));
+                    }
+                    Body::Enum { layout, variants } => {
+                        let variants = variants.iter().map(|variant| {
+                            Json::obj(vec![
+                                ("name", Json::Str(variant.name.clone())),
+                                ("discriminant", Json::Num(variant.discriminant.to_string())),
+                                ("fields", fields_json(&variant.fields)),
+                            ])
+                        });
+                        rest.push(("layout", name_json(&LAYOUTS, layout)));
+                        rest.push(("variants", Json::Arr(variants.collect())));
+                    }
+                    Body::Proxy(schema) => {
+                        rest.push(("schema", json(schema)));
+                    }
+                }
+
+                rest
+            }
+            Kind::Ref(name) => vec![("name", Json::Str(name.clone()))],
+        };
+
+        let mut entries = vec![
+            ("kind", Json::Str(schema.kind.name().to_owned())),
+            (
+                "fixed_size",
+                Json::opt(schema.fixed_size, |size| Json::Num(size.to_string())),
+            ),
+        ];
+        entries.append(&mut rest);
+        Json::obj(entries)
+    }
+}
+
+impl TryFrom<&Json> for Schema {
+    type Error = String;
+
+    fn try_from(json: &Json) -> Result<Self, String> {
+        let get = |key| json.get(key);
+        let schema = |key| Ok::<_, String>(Box::new(Schema::try_from(get(key)?)?));
+
+        let kind = match get("kind")?.str()? {
+            "unit" => Kind::Unit,
+            "bool" => Kind::Bool,
+            "int" => Kind::Int {
+                size: get("size")?.num()?,
+                signed: get("signed")?.bool()?,
+            },
+            "float" => Kind::Float {
+                size: get("size")?.num()?,
+            },
+            "char" => Kind::Char,
+            "str" => Kind::Str,
+            "uuid" => Kind::Uuid,
+            "option" => Kind::Option(schema("some")?),
+            "array" => Kind::Array {
+                item: schema("item")?,
+                len: get("len")?.num()?,
+            },
+            "seq" => Kind::Seq(schema("item")?),
+            "map" => Kind::Map {
+                key: schema("key")?,
+                value: schema("value")?,
+            },
+            "tuple" => Kind::Tuple(
+                get("items")?
+                    .arr()?
+                    .iter()
+                    .map(Schema::try_from)
+                    .collect::<Result<_, _>>()?,
+            ),
+            "varint" => Kind::Varint(schema("inner")?),
+            "compressed" => Kind::Compressed(schema("inner")?),
+            "checked" => Kind::Checked {
+                checksum: from_name(&CHECKSUMS, get("checksum")?)?,
+                inner: schema("inner")?,
+            },
+            "endian" => Kind::Endian {
+                endian: from_name(&ENDIANS, get("endian")?)?,
+                inner: schema("inner")?,
+            },
+            "described" => Kind::Described,
+            "opaque" => Kind::Opaque,
+            "ref" => Kind::Ref(get("name")?.str()?.to_owned()),
+            kind @ ("struct" | "enum" | "proxy") => {
+                let body = match kind {
+                    "struct" => Body::Struct {
+                        layout: from_name(&LAYOUTS, get("layout")?)?,
+                        fields: fields_from_json(get("fields")?)?,
+                    },
+                    "enum" => Body::Enum {
+                        layout: from_name(&LAYOUTS, get("layout")?)?,
+                        variants: get("variants")?
+                            .arr()?
+                            .iter()
+                            .map(|variant| {
+                                Ok(Variant {
+                                    name: variant.get("name")?.str()?.to_owned(),
+                                    discriminant: variant.get("discriminant")?.num()?,
+                                    fields: fields_from_json(variant.get("fields")?)?,
+                                })
+                            })
+                            .collect::<Result<_, String>>()?,
+                    },
+                    _ => Body::Proxy(schema("schema")?),
+                };
+
+                Kind::Container(Box::new(Container {
+                    name: get("name")?.str()?.to_owned(),
+                    magic: get("magic")?.nullable(|magic| {
+                        let magic = magic.str()?;
+                        (0..magic.len())
+                            .step_by(2)
+                            .map(|i| {
+                                magic
+                                    .get(i..i + 2)
+                                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                                    .ok_or_else(|| format!("bad magic `{magic}`"))
+                            })
+                            .collect()
+                    })?,
+                    version: get("version")?.nullable(Json::num)?,
+                    checksum: get("checksum")?.nullable(|json| from_name(&CHECKSUMS, json))?,
+                    body,
+                }))
+            }
+            kind => return Err(format!("unknown kind `{kind}`")),
+        };
+
+        Ok(Schema {
+            fixed_size: get("fixed_size")?.nullable(Json::num)?,
+            kind,
+        })
+    }
+}
+
+fn fields_from_json(json: &Json) -> Result<Vec<Field>, String> {
+    json.arr()?
+        .iter()
+        .map(|field| {
+            Ok(Field {
+                name: field.get("name")?.str()?.to_owned(),
+                id: field.get("id")?.nullable(Json::num)?,
+                since: field.get("since")?.nullable(Json::num)?,
+                bits: field.get("bits")?.nullable(Json::num)?,
+                constant: field.get("constant")?.bool()?,
+                schema: Schema::try_from(field.get("schema")?)?,
+            })
+        })
+        .collect()
+}
+
+fn fields_json(fields: &[Field]) -> Json {
+    Json::Arr(
+        fields
+            .iter()
+            .map(|field| {
+                Json::obj(vec![
+                    ("name", Json::Str(field.name.clone())),
+                    ("id", Json::opt(field.id, |id| Json::Num(id.to_string()))),
+                    (
+                        "since",
+                        Json::opt(field.since, |since| Json::Num(since.to_string())),
+                    ),
+                    (
+                        "bits",
+                        Json::opt(field.bits, |bits| Json::Num(bits.to_string())),
+                    ),
+                    ("constant", Json::Bool(field.constant)),
+                    ("schema", Json::from(&field.schema)),
+                ])
+            })
+            .collect(),
+    )
+}
+
+/// Names of the enums in the export.
+const CHECKSUMS: [(&str, checksum::Checksum); 2] = [
+    ("crc32", checksum::Checksum::Crc32),
+    ("crc32c", checksum::Checksum::Crc32c),
+];
+const ENDIANS: [(&str, Endian); 2] = [("little", Endian::Little), ("big", Endian::Big)];
+const LAYOUTS: [(&str, Layout); 4] = [
+    ("positional", Layout::Positional),
+    ("tagged", Layout::Tagged),
+    ("packed", Layout::Packed),
+    ("transparent", Layout::Transparent),
+];
+
+fn name_json<T: PartialEq>(names: &[(&str, T)], value: &T) -> Json {
+    let (name, _) = names.iter().find(|(_, v)| v == value).unwrap();
+    Json::Str((*name).to_owned())
+}
+
+fn from_name<T: Copy>(names: &[(&str, T)], json: &Json) -> Result<T, String> {
+    let name = json.str()?;
+    names
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, value)| *value)
+        .ok_or_else(|| format!("unknown `{name}`"))
+}
@@ -0,0 +1,567 @@
+//! Compatibility between two versions of a schema, e.g. a snapshot and what the code derives now.
+//! `check` reports every change that keeps payloads written with one from being read with the
+//! other.
+
+use super::*;
+
+use std::fmt;
+
+/// Which payloads a change breaks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Payloads written with the old schema can't be read with the new one.
+    Backward,
+    /// Payloads written with the new schema can't be read with the old one.
+    Forward,
+    Both,
+}
+
+/// A change that breaks compatibility.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Break {
+    /// Where the change is, through field and variant names, `[]` for items, `key` and `value`
+    /// for maps. Empty for the type itself.
+    pub path: String,
+    pub direction: Direction,
+    pub message: String,
+}
+
+impl fmt::Display for Break {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let direction = match self.direction {
+            Direction::Backward => "backward",
+            Direction::Forward => "forward",
+            Direction::Both => "backward and forward",
+        };
+
+        if self.path.is_empty() {
+            write!(f, "{direction}: {}", self.message)
+        } else {
+            write!(f, "{direction}: {}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// Every change from `old` to `new` that breaks compatibility, in either direction.
+pub fn check(old: &Schema, new: &Schema) -> Vec<Break> {
+    let mut checker = Checker { breaks: Vec::new() };
+    checker.schema(old, new, "", (Endian::Little, Endian::Little));
+    checker.breaks
+}
+
+struct Checker {
+    breaks: Vec<Break>,
+}
+
+/// Fields between two length prefixes, like `positional::Unit` in the derive.
+enum Unit<'a> {
+    Field(&'a Field),
+    Bits(Vec<&'a Field>),
+}
+
+fn units(fields: &[Field]) -> Vec<Unit<'_>> {
+    let mut units = Vec::<Unit>::new();
+    for field in fields {
+        match (field.bits.is_some(), units.last_mut()) {
+            (true, Some(Unit::Bits(group))) => group.push(field),
+            (true, _) => units.push(Unit::Bits(vec![field])),
+            (false, _) => units.push(Unit::Field(field)),
+        }
+    }
+
+    units
+}
+
+fn join(path: &str, segment: &str) -> String {
+    if path.is_empty() {
+        segment.to_owned()
+    } else {
+        format!("{path}.{segment}")
+    }
+}
+
+fn endian_name(endian: Endian) -> &'static str {
+    match endian {
+        Endian::Little => "little",
+        Endian::Big => "big",
+    }
+}
+
+impl Checker {
+    fn report(&mut self, path: &str, direction: Direction, message: String) {
+        self.breaks.push(Break {
+            path: path.to_owned(),
+            direction,
+            message,
+        });
+    }
+
+    /// `endians` is the byte order each side is encoded with at this point.
+    fn schema(&mut self, old: &Schema, new: &Schema, path: &str, endians: (Endian, Endian)) {
+        let (old_endian, new_endian) = endians;
+        // Only matters once it reaches something it changes the bytes of.
+        let check_endian = |checker: &mut Self| {
+            if old_endian != new_endian {
+                checker.report(
+                    path,
+                    Direction::Both,
+                    format!(
+                        "byte order changed from {} to {} endian",
+                        endian_name(old_endian),
+                        endian_name(new_endian),
+                    ),
+                );
+            }
+        };
+
+        match (&old.kind, &new.kind) {
+            (Kind::Endian { endian, inner }, _) => {
+                self.schema(inner, new, path, (*endian, new_endian))
+            }
+            (_, Kind::Endian { endian, inner }) => {
+                self.schema(old, inner, path, (old_endian, *endian))
+            }
+            (Kind::Unit, Kind::Unit)
+            | (Kind::Bool, Kind::Bool)
+            | (Kind::Char, Kind::Char)
+            | (Kind::Str, Kind::Str)
+            | (Kind::Described, Kind::Described)
+            | (Kind::Opaque, Kind::Opaque) => {}
+            (
+                Kind::Int {
+                    size: old_size,
+                    signed: old_signed,
+                },
+                Kind::Int {
+                    size: new_size,
+                    signed: new_signed,
+                },
+            ) => {
+                if old_size != new_size {
+                    self.report(
+                        path,
+                        Direction::Both,
+                        format!("size changed from {old_size} to {new_size} bytes"),
+                    );
+                } else if old_signed != new_signed {
+                    self.report(path, Direction::Both, "signedness changed".to_owned());
+                } else if *old_size > 1 {
+                    check_endian(self);
+                }
+            }
+            (Kind::Float { size: old_size }, Kind::Float { size: new_size }) => {
+                if old_size != new_size {
+                    self.report(
+                        path,
+                        Direction::Both,
+                        format!("size changed from {old_size} to {new_size} bytes"),
+                    );
+                } else {
+                    check_endian(self);
+                }
+            }
+            (Kind::Uuid, Kind::Uuid) => check_endian(self),
+            (Kind::Option(old), Kind::Option(new)) => self.schema(old, new, path, endians),
+            (
+                Kind::Array {
+                    item: old_item,
+                    len: old_len,
+                },
+                Kind::Array {
+                    item: new_item,
+                    len: new_len,
+                },
+            ) => {
+                if old_len != new_len {
+                    self.report(
+                        path,
+                        Direction::Both,
+                        format!("length changed from {old_len} to {new_len}"),
+                    );
+                }
+                if old_item.fixed_size.is_some() != new_item.fixed_size.is_some() {
+                    self.report(
+                        path,
+                        Direction::Both,
+                        "items switched between fixed size and length prefixed".to_owned(),
+                    );
+                }
+                self.schema(old_item, new_item, &join(path, "[]"), endians);
+            }
+            (Kind::Seq(old), Kind::Seq(new)) => self.schema(old, new, &join(path, "[]"), endians),
+            (
+                Kind::Map {
+                    key: old_key,
+                    value: old_value,
+                },
+                Kind::Map {
+                    key: new_key,
+                    value: new_value,
+                },
+            ) => {
+                self.schema(old_key, new_key, &join(path, "key"), endians);
+                self.schema(old_value, new_value, &join(path, "value"), endians);
+            }
+            (Kind::Tuple(old), Kind::Tuple(new)) => {
+                if old.len() != new.len() {
+                    self.report(
+                        path,
+                        Direction::Both,
+                        format!("tuple went from {} to {} items", old.len(), new.len()),
+                    );
+                    return;
+                }
+                for (i, (old, new)) in old.iter().zip(new).enumerate() {
+                    self.schema(old, new, &join(path, &i.to_string()), endians);
+                }
+            }
+            (Kind::Varint(old), Kind::Varint(new)) => self.varint(old, new, path),
+            (Kind::Compressed(old), Kind::Compressed(new)) => self.schema(old, new, path, endians),
+            (
+                Kind::Checked {
+                    checksum: old_checksum,
+                    inner: old_inner,
+                },
+                Kind::Checked {
+                    checksum: new_checksum,
+                    inner: new_inner,
+                },
+            ) => {
+                if old_checksum != new_checksum {
+                    self.report(path, Direction::Both, "checksum changed".to_owned());
+                } else if old_endian != new_endian {
+                    self.report(
+                        path,
+                        Direction::Both,
+                        "checksum byte order changed".to_owned(),
+                    );
+                }
+                self.schema(old_inner, new_inner, path, endians);
+            }
+            (Kind::Container(old), Kind::Container(new)) => self.container(old, new, path, endians),
+            (Kind::Ref(old), Kind::Ref(new)) => {
+                if old != new {
+                    self.report(
+                        path,
+                        Direction::Both,
+                        format!("refers to `{new}` instead of `{old}`"),
+                    );
+                }
+            }
+            (old, new) => self.report(
+                path,
+                Direction::Both,
+                format!("changed from {} to {}", old.name(), new.name()),
+            ),
+        }
+    }
+
+    /// Varints only care about the range of the integer, widening one is fine until a value
+    /// that doesn't fit the narrower type shows up.
+    fn varint(&mut self, old: &Schema, new: &Schema, path: &str) {
+        let (
+            Kind::Int {
+                size: old_size,
+                signed: old_signed,
+            },
+            Kind::Int {
+                size: new_size,
+                signed: new_signed,
+            },
+        ) = (&old.kind, &new.kind)
+        else {
+            return self.schema(old, new, path, (Endian::Little, Endian::Little));
+        };
+
+        if old_signed != new_signed {
+            self.report(path, Direction::Both, "signedness changed".to_owned());
+        } else if new_size < old_size {
+            self.report(
+                path,
+                Direction::Backward,
+                format!("narrowed from {old_size} to {new_size} bytes"),
+            );
+        } else if new_size > old_size {
+            self.report(
+                path,
+                Direction::Forward,
+                format!("widened from {old_size} to {new_size} bytes"),
+            );
+        }
+    }
+
+    fn container(
+        &mut self,
+        old: &Container,
+        new: &Container,
+        path: &str,
+        endians: (Endian, Endian),
+    ) {
+        if old.magic != new.magic {
+            self.report(path, Direction::Both, "magic changed".to_owned());
+        }
+        match (old.version, new.version) {
+            (Some(old_version), Some(new_version)) if new_version > old_version => self.report(
+                path,
+                Direction::Forward,
+                format!("version {old_version} rejects payloads of version {new_version}"),
+            ),
+            (Some(_), Some(_)) | (None, None) => {}
+            _ => self.report(
+                path,
+                Direction::Both,
+                "version byte added or removed".to_owned(),
+            ),
+        }
+        if old.checksum != new.checksum {
+            self.report(path, Direction::Both, "checksum changed".to_owned());
+        } else if old.checksum.is_some() && endians.0 != endians.1 {
+            self.report(
+                path,
+                Direction::Both,
+                "checksum byte order changed".to_owned(),
+            );
+        }
+
+        let versions = (old.version, new.version);
+        match (&old.body, &new.body) {
+            (
+                Body::Struct {
+                    layout: old_layout,
+                    fields: old_fields,
+                },
+                Body::Struct {
+                    layout: new_layout,
+                    fields: new_fields,
+                },
+            ) => {
+                if old_layout != new_layout {
+                    self.report(path, Direction::Both, "layout changed".to_owned());
+                    return;
+                }
+                self.fields(*old_layout, old_fields, new_fields, versions, path, endians);
+            }
+            (
+                Body::Enum {
+                    layout: old_layout,
+                    variants: old_variants,
+                },
+                Body::Enum {
+                    layout: new_layout,
+                    variants: new_variants,
+                },
+            ) => {
+                if old_layout != new_layout {
+                    self.report(path, Direction::Both, "layout changed".to_owned());
+                    return;
+                }
+
+                for old_variant in old_variants {
+                    let variant_path = join(path, &old_variant.name);
+                    let Some(new_variant) = new_variants
+                        .iter()
+                        .find(|v| v.discriminant == old_variant.discriminant)
+                    else {
+                        match new_variants.iter().find(|v| v.name == old_variant.name) {
+                            Some(moved) => self.report(
+                                &variant_path,
+                                Direction::Both,
+                                format!(
+                                    "variant renumbered from {} to {}",
+                                    old_variant.discriminant, moved.discriminant
+                                ),
+                            ),
+                            None => self.report(
+                                &variant_path,
+                                Direction::Backward,
+                                format!("variant removed, was {}", old_variant.discriminant),
+                            ),
+                        }
+                        continue;
+                    };
+
+                    self.fields(
+                        *old_layout,
+                        &old_variant.fields,
+                        &new_variant.fields,
+                        versions,
+                        &variant_path,
+                        endians,
+                    );
+                }
+
+                // Renumbered ones were reported with the old variants.
+                for new_variant in new_variants {
+                    if !old_variants.iter().any(|v| {
+                        v.discriminant == new_variant.discriminant || v.name == new_variant.name
+                    }) {
+                        self.report(
+                            &join(path, &new_variant.name),
+                            Direction::Forward,
+                            format!("variant added as {}", new_variant.discriminant),
+                        );
+                    }
+                }
+            }
+            (Body::Proxy(old), Body::Proxy(new)) => self.schema(old, new, path, endians),
+            _ => self.report(
+                path,
+                Direction::Both,
+                format!("changed from {} to {}", old.body.name(), new.body.name()),
+            ),
+        }
+    }
+
+    fn fields(
+        &mut self,
+        layout: Layout,
+        old: &[Field],
+        new: &[Field],
+        versions: (Option<u8>, Option<u8>),
+        path: &str,
+        endians: (Endian, Endian),
+    ) {
+        match layout {
+            // Fields are found by id, unknown ones are skipped and missing ones defaulted.
+            Layout::Tagged => {
+                for old_field in old {
+                    let Some(new_field) = new.iter().find(|f| f.id == old_field.id) else {
+                        continue;
+                    };
+
+                    let field_path = join(path, &new_field.name);
+                    if new_field.name != old_field.name {
+                        self.report(
+                            &field_path,
+                            Direction::Both,
+                            format!(
+                                "id {} reused, it was `{}`",
+                                new_field.id.unwrap_or_default(),
+                                old_field.name
+                            ),
+                        );
+                    }
+                    self.schema(&old_field.schema, &new_field.schema, &field_path, endians);
+                }
+            }
+            Layout::Transparent => {
+                if let (Some(old_field), Some(new_field)) = (old.first(), new.first()) {
+                    self.schema(&old_field.schema, &new_field.schema, path, endians);
+                }
+            }
+            Layout::Positional | Layout::Packed => {
+                let old_units = units(old);
+                let new_units = units(new);
+
+                // A lone field isn't length prefixed unless the container has a version, going
+                // from one field to more changes the framing of the first one.
+                if layout == Layout::Positional
+                    && versions.0.is_none()
+                    && !old_units.is_empty()
+                    && !new_units.is_empty()
+                    && (old_units.len() == 1) != (new_units.len() == 1)
+                {
+                    self.report(
+                        path,
+                        Direction::Both,
+                        format!(
+                            "went from {} to {} length prefixed fields, a lone one has no prefix",
+                            old_units.len(),
+                            new_units.len()
+                        ),
+                    );
+                    return;
+                }
+
+                for (old_unit, new_unit) in old_units.iter().zip(&new_units) {
+                    match (old_unit, new_unit) {
+                        (Unit::Field(old_field), Unit::Field(new_field)) => {
+                            self.positional_field(old, old_field, new_field, path, endians);
+                        }
+                        (Unit::Bits(old_group), Unit::Bits(new_group)) => {
+                            let widths =
+                                |group: &[&Field]| group.iter().map(|f| f.bits).collect::<Vec<_>>();
+                            if widths(old_group) != widths(new_group) {
+                                self.report(
+                                    &join(path, &new_group[0].name),
+                                    Direction::Both,
+                                    "bit fields changed".to_owned(),
+                                );
+                                continue;
+                            }
+                            for (old_field, new_field) in old_group.iter().zip(new_group) {
+                                self.positional_field(old, old_field, new_field, path, endians);
+                            }
+                        }
+                        (Unit::Field(field), _) | (Unit::Bits(_), Unit::Field(field)) => {
+                            self.report(
+                                &join(path, &field.name),
+                                Direction::Both,
+                                "changed between a bit field and a whole one".to_owned(),
+                            );
+                        }
+                    }
+                }
+
+                for unit in old_units.iter().skip(new_units.len()) {
+                    for field in unit_fields(unit) {
+                        self.report(
+                            &join(path, &field.name),
+                            Direction::Forward,
+                            "field removed".to_owned(),
+                        );
+                    }
+                }
+
+                // Payloads written before a field was added are fine without it, as long as
+                // their version tells the decoder so.
+                let (old_version, _) = versions;
+                for unit in new_units.iter().skip(old_units.len()) {
+                    for field in unit_fields(unit) {
+                        let defaulted = matches!(
+                            (field.since, old_version),
+                            (Some(since), Some(version)) if since > version
+                        );
+                        if !defaulted {
+                            self.report(
+                                &join(path, &field.name),
+                                Direction::Backward,
+                                "field added without a #[sadby(since)] newer than the old version"
+                                    .to_owned(),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// A field found at the same position on both sides.
+    fn positional_field(
+        &mut self,
+        old_fields: &[Field],
+        old: &Field,
+        new: &Field,
+        path: &str,
+        endians: (Endian, Endian),
+    ) {
+        let field_path = join(path, &new.name);
+        if new.name != old.name && old_fields.iter().any(|f| f.name == new.name) {
+            self.report(
+                &field_path,
+                Direction::Both,
+                format!("field moved, `{}` used to be here", old.name),
+            );
+            // Comparing it with whatever used to be here would only repeat that.
+            return;
+        }
+        self.schema(&old.schema, &new.schema, &field_path, endians);
+    }
+}
+
+fn unit_fields<'a>(unit: &Unit<'a>) -> Vec<&'a Field> {
+    match unit {
+        Unit::Field(field) => vec![field],
+        Unit::Bits(group) => group.clone(),
+    }
+}
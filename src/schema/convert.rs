@@ -0,0 +1,529 @@
+//! Values as JSON documents shaped by their schema, for people and tools that read and write
+//! payloads without the Rust types, e.g. the `sadby` binary.
+//!
+//! Structs are objects keyed by field name, tagged ones too. Unit variants are their name as a
+//! string, others an object with the name as its one key. Maps with string keys are objects,
+//! other maps arrays of `[key, value]` pairs. Uuids are hyphenated strings, opaque bytes hex
+//! strings, and floats that aren't finite the strings `NaN`, `inf` and `-inf`. Options are `null`
+//! or their value, so a `Some(None)` reads back as `None`.
+
+use super::*;
+
+use super::decode::{extend, int, int_name};
+
+impl Schema {
+    /// `value`, e.g. one `decode` gives, as pretty printed JSON. Parts that don't fit the schema
+    /// are written like `Kind::Described` values are, without one.
+    pub fn value_to_json(&self, value: &Value) -> String {
+        let mut out = String::new();
+        Converter::default().json(self, value).write(&mut out, 0);
+        out
+    }
+
+    /// Reads a value of this schema out of JSON written like `value_to_json` does, ready for
+    /// `encode`. Fields left out are left out of the value as well.
+    pub fn value_from_json(&self, input: &str) -> Result<Value, SadbyError> {
+        let json = Json::parse(input).map_err(|e| SadbyError::Custom(format!("Bad JSON: {e}")))?;
+        let mut converter = Converter::default();
+        converter.value(self, &json).map_err(|e| {
+            let path = converter.path.concat();
+            match path.trim_start_matches('.') {
+                "" => SadbyError::Custom(format!("Bad value: {e}")),
+                path => SadbyError::Custom(format!("Bad value at `{path}`: {e}")),
+            }
+        })
+    }
+
+    /// `value` as JSON without a schema, like `Kind::Described` values are written.
+    pub(super) fn value_json(value: &Value) -> String {
+        let mut out = String::new();
+        generic(value).write(&mut out, 0);
+        out
+    }
+}
+
+#[derive(Default)]
+struct Converter<'a> {
+    /// Containers being converted, innermost last, for `Kind::Ref` to find.
+    containers: Vec<&'a Container>,
+    /// Where the value being read from JSON is, e.g. `.header`, `[2]`, left as it is on errors.
+    path: Vec<String>,
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>, String> {
+    let digits = hex.as_bytes();
+    if !digits.len().is_multiple_of(2) {
+        return Err("expected an even number of hex digits".to_owned());
+    }
+
+    digits
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| format!("bad hex digits `{}`", String::from_utf8_lossy(pair)))
+        })
+        .collect()
+}
+
+fn float(value: f64) -> Json {
+    match value {
+        value if value.is_finite() => Json::Num(value.to_string()),
+        value if value.is_nan() => Json::Str("NaN".to_owned()),
+        value if value > 0.0 => Json::Str("inf".to_owned()),
+        _ => Json::Str("-inf".to_owned()),
+    }
+}
+
+/// `value` as JSON, as far as it can tell from the value alone.
+fn generic(value: &Value) -> Json {
+    match value {
+        Value::Unit | Value::None => Json::Null,
+        Value::Bool(b) => Json::Bool(*b),
+        Value::U8(n) => Json::Num(n.to_string()),
+        Value::U16(n) => Json::Num(n.to_string()),
+        Value::U32(n) => Json::Num(n.to_string()),
+        Value::U64(n) => Json::Num(n.to_string()),
+        Value::U128(n) => Json::Num(n.to_string()),
+        Value::I8(n) => Json::Num(n.to_string()),
+        Value::I16(n) => Json::Num(n.to_string()),
+        Value::I32(n) => Json::Num(n.to_string()),
+        Value::I64(n) => Json::Num(n.to_string()),
+        Value::I128(n) => Json::Num(n.to_string()),
+        // Through its shortest decimal form, which reads back as the same `f32`.
+        Value::F32(n) => float(n.to_string().parse().unwrap_or(*n as f64)),
+        Value::F64(n) => float(*n),
+        Value::Char(c) => Json::Str(c.to_string()),
+        Value::Str(s) => Json::Str(s.clone()),
+        Value::Bytes(bytes) => Json::Str(hex(bytes)),
+        Value::Some(value) => generic(value),
+        Value::Seq(items) | Value::Tuple(items) => Json::Arr(items.iter().map(generic).collect()),
+        Value::Map(entries) => Json::Arr(
+            entries
+                .iter()
+                .map(|(key, value)| Json::Arr(vec![generic(key), generic(value)]))
+                .collect(),
+        ),
+        Value::Struct(fields) => Json::Obj(
+            fields
+                .iter()
+                .map(|(name, value)| (name.clone(), generic(value)))
+                .collect(),
+        ),
+        Value::StructIds(fields) => Json::Obj(
+            fields
+                .iter()
+                .map(|(id, value)| (id.to_string(), generic(value)))
+                .collect(),
+        ),
+        Value::Variant { name, value, .. } => match **value {
+            Value::Unit => Json::Str(name.clone()),
+            ref value => Json::Obj(vec![(name.clone(), generic(value))]),
+        },
+    }
+}
+
+/// A value out of JSON without a schema, integers as the smallest of `i64`, `u64` and 128 bit
+/// ones they fit, objects as structs.
+fn generic_from_json(json: &Json) -> Result<Value, String> {
+    Ok(match json {
+        Json::Null => Value::Unit,
+        Json::Bool(b) => Value::Bool(*b),
+        Json::Num(n) if n.contains(['.', 'e', 'E']) => Value::F64(json.num()?),
+        Json::Num(n) => match (n.parse::<i64>(), n.parse::<u64>()) {
+            (Ok(n), _) => Value::I64(n),
+            (_, Ok(n)) => Value::U64(n),
+            _ => match n.starts_with('-') {
+                true => Value::I128(json.num()?),
+                false => Value::U128(json.num()?),
+            },
+        },
+        Json::Str(s) => Value::Str(s.clone()),
+        Json::Arr(items) => Value::Seq(
+            items
+                .iter()
+                .map(generic_from_json)
+                .collect::<Result<_, _>>()?,
+        ),
+        Json::Obj(entries) => Value::Struct(
+            entries
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), generic_from_json(value)?)))
+                .collect::<Result<_, String>>()?,
+        ),
+    })
+}
+
+/// Whether values of `schema` are strings in JSON, so maps keyed by them can be objects.
+fn is_str(schema: &Schema) -> bool {
+    match &schema.kind {
+        Kind::Str => true,
+        Kind::Endian { inner, .. } | Kind::Compressed(inner) | Kind::Checked { inner, .. } => {
+            is_str(inner)
+        }
+        _ => false,
+    }
+}
+
+impl<'a> Converter<'a> {
+    fn resolve(&self, name: &str) -> Option<&'a Container> {
+        self.containers
+            .iter()
+            .rev()
+            .find(|c| c.name == name)
+            .copied()
+    }
+
+    fn json(&mut self, schema: &'a Schema, value: &Value) -> Json {
+        match (&schema.kind, value) {
+            (
+                Kind::Varint(inner)
+                | Kind::Compressed(inner)
+                | Kind::Checked { inner, .. }
+                | Kind::Endian { inner, .. },
+                value,
+            ) => self.json(inner, value),
+            (Kind::Uuid, Value::Bytes(bytes)) if bytes.len() == 16 => {
+                let hex = hex(bytes);
+                Json::Str(format!(
+                    "{}-{}-{}-{}-{}",
+                    &hex[..8],
+                    &hex[8..12],
+                    &hex[12..16],
+                    &hex[16..20],
+                    &hex[20..]
+                ))
+            }
+            (Kind::Option(some), Value::Some(value)) => self.json(some, value),
+            (Kind::Array { item, .. } | Kind::Seq(item), Value::Seq(items)) => {
+                Json::Arr(items.iter().map(|value| self.json(item, value)).collect())
+            }
+            (Kind::Map { key, value }, Value::Map(entries)) if is_str(key) => Json::Obj(
+                entries
+                    .iter()
+                    .map(|(k, v)| match k {
+                        Value::Str(k) => (k.clone(), self.json(value, v)),
+                        k => (Schema::value_json(k), self.json(value, v)),
+                    })
+                    .collect(),
+            ),
+            (Kind::Map { key, value }, Value::Map(entries)) => Json::Arr(
+                entries
+                    .iter()
+                    .map(|(k, v)| Json::Arr(vec![self.json(key, k), self.json(value, v)]))
+                    .collect(),
+            ),
+            (Kind::Tuple(items), Value::Tuple(values)) if items.len() == values.len() => Json::Arr(
+                items
+                    .iter()
+                    .zip(values)
+                    .map(|(item, value)| self.json(item, value))
+                    .collect(),
+            ),
+            (Kind::Container(container), value) => self.container_to_json(container, value),
+            (Kind::Ref(name), value) => match self.resolve(name) {
+                Some(container) => self.container_to_json(container, value),
+                None => generic(value),
+            },
+            (_, value) => generic(value),
+        }
+    }
+
+    fn container_to_json(&mut self, container: &'a Container, value: &Value) -> Json {
+        self.containers.push(container);
+        let json = match (&container.body, value) {
+            (
+                Body::Struct {
+                    layout: Layout::Transparent,
+                    fields,
+                },
+                value,
+            ) if fields.len() == 1 => self.json(&fields[0].schema, value),
+            (Body::Struct { fields, .. }, value) => self.fields_to_json(fields, value),
+            (Body::Enum { variants, .. }, Value::Variant { name, value, .. }) => {
+                match (variants.iter().find(|v| v.name == *name), &**value) {
+                    (_, Value::Unit) => Json::Str(name.clone()),
+                    (Some(variant), value) => Json::Obj(vec![(
+                        name.clone(),
+                        self.fields_to_json(&variant.fields, value),
+                    )]),
+                    (None, value) => Json::Obj(vec![(name.clone(), generic(value))]),
+                }
+            }
+            (Body::Proxy(schema), value) => self.json(schema, value),
+            (_, value) => generic(value),
+        };
+        self.containers.pop();
+        json
+    }
+
+    fn fields_to_json(&mut self, fields: &'a [Field], value: &Value) -> Json {
+        match value {
+            Value::Struct(values) => Json::Obj(
+                values
+                    .iter()
+                    .map(
+                        |(name, value)| match fields.iter().find(|f| f.name == *name) {
+                            Some(field) => (name.clone(), self.json(&field.schema, value)),
+                            None => (name.clone(), generic(value)),
+                        },
+                    )
+                    .collect(),
+            ),
+            Value::StructIds(values) => Json::Obj(
+                values
+                    .iter()
+                    .map(
+                        |(id, value)| match fields.iter().find(|f| f.id == Some(*id)) {
+                            Some(field) => (field.name.clone(), self.json(&field.schema, value)),
+                            None => (id.to_string(), generic(value)),
+                        },
+                    )
+                    .collect(),
+            ),
+            value => generic(value),
+        }
+    }
+
+    /// Runs `f` with `segment` added to the path, which stays there if it fails.
+    fn within<T>(
+        &mut self,
+        segment: String,
+        f: impl FnOnce(&mut Self) -> Result<T, String>,
+    ) -> Result<T, String> {
+        self.path.push(segment);
+        let value = f(self)?;
+        self.path.pop();
+        Ok(value)
+    }
+
+    fn items(&mut self, item: &'a Schema, items: &[Json]) -> Result<Vec<Value>, String> {
+        items
+            .iter()
+            .enumerate()
+            .map(|(i, json)| self.within(format!("[{i}]"), |c| c.value(item, json)))
+            .collect()
+    }
+
+    fn value(&mut self, schema: &'a Schema, json: &Json) -> Result<Value, String> {
+        Ok(match &schema.kind {
+            Kind::Unit => match json {
+                Json::Null => Value::Unit,
+                _ => return Err("expected null".to_owned()),
+            },
+            Kind::Bool => Value::Bool(json.bool()?),
+            Kind::Int { size, signed } => {
+                let width = *size as u32 * 8;
+                let raw = match signed {
+                    false => json.num::<u128>()?,
+                    true => json.num::<i128>()? as u128,
+                };
+                let fits = match signed {
+                    _ if width >= 128 => true,
+                    false => raw >> width == 0,
+                    true => extend(raw, width, true) == raw,
+                };
+                if !fits {
+                    return Err(format!("out of range for {}", int_name(*size, *signed)));
+                }
+                int(*size, *signed, raw).map_err(|_| format!("no {size} byte integers"))?
+            }
+            Kind::Float { size: 4 } => Value::F32(match json {
+                Json::Str(s) => s.parse().map_err(|_| format!("`{s}` isn't a float"))?,
+                json => json.num()?,
+            }),
+            Kind::Float { .. } => Value::F64(match json {
+                Json::Str(s) => s.parse().map_err(|_| format!("`{s}` isn't a float"))?,
+                json => json.num()?,
+            }),
+            Kind::Char => {
+                let mut chars = json.str()?.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Value::Char(c),
+                    _ => return Err("expected a single character".to_owned()),
+                }
+            }
+            Kind::Str => Value::Str(json.str()?.to_owned()),
+            Kind::Uuid => {
+                let bytes = from_hex(&json.str()?.replace('-', ""))?;
+                if bytes.len() != 16 {
+                    return Err("expected a uuid".to_owned());
+                }
+                Value::Bytes(bytes)
+            }
+            Kind::Option(some) => match json {
+                Json::Null => Value::None,
+                json => Value::Some(Box::new(self.value(some, json)?)),
+            },
+            Kind::Array { item, len } => {
+                let items = json.arr()?;
+                if items.len() != *len {
+                    return Err(format!("expected {len} items, found {}", items.len()));
+                }
+                Value::Seq(self.items(item, items)?)
+            }
+            Kind::Seq(item) => Value::Seq(self.items(item, json.arr()?)?),
+            Kind::Map { key, value } => match json {
+                Json::Obj(entries) if is_str(key) => Value::Map(
+                    entries
+                        .iter()
+                        .map(|(k, v)| {
+                            let v = self.within(format!(".{k}"), |c| c.value(value, v))?;
+                            Ok((Value::Str(k.clone()), v))
+                        })
+                        .collect::<Result<_, String>>()?,
+                ),
+                json => Value::Map(
+                    json.arr()?
+                        .iter()
+                        .enumerate()
+                        .map(|(i, entry)| {
+                            self.within(format!("[{i}]"), |c| match entry.arr()? {
+                                [k, v] => Ok((c.value(key, k)?, c.value(value, v)?)),
+                                _ => Err("expected a `[key, value]` pair".to_owned()),
+                            })
+                        })
+                        .collect::<Result<_, String>>()?,
+                ),
+            },
+            Kind::Tuple(items) => {
+                let values = json.arr()?;
+                if values.len() != items.len() {
+                    return Err(format!(
+                        "expected {} items, found {}",
+                        items.len(),
+                        values.len()
+                    ));
+                }
+                Value::Tuple(
+                    items
+                        .iter()
+                        .zip(values)
+                        .enumerate()
+                        .map(|(i, (item, json))| {
+                            self.within(format!(".{i}"), |c| c.value(item, json))
+                        })
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            Kind::Varint(inner)
+            | Kind::Compressed(inner)
+            | Kind::Checked { inner, .. }
+            | Kind::Endian { inner, .. } => self.value(inner, json)?,
+            Kind::Described => generic_from_json(json)?,
+            Kind::Opaque => Value::Bytes(from_hex(json.str()?)?),
+            Kind::Container(container) => self.container_from_json(container, json)?,
+            Kind::Ref(name) => {
+                let container = self
+                    .resolve(name)
+                    .ok_or_else(|| format!("`{name}` refers to no container around it"))?;
+                self.container_from_json(container, json)?
+            }
+        })
+    }
+
+    fn container_from_json(
+        &mut self,
+        container: &'a Container,
+        json: &Json,
+    ) -> Result<Value, String> {
+        self.containers.push(container);
+        let value = match &container.body {
+            Body::Struct {
+                layout: Layout::Transparent,
+                fields,
+            } => match fields.as_slice() {
+                [field] => self.value(&field.schema, json)?,
+                _ => {
+                    return Err(format!(
+                        "transparent `{}` needs exactly one field",
+                        container.name
+                    ));
+                }
+            },
+            Body::Struct { layout, fields } => self.fields_from_json(*layout, fields, json)?,
+            Body::Enum { layout, variants } => {
+                let (name, fields) = match json {
+                    Json::Str(name) => (name, None),
+                    Json::Obj(entries) if entries.len() == 1 => {
+                        (&entries[0].0, Some(&entries[0].1))
+                    }
+                    _ => return Err("expected a variant name or an object with one".to_owned()),
+                };
+                let variant = variants
+                    .iter()
+                    .find(|v| v.name == *name)
+                    .ok_or_else(|| format!("no variant `{name}` in `{}`", container.name))?;
+
+                let value = match (variant.fields.is_empty(), fields) {
+                    (true, None | Some(Json::Null)) => Value::Unit,
+                    (true, Some(_)) => return Err(format!("variant `{name}` has no fields")),
+                    (false, None) => return Err(format!("variant `{name}` needs its fields")),
+                    (false, Some(fields)) => self.within(format!(".{name}"), |c| {
+                        c.fields_from_json(*layout, &variant.fields, fields)
+                    })?,
+                };
+                Value::Variant {
+                    discriminant: variant.discriminant,
+                    name: variant.name.clone(),
+                    value: Box::new(value),
+                }
+            }
+            Body::Proxy(schema) => self.value(schema, json)?,
+        };
+        self.containers.pop();
+        Ok(value)
+    }
+
+    fn fields_from_json(
+        &mut self,
+        layout: Layout,
+        fields: &'a [Field],
+        json: &Json,
+    ) -> Result<Value, String> {
+        let Json::Obj(entries) = json else {
+            return Err("expected an object".to_owned());
+        };
+        if let Some((key, _)) = entries
+            .iter()
+            .find(|(key, _)| fields.iter().all(|field| field.name != *key))
+        {
+            return Err(format!("unknown field `{key}`"));
+        }
+
+        let mut values = Vec::new();
+        for field in fields {
+            let Some((_, json)) = entries.iter().find(|(key, _)| *key == field.name) else {
+                continue;
+            };
+            let value =
+                self.within(format!(".{}", field.name), |c| c.value(&field.schema, json))?;
+            values.push((field, value));
+        }
+
+        Ok(match layout {
+            Layout::Tagged => Value::StructIds(
+                values
+                    .into_iter()
+                    .map(|(field, value)| {
+                        let id = field
+                            .id
+                            .ok_or_else(|| format!("tagged field `{}` has no id", field.name))?;
+                        Ok((id, value))
+                    })
+                    .collect::<Result<_, String>>()?,
+            ),
+            _ => Value::Struct(
+                values
+                    .into_iter()
+                    .map(|(field, value)| (field.name.clone(), value))
+                    .collect(),
+            ),
+        })
+    }
+}
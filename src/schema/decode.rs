@@ -0,0 +1,694 @@
+//! Decoding positional payloads with nothing but their schema, e.g. one loaded with
+//! `Schema::from_json`, for code that can't link the types the payloads were written with.
+
+use super::*;
+
+use std::cmp::Reverse;
+use std::ops::Range;
+use std::slice::SliceIndex;
+
+impl Schema {
+    /// Decodes `input` like `de_bytes` of the described type would, into the `Value`
+    /// `value::to_value` gives for what it decodes to. Malformed input fails with the same
+    /// errors, except that running out of input is an `UnexpectedToken` instead of a panic.
+    ///
+    /// The schema doesn't know the defaults of fields, so fields the payload doesn't carry, those
+    /// newer than its version and missing tagged ones, are left out of the value. Converting it
+    /// with `value::from_value` fills them in. `#[sadby(constant)]` values and
+    /// `#[sadby(validate)]` checks aren't part of the schema either and aren't checked.
+    pub fn decode(&self, input: &[u8]) -> Result<Value, SadbyError> {
+        Decoder::new(input, false).schema(self, input, Endian::Little)
+    }
+
+    /// Decodes `input` like `decode`, also telling what each range of bytes read is, ordered by
+    /// where they start. Ranges nest, a field's length prefix and value come after the range of
+    /// the whole struct. What's inside compressed bytes isn't annotated.
+    ///
+    /// Decoding stops at the first error, which is returned along with what was read up to it.
+    pub fn annotate(&self, input: &[u8]) -> (Vec<Annotation>, Result<Value, SadbyError>) {
+        let mut decoder = Decoder::new(input, true);
+        let value = decoder.schema(self, input, Endian::Little);
+
+        let mut notes = decoder.notes.unwrap_or_default();
+        notes.sort_by_key(|note| (note.range.start, Reverse(note.range.end)));
+        (notes, value)
+    }
+}
+
+/// What a range of bytes in a payload is, see `Schema::annotate`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Annotation {
+    pub range: Range<usize>,
+    /// The field the bytes belong to, e.g. `header.items[2]`, empty for the payload itself.
+    pub path: String,
+    /// What the bytes are, e.g. `length 4` or `u32 7`.
+    pub note: String,
+}
+
+struct Decoder<'a> {
+    /// Containers being decoded, innermost last, for `Kind::Ref` to find.
+    containers: Vec<&'a Container>,
+    /// Addresses of the payload, slices of it are annotated with their offset into it.
+    base: Range<usize>,
+    /// What was read so far, when annotating.
+    notes: Option<Vec<Annotation>>,
+    /// Where the field being read is, e.g. `.header`, `[2]`, only kept when annotating.
+    path: Vec<String>,
+}
+
+pub(super) fn bad_schema(message: String) -> SadbyError {
+    SadbyError::Custom(format!("Bad schema: {message}"))
+}
+
+fn get<I: SliceIndex<[u8]>>(input: &[u8], index: I) -> Result<&I::Output, SadbyError> {
+    input.get(index).ok_or(SadbyError::UnexpectedToken)
+}
+
+/// Where the bytes prefixed with their length at `current` are, and where the next ones start.
+fn prefixed(input: &[u8], current: usize) -> Result<(Range<usize>, usize), SadbyError> {
+    let next = current + *get(input, current)? as usize;
+    get(input, current + 1..=next)?;
+    Ok((current + 1..next + 1, next + 1))
+}
+
+/// An integer of the given size out of its two's complement, sign extended to 128 bits.
+pub(super) fn int(size: usize, signed: bool, raw: u128) -> Result<Value, SadbyError> {
+    Ok(match (size, signed) {
+        (1, false) => Value::U8(raw as u8),
+        (2, false) => Value::U16(raw as u16),
+        (4, false) => Value::U32(raw as u32),
+        (8, false) => Value::U64(raw as u64),
+        (16, false) => Value::U128(raw),
+        (1, true) => Value::I8(raw as i8),
+        (2, true) => Value::I16(raw as i16),
+        (4, true) => Value::I32(raw as i32),
+        (8, true) => Value::I64(raw as i64),
+        (16, true) => Value::I128(raw as i128),
+        _ => return Err(bad_schema(format!("no {size} byte integers"))),
+    })
+}
+
+/// The first `size` bytes of `input` in the given byte order, zero or sign extended.
+fn raw_int(input: &[u8], size: usize, signed: bool, endian: Endian) -> Result<u128, SadbyError> {
+    if size == 0 || size > 16 {
+        return Err(bad_schema(format!("no {size} byte integers")));
+    }
+
+    let mut bytes = get(input, ..size)?.to_vec();
+    if endian == Endian::Big {
+        bytes.reverse();
+    }
+    let raw = bytes
+        .iter()
+        .rev()
+        .fold(0u128, |raw, &byte| raw << 8 | byte as u128);
+
+    Ok(extend(raw, size as u32 * 8, signed))
+}
+
+/// Sign extends the low `width` bits of `raw` if `signed`.
+pub(super) fn extend(raw: u128, width: u32, signed: bool) -> u128 {
+    let shift = 128 - width;
+    if signed && shift > 0 {
+        (((raw << shift) as i128) >> shift) as u128
+    } else {
+        raw
+    }
+}
+
+/// What an integer kind is called in Rust, e.g. `u32`.
+pub(super) fn int_name(size: usize, signed: bool) -> String {
+    format!("{}{}", if signed { 'i' } else { 'u' }, size * 8)
+}
+
+impl<'a> Decoder<'a> {
+    fn new(input: &[u8], annotate: bool) -> Self {
+        let start = input.as_ptr() as usize;
+        Self {
+            containers: Vec::new(),
+            base: start..start + input.len(),
+            notes: annotate.then(Vec::new),
+            path: Vec::new(),
+        }
+    }
+
+    /// Annotates `bytes` with `note` if annotating and they're part of the payload, unlike
+    /// decompressed ones.
+    fn note(&mut self, bytes: &[u8], note: impl FnOnce() -> String) {
+        let Some(notes) = &mut self.notes else {
+            return;
+        };
+
+        let start = bytes.as_ptr() as usize;
+        if bytes.is_empty() || start < self.base.start || start + bytes.len() > self.base.end {
+            return;
+        }
+
+        let start = start - self.base.start;
+        notes.push(Annotation {
+            range: start..start + bytes.len(),
+            path: self.path.concat().trim_start_matches('.').to_owned(),
+            note: note(),
+        });
+    }
+
+    /// Runs `f` with `segment` added to the path.
+    fn within<T>(&mut self, segment: impl FnOnce() -> String, f: impl FnOnce(&mut Self) -> T) -> T {
+        if self.notes.is_none() {
+            return f(self);
+        }
+
+        self.path.push(segment());
+        let value = f(self);
+        self.path.pop();
+        value
+    }
+
+    /// Annotates the length prefix at `current`.
+    fn note_prefix(&mut self, input: &[u8], current: usize) {
+        if let Some(len) = input.get(current..current + 1) {
+            self.note(len, || format!("length {}", len[0]));
+        }
+    }
+
+    fn schema(
+        &mut self,
+        schema: &'a Schema,
+        input: &[u8],
+        endian: Endian,
+    ) -> Result<Value, SadbyError> {
+        let value = self.kind(schema, input, endian)?;
+
+        let leaf = match &schema.kind {
+            Kind::Bool | Kind::Char => Some((1, schema.kind.name().to_owned())),
+            Kind::Int { size, signed } => Some((*size, int_name(*size, *signed))),
+            Kind::Float { size } => Some((*size, format!("f{}", size * 8))),
+            Kind::Uuid => Some((16, "uuid".to_owned())),
+            Kind::Str => Some((input.len(), "str".to_owned())),
+            Kind::Varint(inner) => match inner.kind {
+                Kind::Int { size, signed } => {
+                    Some((input.len(), format!("varint {}", int_name(size, signed))))
+                }
+                _ => None,
+            },
+            Kind::Described | Kind::Opaque => {
+                let note = format!("{}, {} bytes", schema.kind.name(), input.len());
+                self.note(input, || note);
+                None
+            }
+            _ => None,
+        };
+        if let Some((len, name)) = leaf {
+            let bytes = &input[..len.min(input.len())];
+            self.note(bytes, || format!("{name} {}", schema.value_to_json(&value)));
+        }
+
+        Ok(value)
+    }
+
+    fn kind(
+        &mut self,
+        schema: &'a Schema,
+        input: &[u8],
+        endian: Endian,
+    ) -> Result<Value, SadbyError> {
+        Ok(match &schema.kind {
+            Kind::Unit => Value::Unit,
+            Kind::Bool => match get(input, 0)? {
+                0 => Value::Bool(false),
+                1 => Value::Bool(true),
+                _ => return Err(SadbyError::UnexpectedToken),
+            },
+            Kind::Int { size, signed } => {
+                int(*size, *signed, raw_int(input, *size, *signed, endian)?)?
+            }
+            Kind::Float { size: 4 } => {
+                Value::F32(f32::from_bits(raw_int(input, 4, false, endian)? as u32))
+            }
+            Kind::Float { size: 8 } => {
+                Value::F64(f64::from_bits(raw_int(input, 8, false, endian)? as u64))
+            }
+            Kind::Float { size } => return Err(bad_schema(format!("no {size} byte floats"))),
+            Kind::Char => Value::Char(*get(input, 0)? as char),
+            Kind::Str => Value::Str(
+                String::from_utf8(input.to_vec()).map_err(|_| SadbyError::UnexpectedToken)?,
+            ),
+            Kind::Uuid => {
+                let mut bytes = get(input, ..16)?.to_vec();
+                // The Microsoft order has the first three groups little endian.
+                if endian == Endian::Little {
+                    bytes[..4].reverse();
+                    bytes[4..6].reverse();
+                    bytes[6..8].reverse();
+                }
+                Value::Bytes(bytes)
+            }
+            Kind::Option(some) => match get(input, 0)? {
+                b'S' => {
+                    self.note(&input[..1], || "some".to_owned());
+                    Value::Some(Box::new(self.schema(some, &input[1..], endian)?))
+                }
+                b'N' => {
+                    self.note(&input[..1], || "none".to_owned());
+                    Value::None
+                }
+                _ => return Err(SadbyError::UnexpectedToken),
+            },
+            Kind::Array { item, len } => {
+                let items = match item.fixed_size {
+                    Some(0) => (0..*len)
+                        .map(|_| self.schema(item, &[], endian))
+                        .collect::<Result<Vec<_>, _>>()?,
+                    Some(size) => get(input, ..size * len)?
+                        .chunks(size)
+                        .enumerate()
+                        .map(|(i, chunk)| {
+                            self.within(|| format!("[{i}]"), |d| d.schema(item, chunk, endian))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                    None => self.items(item, input, endian)?,
+                };
+                if items.len() != *len {
+                    return Err(SadbyError::UnexpectedToken);
+                }
+                Value::Seq(items)
+            }
+            Kind::Seq(item) => Value::Seq(self.items(item, input, endian)?),
+            Kind::Map { key, value } => {
+                let mut entries = Vec::new();
+                let mut current = 0;
+                while current < input.len() {
+                    let i = entries.len();
+                    self.within(|| format!("[{i}]"), |d| d.note_prefix(input, current));
+                    let (entry, next) = prefixed(input, current)?;
+                    let entry = &input[entry];
+                    self.within(
+                        || format!("[{i}]"),
+                        |d| {
+                            d.within(|| ".key".to_owned(), |d| d.note_prefix(entry, 0));
+                            let (key_bytes, _) = prefixed(entry, 0)?;
+                            let key = d.within(
+                                || ".key".to_owned(),
+                                |d| d.schema(key, &entry[key_bytes.clone()], endian),
+                            )?;
+                            let value = d.within(
+                                || ".value".to_owned(),
+                                |d| d.schema(value, &entry[key_bytes.end..], endian),
+                            )?;
+                            entries.push((key, value));
+                            Ok::<_, SadbyError>(())
+                        },
+                    )?;
+                    current = next;
+                }
+                Value::Map(entries)
+            }
+            Kind::Tuple(items) => {
+                let Some((last, heads)) = items.split_last() else {
+                    return Err(bad_schema("empty tuple".to_owned()));
+                };
+
+                let mut values = Vec::new();
+                let mut current = 0;
+                for (i, item) in heads.iter().enumerate() {
+                    self.within(|| format!(".{i}"), |d| d.note_prefix(input, current));
+                    let (bytes, next) = prefixed(input, current)?;
+                    let bytes = &input[bytes];
+                    values
+                        .push(self.within(|| format!(".{i}"), |d| d.schema(item, bytes, endian))?);
+                    current = next;
+                }
+                let bytes = get(input, current..)?;
+                let i = heads.len();
+                values.push(self.within(|| format!(".{i}"), |d| d.schema(last, bytes, endian))?);
+                Value::Tuple(values)
+            }
+            Kind::Varint(inner) => {
+                let Kind::Int { size, signed } = inner.kind else {
+                    return Err(bad_schema(format!("varint of {}", inner.kind.name())));
+                };
+                let raw = match signed {
+                    false => varint::de::<u128>(input)?,
+                    true => varint::de::<i128>(input)? as u128,
+                };
+                // Anything the integer type couldn't hold was out of range for it as well.
+                let width = size as u32 * 8;
+                let fits = match signed {
+                    _ if width >= 128 => true,
+                    false => raw >> width == 0,
+                    true => extend(raw, width, true) == raw,
+                };
+                if !fits {
+                    return Err(SadbyError::OutOfRange);
+                }
+                int(size, signed, raw)?
+            }
+            Kind::Compressed(inner) => {
+                let raw = compress::de(input, compress::DEFAULT_LIMIT)?;
+                self.note(input, || {
+                    format!("compressed, {} bytes unpacked", raw.len())
+                });
+                self.schema(inner, &raw, endian)?
+            }
+            Kind::Checked { checksum, inner } => {
+                let data = self.checked(*checksum, input, endian)?;
+                self.schema(inner, data, endian)?
+            }
+            Kind::Endian { endian, inner } => self.schema(inner, input, *endian)?,
+            Kind::Described => Value::de_bytes(input)?,
+            // What `se_described` writes for types without a described encoding of their own.
+            Kind::Opaque => Value::Bytes(input.to_vec()),
+            Kind::Container(container) => self.container(container, input, endian)?,
+            Kind::Ref(name) => {
+                let Some(container) = self.containers.iter().rev().find(|c| c.name == *name) else {
+                    return Err(bad_schema(format!(
+                        "`{name}` refers to no container around it"
+                    )));
+                };
+                self.container(container, input, endian)?
+            }
+        })
+    }
+
+    /// Items each prefixed with their length, up to the end of `input`.
+    fn items(
+        &mut self,
+        item: &'a Schema,
+        input: &[u8],
+        endian: Endian,
+    ) -> Result<Vec<Value>, SadbyError> {
+        let mut items = Vec::new();
+        let mut current = 0;
+        while current < input.len() {
+            let i = items.len();
+            self.within(|| format!("[{i}]"), |d| d.note_prefix(input, current));
+            let (bytes, next) = prefixed(input, current)?;
+            let bytes = &input[bytes];
+            items.push(self.within(|| format!("[{i}]"), |d| d.schema(item, bytes, endian))?);
+            current = next;
+        }
+
+        Ok(items)
+    }
+
+    /// The data `checksum` covers, with the checksum after it annotated.
+    fn checked<'i>(
+        &mut self,
+        checksum: checksum::Checksum,
+        input: &'i [u8],
+        endian: Endian,
+    ) -> Result<&'i [u8], SadbyError> {
+        let data = checksum.verify(input, endian)?;
+        self.note(&input[data.len()..], || format!("{checksum:?} checksum"));
+        Ok(data)
+    }
+
+    fn container(
+        &mut self,
+        container: &'a Container,
+        input: &[u8],
+        endian: Endian,
+    ) -> Result<Value, SadbyError> {
+        // Nothing stops a payload of a recursive type from nesting deep enough to overflow the
+        // stack otherwise.
+        if self.containers.len() > described::MAX_DEPTH {
+            return Err(SadbyError::UnexpectedToken);
+        }
+
+        self.note(input, || {
+            format!("{} {}", container.body.name(), container.name)
+        });
+        self.containers.push(container);
+        let value = self.container_inner(container, input, endian);
+        self.containers.pop();
+        value
+    }
+
+    fn container_inner(
+        &mut self,
+        container: &'a Container,
+        input: &[u8],
+        endian: Endian,
+    ) -> Result<Value, SadbyError> {
+        let mut input = input;
+        if let Some(magic) = &container.magic {
+            if !input.starts_with(magic) {
+                return Err(SadbyError::BadMagic {
+                    expected: magic.clone(),
+                    found: input[..input.len().min(magic.len())].to_vec(),
+                });
+            }
+            self.note(&input[..magic.len()], || "magic".to_owned());
+            input = &input[magic.len()..];
+        }
+        if let Some(checksum) = container.checksum {
+            input = self.checked(checksum, input, endian)?;
+        }
+        let version = match container.version {
+            Some(supported) => {
+                let version = *get(input, 0)?;
+                self.note(&input[..1], || format!("version {version}"));
+                if version > supported {
+                    return Err(SadbyError::NewerVersion {
+                        found: version,
+                        supported,
+                    });
+                }
+                input = &input[1..];
+                Some(version)
+            }
+            None => None,
+        };
+
+        match &container.body {
+            Body::Struct {
+                layout: Layout::Transparent,
+                fields,
+            } => match fields.as_slice() {
+                [field] => self.schema(&field.schema, input, endian),
+                _ => Err(bad_schema(format!(
+                    "transparent `{}` needs exactly one field",
+                    container.name
+                ))),
+            },
+            Body::Struct { layout, fields } => {
+                self.fields(*layout, fields, input, 0, version, endian)
+            }
+            Body::Enum { layout, variants } => {
+                let discriminant = *get(input, 0)?;
+                let variant = variants
+                    .iter()
+                    .find(|v| v.discriminant == discriminant)
+                    .ok_or(SadbyError::UnexpectedToken)?;
+                self.note(&input[..1], || format!("variant {}", variant.name));
+
+                let value = match variant.fields.is_empty() {
+                    true => Value::Unit,
+                    false => self.within(
+                        || format!(".{}", variant.name),
+                        |d| d.fields(*layout, &variant.fields, input, 1, version, endian),
+                    )?,
+                };
+                Ok(Value::Variant {
+                    discriminant,
+                    name: variant.name.clone(),
+                    value: Box::new(value),
+                })
+            }
+            Body::Proxy(schema) => self.schema(schema, input, endian),
+        }
+    }
+
+    /// The fields of a struct or variant, starting at `offset`.
+    fn fields(
+        &mut self,
+        layout: Layout,
+        fields: &'a [Field],
+        input: &[u8],
+        offset: usize,
+        version: Option<u8>,
+        endian: Endian,
+    ) -> Result<Value, SadbyError> {
+        if layout == Layout::Tagged {
+            return self.tagged(fields, input, offset, endian);
+        }
+
+        // Fields added after the payload was written aren't in it, not even their length.
+        let skipped = |unit: &[&Field]| match (unit, version) {
+            ([field], Some(version)) => field.since.is_some_and(|since| version < since),
+            _ => false,
+        };
+
+        let units = units(fields);
+        let mut values = Vec::new();
+        let mut current = offset;
+        for unit in &units {
+            if skipped(unit) {
+                continue;
+            }
+
+            let bytes = match layout {
+                // A lone unit isn't prefixed unless the container has a version, it takes up the
+                // rest of the input.
+                Layout::Positional if units.len() == 1 && version.is_none() => {
+                    get(input, current..)?
+                }
+                Layout::Positional => {
+                    let segment = || match unit.as_slice() {
+                        [field] if field.bits.is_none() => format!(".{}", field.name),
+                        _ => String::new(),
+                    };
+                    self.within(segment, |d| d.note_prefix(input, current));
+                    let (bytes, next) = prefixed(input, current)?;
+                    current = next;
+                    &input[bytes]
+                }
+                _ => {
+                    let size = unit_size(unit)?;
+                    let bytes = get(input, current..current + size)?;
+                    current += size;
+                    bytes
+                }
+            };
+
+            match unit.as_slice() {
+                [field] if field.bits.is_none() => {
+                    let value = self.within(
+                        || format!(".{}", field.name),
+                        |d| d.schema(&field.schema, bytes, endian),
+                    )?;
+                    values.push((field.name.clone(), value));
+                }
+                _ => {
+                    let bits = bit_fields(unit, bytes)?;
+                    self.note(bytes, || {
+                        let bits = bits
+                            .iter()
+                            .map(|(name, value)| format!("{name}={}", Schema::value_json(value)))
+                            .collect::<Vec<_>>();
+                        format!("bits {}", bits.join(" "))
+                    });
+                    values.extend(bits);
+                }
+            }
+        }
+
+        Ok(Value::Struct(values))
+    }
+
+    /// `[id, len, bytes..]` for every field, in any order. Unknown ids are skipped, a repeated
+    /// one replaces what was read before.
+    fn tagged(
+        &mut self,
+        fields: &'a [Field],
+        input: &[u8],
+        offset: usize,
+        endian: Endian,
+    ) -> Result<Value, SadbyError> {
+        let mut values = vec![None; fields.len()];
+        let mut current = offset;
+        while current < input.len() {
+            let id = input[current];
+            let field = fields.iter().position(|field| field.id == Some(id));
+            let segment = || match field {
+                Some(i) => format!(".{}", fields[i].name),
+                None => format!(".{id}"),
+            };
+            self.within(segment, |d| {
+                d.note(&input[current..current + 1], || format!("id {id}"));
+                d.note_prefix(input, current + 1);
+            });
+            let (bytes, next) = prefixed(input, current + 1)?;
+            if field.is_none() {
+                self.within(segment, |d| {
+                    d.note(&input[bytes.clone()], || "unknown field".to_owned())
+                });
+            }
+            if let Some(i) = field {
+                let bytes = &input[bytes];
+                let value = self.within(
+                    || format!(".{}", fields[i].name),
+                    |d| d.schema(&fields[i].schema, bytes, endian),
+                )?;
+                values[i] = Some(value);
+            }
+            current = next;
+        }
+
+        Ok(Value::StructIds(
+            fields
+                .iter()
+                .zip(values)
+                .filter_map(|(field, value)| Some((field.id?, value?)))
+                .collect(),
+        ))
+    }
+}
+
+/// Fields between two length prefixes, neighbouring bit fields share them.
+pub(super) fn units(fields: &[Field]) -> Vec<Vec<&Field>> {
+    let mut units = Vec::<Vec<&Field>>::new();
+    for field in fields {
+        match units.last_mut() {
+            Some(unit) if field.bits.is_some() && unit[0].bits.is_some() => unit.push(field),
+            _ => units.push(vec![field]),
+        }
+    }
+
+    units
+}
+
+/// Size of a unit in a packed container.
+pub(super) fn unit_size(unit: &[&Field]) -> Result<usize, SadbyError> {
+    match unit {
+        [field] if field.bits.is_none() => field
+            .schema
+            .fixed_size
+            .ok_or_else(|| bad_schema(format!("packed field `{}` has no fixed size", field.name))),
+        _ => Ok(unit
+            .iter()
+            .map(|field| field.bits.unwrap_or_default() as usize)
+            .sum::<usize>()
+            .div_ceil(8)),
+    }
+}
+
+/// Bit fields packed together like `bits::BitWriter` does.
+fn bit_fields(unit: &[&Field], input: &[u8]) -> Result<Vec<(String, Value)>, SadbyError> {
+    let mut pos = 0;
+    unit.iter()
+        .map(|field| {
+            let width = field.bits.unwrap_or_default();
+            let (size, signed) = match field.schema.kind {
+                Kind::Int { size, signed } if size <= 8 => (size, signed),
+                Kind::Bool => (0, false),
+                _ => {
+                    return Err(bad_schema(format!(
+                        "bit field `{}` of {}",
+                        field.name,
+                        field.schema.kind.name()
+                    )));
+                }
+            };
+            if width == 0 || width > (size as u32 * 8).max(1) {
+                return Err(bad_schema(format!(
+                    "bit field `{}` is {width} bits wide",
+                    field.name
+                )));
+            }
+
+            let mut raw = 0u128;
+            for i in 0..width {
+                let byte = get(input, pos / 8)?;
+                raw |= (((byte >> (pos % 8)) & 1) as u128) << i;
+                pos += 1;
+            }
+
+            let value = match field.schema.kind {
+                Kind::Bool => Value::Bool(raw == 1),
+                _ => int(size, signed, extend(raw, width, signed))?,
+            };
+            Ok((field.name.clone(), value))
+        })
+        .collect()
+}
@@ -0,0 +1,430 @@
+//! Encoding values with nothing but their schema, the inverse of `Schema::decode`.
+
+use super::*;
+
+use super::decode::{bad_schema, extend, int_name, unit_size, units};
+
+impl Schema {
+    /// Encodes `value` like `se_bytes` of the described type would, e.g. one `decode` or
+    /// `value_from_json` gives. Containers are written with their current version, so every
+    /// field has to be in the value, tagged ones excepted.
+    ///
+    /// A value that doesn't fit the schema fails with a `Custom` error naming where, one too
+    /// large for its integer, bit field or length prefix with `OutOfRange`.
+    pub fn encode(&self, value: &Value) -> Result<Vec<u8>, SadbyError> {
+        let mut encoder = Encoder::default();
+        encoder
+            .schema(self, value, Endian::Little)
+            .map_err(|e| match e {
+                SadbyError::Custom(message) if !message.starts_with("Bad schema") => {
+                    match encoder.path.concat().trim_start_matches('.') {
+                        "" => SadbyError::Custom(format!("Bad value: {message}")),
+                        path => SadbyError::Custom(format!("Bad value at `{path}`: {message}")),
+                    }
+                }
+                e => e,
+            })
+    }
+}
+
+#[derive(Default)]
+struct Encoder<'a> {
+    /// Containers being encoded, innermost last, for `Kind::Ref` to find.
+    containers: Vec<&'a Container>,
+    /// Where the value being encoded is, e.g. `.header`, `[2]`, left as it is on errors.
+    path: Vec<String>,
+}
+
+fn mismatch(expected: &str, value: &Value) -> SadbyError {
+    SadbyError::Custom(format!(
+        "expected {expected}, found {}",
+        Schema::value_json(value)
+    ))
+}
+
+/// An integer value as its two's complement, and whether it's negative.
+fn raw_int(value: &Value) -> Option<(u128, bool)> {
+    Some(match *value {
+        Value::U8(n) => (n as u128, false),
+        Value::U16(n) => (n as u128, false),
+        Value::U32(n) => (n as u128, false),
+        Value::U64(n) => (n as u128, false),
+        Value::U128(n) => (n, false),
+        Value::I8(n) => (n as u128, n < 0),
+        Value::I16(n) => (n as u128, n < 0),
+        Value::I32(n) => (n as u128, n < 0),
+        Value::I64(n) => (n as u128, n < 0),
+        Value::I128(n) => (n as u128, n < 0),
+        _ => return None,
+    })
+}
+
+/// Two's complement of an integer value `width` bits wide, failing if it doesn't fit.
+fn fitting(value: &Value, width: u32, signed: bool) -> Result<u128, SadbyError> {
+    let Some((raw, negative)) = raw_int(value) else {
+        return Err(mismatch("an integer", value));
+    };
+
+    let fits = match (signed, negative) {
+        (false, true) => false,
+        (false, false) => width >= 128 || raw >> width == 0,
+        (true, true) => extend(raw, width, true) == raw,
+        (true, false) => raw >> (width - 1) == 0,
+    };
+    match fits {
+        true => Ok(raw),
+        false => Err(SadbyError::OutOfRange),
+    }
+}
+
+/// Appends `bytes` prefixed with their length as a byte.
+fn prefixed(out: &mut Vec<u8>, bytes: &[u8]) -> Result<(), SadbyError> {
+    out.push(u8::try_from(bytes.len()).map_err(|_| SadbyError::OutOfRange)?);
+    out.extend_from_slice(bytes);
+    Ok(())
+}
+
+/// Bit fields packed together like `bits::BitWriter` does.
+fn bit_fields(unit: &[&Field], values: &[(String, Value)]) -> Result<Vec<u8>, SadbyError> {
+    let mut out = Vec::new();
+    let mut len = 0;
+    for field in unit {
+        let width = field.bits.unwrap_or_default();
+        let value = field_value(field, values)?;
+        let raw = match (&field.schema.kind, value) {
+            (Kind::Bool, Value::Bool(b)) => *b as u128,
+            (Kind::Bool, value) => return Err(mismatch("a bool", value)),
+            (Kind::Int { signed, .. }, value) => fitting(value, width, *signed)?,
+            (kind, _) => {
+                return Err(bad_schema(format!(
+                    "bit field `{}` of {}",
+                    field.name,
+                    kind.name()
+                )));
+            }
+        };
+
+        for i in 0..width {
+            if len % 8 == 0 {
+                out.push(0);
+            }
+            if (raw >> i) & 1 == 1 {
+                *out.last_mut().unwrap() |= 1 << (len % 8);
+            }
+            len += 1;
+        }
+    }
+
+    Ok(out)
+}
+
+fn field_value<'v>(field: &Field, values: &'v [(String, Value)]) -> Result<&'v Value, SadbyError> {
+    values
+        .iter()
+        .find(|(name, _)| *name == field.name)
+        .map(|(_, value)| value)
+        .ok_or_else(|| SadbyError::Custom(format!("missing field `{}`", field.name)))
+}
+
+impl<'a> Encoder<'a> {
+    /// Runs `f` with `segment` added to the path, which stays there if it fails.
+    fn within<T>(
+        &mut self,
+        segment: String,
+        f: impl FnOnce(&mut Self) -> Result<T, SadbyError>,
+    ) -> Result<T, SadbyError> {
+        self.path.push(segment);
+        let value = f(self)?;
+        self.path.pop();
+        Ok(value)
+    }
+
+    fn schema(
+        &mut self,
+        schema: &'a Schema,
+        value: &Value,
+        endian: Endian,
+    ) -> Result<Vec<u8>, SadbyError> {
+        Ok(match (&schema.kind, value) {
+            (Kind::Unit, Value::Unit) => Vec::new(),
+            (Kind::Bool, Value::Bool(b)) => vec![*b as u8],
+            (Kind::Int { size, signed }, value) => {
+                if *size == 0 || *size > 16 {
+                    return Err(bad_schema(format!("no {size} byte integers")));
+                }
+                let raw = fitting(value, *size as u32 * 8, *signed)?;
+                let mut bytes = raw.to_le_bytes()[..*size].to_vec();
+                if endian == Endian::Big {
+                    bytes.reverse();
+                }
+                bytes
+            }
+            (Kind::Float { size: 4 }, Value::F32(n)) => match endian {
+                Endian::Little => n.to_le_bytes().to_vec(),
+                Endian::Big => n.to_be_bytes().to_vec(),
+            },
+            (Kind::Float { size: 8 }, Value::F64(n)) => match endian {
+                Endian::Little => n.to_le_bytes().to_vec(),
+                Endian::Big => n.to_be_bytes().to_vec(),
+            },
+            (Kind::Float { size }, _) if *size != 4 && *size != 8 => {
+                return Err(bad_schema(format!("no {size} byte floats")));
+            }
+            // Only the low byte is written, anything past it wouldn't read back.
+            (Kind::Char, Value::Char(c)) => {
+                vec![u8::try_from(*c as u32).map_err(|_| SadbyError::OutOfRange)?]
+            }
+            (Kind::Str, Value::Str(s)) => s.as_bytes().to_vec(),
+            (Kind::Uuid, Value::Bytes(bytes)) if bytes.len() == 16 => {
+                let mut bytes = bytes.clone();
+                // The Microsoft order has the first three groups little endian.
+                if endian == Endian::Little {
+                    bytes[..4].reverse();
+                    bytes[4..6].reverse();
+                    bytes[6..8].reverse();
+                }
+                bytes
+            }
+            (Kind::Option(_), Value::None) => vec![b'N'],
+            (Kind::Option(some), Value::Some(value)) => {
+                let mut out = vec![b'S'];
+                out.extend(self.schema(some, value, endian)?);
+                out
+            }
+            (Kind::Array { item, len }, Value::Seq(items)) => {
+                if items.len() != *len {
+                    return Err(SadbyError::Custom(format!(
+                        "expected {len} items, found {}",
+                        items.len()
+                    )));
+                }
+                match item.fixed_size {
+                    Some(_) => {
+                        let mut out = Vec::new();
+                        for (i, value) in items.iter().enumerate() {
+                            out.extend(
+                                self.within(format!("[{i}]"), |e| e.schema(item, value, endian))?,
+                            );
+                        }
+                        out
+                    }
+                    None => self.items(item, items, endian)?,
+                }
+            }
+            (Kind::Seq(item), Value::Seq(items)) => self.items(item, items, endian)?,
+            (Kind::Map { key, value }, Value::Map(entries)) => {
+                let mut out = Vec::new();
+                for (i, (k, v)) in entries.iter().enumerate() {
+                    let entry = self.within(format!("[{i}]"), |e| {
+                        let mut entry = Vec::new();
+                        prefixed(&mut entry, &e.schema(key, k, endian)?)?;
+                        entry.extend(e.schema(value, v, endian)?);
+                        Ok(entry)
+                    })?;
+                    prefixed(&mut out, &entry)?;
+                }
+                out
+            }
+            (Kind::Tuple(items), Value::Tuple(values)) if items.len() == values.len() => {
+                let mut out = Vec::new();
+                for (i, (item, value)) in items.iter().zip(values).enumerate() {
+                    let bytes = self.within(format!(".{i}"), |e| e.schema(item, value, endian))?;
+                    match i + 1 == items.len() {
+                        true => out.extend(bytes),
+                        false => prefixed(&mut out, &bytes)?,
+                    }
+                }
+                out
+            }
+            (Kind::Varint(inner), value) => {
+                let Kind::Int { size, signed } = inner.kind else {
+                    return Err(bad_schema(format!("varint of {}", inner.kind.name())));
+                };
+                let raw = fitting(value, size as u32 * 8, signed)?;
+                match signed {
+                    false => varint::se(&raw),
+                    true => varint::se(&(raw as i128)),
+                }
+            }
+            (Kind::Compressed(inner), value) => compress::se(&self.schema(inner, value, endian)?),
+            (Kind::Checked { checksum, inner }, value) => {
+                let mut out = self.schema(inner, value, endian)?;
+                checksum.append(&mut out, endian);
+                out
+            }
+            (Kind::Endian { endian, inner }, value) => self.schema(inner, value, *endian)?,
+            (Kind::Described, value) => value.try_se_bytes()?,
+            (Kind::Opaque, Value::Bytes(bytes)) => bytes.clone(),
+            (Kind::Container(container), value) => self.container(container, value, endian)?,
+            (Kind::Ref(name), value) => {
+                let Some(container) = self.containers.iter().rev().find(|c| c.name == *name) else {
+                    return Err(bad_schema(format!(
+                        "`{name}` refers to no container around it"
+                    )));
+                };
+                self.container(container, value, endian)?
+            }
+            (kind, value) => {
+                let expected = match kind {
+                    Kind::Int { size, signed } => int_name(*size, *signed),
+                    kind => kind.name().to_owned(),
+                };
+                return Err(mismatch(&format!("a {expected}"), value));
+            }
+        })
+    }
+
+    /// Items each prefixed with their length.
+    fn items(
+        &mut self,
+        item: &'a Schema,
+        items: &[Value],
+        endian: Endian,
+    ) -> Result<Vec<u8>, SadbyError> {
+        let mut out = Vec::new();
+        for (i, value) in items.iter().enumerate() {
+            let bytes = self.within(format!("[{i}]"), |e| e.schema(item, value, endian))?;
+            prefixed(&mut out, &bytes)?;
+        }
+
+        Ok(out)
+    }
+
+    fn container(
+        &mut self,
+        container: &'a Container,
+        value: &Value,
+        endian: Endian,
+    ) -> Result<Vec<u8>, SadbyError> {
+        self.containers.push(container);
+        let body = self.body(container, value, endian)?;
+        self.containers.pop();
+
+        let mut out = container.magic.clone().unwrap_or_default();
+        let mut checked = Vec::from_iter(container.version);
+        checked.extend(body);
+        if let Some(checksum) = container.checksum {
+            checksum.append(&mut checked, endian);
+        }
+        out.extend(checked);
+        Ok(out)
+    }
+
+    fn body(
+        &mut self,
+        container: &'a Container,
+        value: &Value,
+        endian: Endian,
+    ) -> Result<Vec<u8>, SadbyError> {
+        match (&container.body, value) {
+            (
+                Body::Struct {
+                    layout: Layout::Transparent,
+                    fields,
+                },
+                value,
+            ) => match fields.as_slice() {
+                [field] => self.schema(&field.schema, value, endian),
+                _ => Err(bad_schema(format!(
+                    "transparent `{}` needs exactly one field",
+                    container.name
+                ))),
+            },
+            (Body::Struct { layout, fields }, value) => {
+                self.fields(*layout, fields, container.version.is_some(), value, endian)
+            }
+            (Body::Enum { layout, variants }, Value::Variant { name, value, .. }) => {
+                let variant = variants.iter().find(|v| v.name == *name).ok_or_else(|| {
+                    SadbyError::Custom(format!("no variant `{name}` in `{}`", container.name))
+                })?;
+
+                let mut out = vec![variant.discriminant];
+                if !variant.fields.is_empty() {
+                    out.extend(self.within(format!(".{name}"), |e| {
+                        e.fields(
+                            *layout,
+                            &variant.fields,
+                            container.version.is_some(),
+                            value,
+                            endian,
+                        )
+                    })?);
+                }
+                Ok(out)
+            }
+            (Body::Enum { .. }, value) => Err(mismatch("a variant", value)),
+            (Body::Proxy(schema), value) => self.schema(schema, value, endian),
+        }
+    }
+
+    /// The fields of a struct or variant, of a container with a version if `versioned`.
+    fn fields(
+        &mut self,
+        layout: Layout,
+        fields: &'a [Field],
+        versioned: bool,
+        value: &Value,
+        endian: Endian,
+    ) -> Result<Vec<u8>, SadbyError> {
+        let mut out = Vec::new();
+        if layout == Layout::Tagged {
+            let Value::StructIds(values) = value else {
+                return Err(mismatch("a tagged struct", value));
+            };
+            if let Some((id, _)) = values
+                .iter()
+                .find(|(id, _)| fields.iter().all(|field| field.id != Some(*id)))
+            {
+                return Err(SadbyError::Custom(format!("unknown field id {id}")));
+            }
+
+            for field in fields {
+                let Some((id, value)) = values.iter().find(|(id, _)| field.id == Some(*id)) else {
+                    continue;
+                };
+                let bytes = self.within(format!(".{}", field.name), |e| {
+                    e.schema(&field.schema, value, endian)
+                })?;
+                out.push(*id);
+                prefixed(&mut out, &bytes)?;
+            }
+            return Ok(out);
+        }
+
+        let Value::Struct(values) = value else {
+            return Err(mismatch("a struct", value));
+        };
+        if let Some((name, _)) = values
+            .iter()
+            .find(|(name, _)| fields.iter().all(|field| field.name != *name))
+        {
+            return Err(SadbyError::Custom(format!("unknown field `{name}`")));
+        }
+
+        let units = units(fields);
+        for unit in &units {
+            let bytes = match unit.as_slice() {
+                [field] if field.bits.is_none() => {
+                    let value = field_value(field, values)?;
+                    self.within(format!(".{}", field.name), |e| {
+                        e.schema(&field.schema, value, endian)
+                    })?
+                }
+                _ => bit_fields(unit, values)?,
+            };
+
+            match layout {
+                // A lone unit isn't prefixed unless the container has a version, it takes up the
+                // rest of the input.
+                Layout::Positional if units.len() == 1 && !versioned => out.extend(bytes),
+                Layout::Positional => prefixed(&mut out, &bytes)?,
+                _ => {
+                    unit_size(unit)?;
+                    out.extend(bytes);
+                }
+            }
+        }
+
+        Ok(out)
+    }
+}
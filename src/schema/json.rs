@@ -0,0 +1,323 @@
+//! The little JSON schemas and values are exported as, with its keys kept in the order they're
+//! written in.
+
+use std::str::FromStr;
+
+/// Deepest nesting `Json::parse` goes through.
+const MAX_DEPTH: usize = 128;
+
+pub(super) enum Json {
+    Null,
+    Bool(bool),
+    /// A number as it's written, so integers of any size and floats all survive.
+    Num(String),
+    Str(String),
+    Arr(Vec<Json>),
+    Obj(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub(super) fn obj(entries: Vec<(&str, Json)>) -> Json {
+        Json::Obj(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_owned(), value))
+                .collect(),
+        )
+    }
+
+    pub(super) fn opt<T>(value: Option<T>, f: impl FnOnce(T) -> Json) -> Json {
+        value.map_or(Json::Null, f)
+    }
+
+    /// The value of `key`, failing if it's missing or this isn't an object.
+    pub(super) fn get(&self, key: &str) -> Result<&Json, String> {
+        let Json::Obj(entries) = self else {
+            return Err(format!("expected an object with `{key}`"));
+        };
+
+        entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+            .ok_or_else(|| format!("missing `{key}`"))
+    }
+
+    pub(super) fn str(&self) -> Result<&str, String> {
+        match self {
+            Json::Str(s) => Ok(s),
+            _ => Err("expected a string".to_owned()),
+        }
+    }
+
+    pub(super) fn num<T: FromStr>(&self) -> Result<T, String> {
+        match self {
+            Json::Num(n) => n.parse().map_err(|_| format!("{n} is out of range")),
+            _ => Err("expected a number".to_owned()),
+        }
+    }
+
+    pub(super) fn bool(&self) -> Result<bool, String> {
+        match self {
+            Json::Bool(b) => Ok(*b),
+            _ => Err("expected a boolean".to_owned()),
+        }
+    }
+
+    pub(super) fn arr(&self) -> Result<&[Json], String> {
+        match self {
+            Json::Arr(items) => Ok(items),
+            _ => Err("expected an array".to_owned()),
+        }
+    }
+
+    /// `None` for `null`, `f` of the value otherwise.
+    pub(super) fn nullable<T>(
+        &self,
+        f: impl FnOnce(&Json) -> Result<T, String>,
+    ) -> Result<Option<T>, String> {
+        match self {
+            Json::Null => Ok(None),
+            json => f(json).map(Some),
+        }
+    }
+
+    pub(super) fn write(&self, out: &mut String, indent: usize) {
+        match self {
+            Json::Null => out.push_str("null"),
+            Json::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+            Json::Num(n) => out.push_str(n),
+            Json::Str(s) => {
+                out.push('"');
+                for c in s.chars() {
+                    match c {
+                        '"' => out.push_str("\\\""),
+                        '\\' => out.push_str("\\\\"),
+                        c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+                        c => out.push(c),
+                    }
+                }
+                out.push('"');
+            }
+            Json::Arr(items) if items.is_empty() => out.push_str("[]"),
+            Json::Arr(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    out.push_str(if i == 0 { "\n" } else { ",\n" });
+                    out.push_str(&"  ".repeat(indent + 1));
+                    item.write(out, indent + 1);
+                }
+                out.push('\n');
+                out.push_str(&"  ".repeat(indent));
+                out.push(']');
+            }
+            Json::Obj(entries) => {
+                out.push('{');
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    out.push_str(if i == 0 { "\n" } else { ",\n" });
+                    out.push_str(&"  ".repeat(indent + 1));
+                    Json::Str(key.clone()).write(out, indent + 1);
+                    out.push_str(": ");
+                    value.write(out, indent + 1);
+                }
+                out.push('\n');
+                out.push_str(&"  ".repeat(indent));
+                out.push('}');
+            }
+        }
+    }
+
+    /// Parses the whole of `input`.
+    pub(super) fn parse(input: &str) -> Result<Json, String> {
+        let mut parser = Parser {
+            input: input.as_bytes(),
+            pos: 0,
+        };
+        let json = parser.value(0)?;
+        parser.skip_whitespace();
+        if parser.pos != parser.input.len() {
+            return Err(parser.error("trailing characters"));
+        }
+
+        Ok(json)
+    }
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        format!("{message} at byte {}", self.pos)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.input.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skips whitespace and `c`, failing if anything else comes first.
+    fn expect(&mut self, c: u8) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.input.get(self.pos) != Some(&c) {
+            return Err(self.error(&format!("expected `{}`", c as char)));
+        }
+
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Skips whitespace and `c` if it's next.
+    fn eat(&mut self, c: u8) -> bool {
+        self.skip_whitespace();
+        let found = self.input.get(self.pos) == Some(&c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn keyword(&mut self, keyword: &str, json: Json) -> Result<Json, String> {
+        if !self.input[self.pos..].starts_with(keyword.as_bytes()) {
+            return Err(self.error("unexpected character"));
+        }
+
+        self.pos += keyword.len();
+        Ok(json)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Json, String> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nested too deep"));
+        }
+
+        self.skip_whitespace();
+        match self.input.get(self.pos) {
+            None => Err(self.error("unexpected end")),
+            Some(b'n') => self.keyword("null", Json::Null),
+            Some(b't') => self.keyword("true", Json::Bool(true)),
+            Some(b'f') => self.keyword("false", Json::Bool(false)),
+            Some(b'"') => Ok(Json::Str(self.string()?)),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                if !self.eat(b']') {
+                    loop {
+                        items.push(self.value(depth + 1)?);
+                        if self.eat(b']') {
+                            break;
+                        }
+                        self.expect(b',')?;
+                    }
+                }
+                Ok(Json::Arr(items))
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut entries = Vec::new();
+                if !self.eat(b'}') {
+                    loop {
+                        self.skip_whitespace();
+                        let key = self.string()?;
+                        self.expect(b':')?;
+                        entries.push((key, self.value(depth + 1)?));
+                        if self.eat(b'}') {
+                            break;
+                        }
+                        self.expect(b',')?;
+                    }
+                }
+                Ok(Json::Obj(entries))
+            }
+            Some(_) => Err(self.error("unexpected character")),
+        }
+    }
+
+    /// `-`, digits, then optionally a fraction and an exponent.
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.pos;
+        let digits = |parser: &mut Self| {
+            let start = parser.pos;
+            while let Some(b'0'..=b'9') = parser.input.get(parser.pos) {
+                parser.pos += 1;
+            }
+            match parser.pos > start {
+                true => Ok(()),
+                false => Err(parser.error("expected a digit")),
+            }
+        };
+
+        if self.input[self.pos] == b'-' {
+            self.pos += 1;
+        }
+        digits(self)?;
+        if self.input.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            digits(self)?;
+        }
+        if let Some(b'e' | b'E') = self.input.get(self.pos) {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.input.get(self.pos) {
+                self.pos += 1;
+            }
+            digits(self)?;
+        }
+
+        let number = std::str::from_utf8(&self.input[start..self.pos]).unwrap();
+        Ok(Json::Num(number.to_owned()))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        if self.input.get(self.pos) != Some(&b'"') {
+            return Err(self.error("expected a string"));
+        }
+        self.pos += 1;
+
+        let mut out = Vec::new();
+        loop {
+            let Some(&byte) = self.input.get(self.pos) else {
+                return Err(self.error("unterminated string"));
+            };
+            self.pos += 1;
+
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let Some(&escape) = self.input.get(self.pos) else {
+                        return Err(self.error("unterminated string"));
+                    };
+                    self.pos += 1;
+
+                    let c = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let code = self
+                                .input
+                                .get(self.pos..self.pos + 4)
+                                .and_then(|hex| std::str::from_utf8(hex).ok())
+                                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error("bad \\u escape"))?;
+                            self.pos += 4;
+                            code
+                        }
+                        _ => return Err(self.error("bad escape")),
+                    };
+                    out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                byte => out.push(byte),
+            }
+        }
+
+        String::from_utf8(out).map_err(|_| self.error("invalid UTF-8"))
+    }
+}
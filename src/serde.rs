@@ -0,0 +1,739 @@
+//! The positional wire format for serde types, with the layout `#[derive(Sadby)]` gives the same
+//! Rust type: structs and tuple structs are positional, tuples prefix all but the last item,
+//! sequences and maps are laid out like `Vec<T>` and `Vec<(K, V)>`.
+//!
+//! Serde can't tell a few things apart, so some types differ from their `Sadby` encoding:
+//! arrays are written like tuples, and enum variants are numbered in declaration order, ignoring
+//! explicit discriminants. Maps are sorted by the encoding of their keys like `Sadby` does, sets
+//! look like any other sequence though, a `HashSet` needs `sorted_set` to match. Everything is
+//! little endian. The format isn't self-describing, so `deserialize_any` and anything relying on
+//! it, like `#[serde(flatten)]` or untagged enums, fails.
+
+use super::*;
+
+use std::collections::HashSet;
+use std::fmt;
+
+use ::serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use ::serde::ser::{self, Serialize};
+
+/// Encodes `value` like the `Sadby` encoding of the same type would.
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, SadbyError> {
+    value.serialize(Serializer).map_err(|e| e.0)
+}
+
+/// Decodes a value written by `to_bytes`.
+pub fn from_bytes<'de, T: de::Deserialize<'de>>(input: &'de [u8]) -> Result<T, SadbyError> {
+    T::deserialize(Deserializer::new(input)).map_err(|e| e.0)
+}
+
+/// Encodes a serde type with `to_bytes`, so it can be a field of a type deriving `Sadby`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SerdeAsSadby<T>(pub T);
+
+impl<T> From<T> for SerdeAsSadby<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> Sadby for SerdeAsSadby<T> {
+    fn se_bytes(&self) -> Vec<u8> {
+        match self.try_se_bytes() {
+            Ok(buf) => buf,
+            Err(e) => panic!("Failed to encode: {e:?}"),
+        }
+    }
+    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
+        Ok(Self(from_bytes(input)?))
+    }
+    fn try_se_bytes(&self) -> Result<Vec<u8>, SadbyError> {
+        to_bytes(&self.0)
+    }
+}
+
+/// Serializes a set sorted by the encoding of its items, the way the `Sadby` impl writes it. Meant
+/// for `#[serde(serialize_with = "sadby::serde::sorted_set")]` on `HashSet` fields, which serde
+/// writes in whatever order the hasher gives otherwise.
+pub fn sorted_set<T: Serialize, H, S: ser::Serializer>(
+    set: &HashSet<T, H>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut items = set
+        .iter()
+        .map(|item| Ok((to_bytes(item)?, item)))
+        .collect::<Result<Vec<_>, SadbyError>>()
+        .map_err(|e| <S::Error as ser::Error>::custom(Error(e)))?;
+    items.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    serializer.collect_seq(items.into_iter().map(|(_, item)| item))
+}
+
+impl<T: Serialize + DeserializeOwned> schema::SadbySchema for SerdeAsSadby<T> {
+    fn schema() -> schema::Schema {
+        schema::Schema::new::<Self>(schema::Kind::Opaque)
+    }
+}
+
+/// `SadbyError` with the traits serde wants of its errors.
+#[derive(Debug)]
+pub struct Error(pub SadbyError);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<M: fmt::Display>(message: M) -> Self {
+        Error(SadbyError::Custom(message.to_string()))
+    }
+}
+
+impl de::Error for Error {
+    fn custom<M: fmt::Display>(message: M) -> Self {
+        Error(SadbyError::Custom(message.to_string()))
+    }
+}
+
+impl From<SadbyError> for Error {
+    fn from(e: SadbyError) -> Self {
+        Error(e)
+    }
+}
+
+/// How the items of a compound value are separated.
+#[derive(Clone, Copy)]
+enum Framing {
+    /// Fields of a struct or variant: each prefixed with its length, unless there's only one.
+    Fields(usize),
+    /// Items of a tuple: each but the last prefixed with its length.
+    Tuple(usize),
+    /// Items of a sequence or entries of a map: each prefixed with its length, up to the end.
+    Items,
+}
+
+/// Returns the encoding of every value instead of appending to a buffer, the length prefixes go
+/// in front of the items once they're known.
+pub struct Serializer;
+
+/// Items of a compound value, joined by `end`.
+pub struct Compound {
+    buf: Vec<u8>,
+    items: Vec<Vec<u8>>,
+    framing: Framing,
+}
+
+impl Compound {
+    fn new(buf: Vec<u8>, framing: Framing) -> Self {
+        Self {
+            buf,
+            items: Vec::new(),
+            framing,
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.items.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Vec<u8>, Error> {
+        let Compound {
+            mut buf,
+            items,
+            framing,
+        } = self;
+
+        let count = items.len();
+        for (i, mut item) in items.into_iter().enumerate() {
+            let prefixed = match framing {
+                Framing::Fields(len) => len != 1,
+                Framing::Tuple(_) => i + 1 != count,
+                Framing::Items => true,
+            };
+            if prefixed {
+                buf.push(u8::try_from(item.len()).map_err(|_| SadbyError::OutOfRange)?);
+            }
+            buf.append(&mut item);
+        }
+
+        Ok(buf)
+    }
+}
+
+/// The discriminant byte of a variant.
+fn discriminant(variant_index: u32) -> Result<u8, Error> {
+    u8::try_from(variant_index).map_err(|_| Error(SadbyError::OutOfRange))
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Vec<u8>;
+    type Error = Error;
+    type SerializeSeq = Compound;
+    type SerializeTuple = Compound;
+    type SerializeTupleStruct = Compound;
+    type SerializeTupleVariant = Compound;
+    type SerializeMap = MapCompound;
+    type SerializeStruct = Compound;
+    type SerializeStructVariant = Compound;
+
+    fn serialize_bool(self, v: bool) -> Result<Vec<u8>, Error> {
+        Ok(v.se_bytes())
+    }
+    fn serialize_i8(self, v: i8) -> Result<Vec<u8>, Error> {
+        Ok(v.se_bytes())
+    }
+    fn serialize_i16(self, v: i16) -> Result<Vec<u8>, Error> {
+        Ok(v.se_bytes())
+    }
+    fn serialize_i32(self, v: i32) -> Result<Vec<u8>, Error> {
+        Ok(v.se_bytes())
+    }
+    fn serialize_i64(self, v: i64) -> Result<Vec<u8>, Error> {
+        Ok(v.se_bytes())
+    }
+    fn serialize_i128(self, v: i128) -> Result<Vec<u8>, Error> {
+        Ok(v.se_bytes())
+    }
+    fn serialize_u8(self, v: u8) -> Result<Vec<u8>, Error> {
+        Ok(v.se_bytes())
+    }
+    fn serialize_u16(self, v: u16) -> Result<Vec<u8>, Error> {
+        Ok(v.se_bytes())
+    }
+    fn serialize_u32(self, v: u32) -> Result<Vec<u8>, Error> {
+        Ok(v.se_bytes())
+    }
+    fn serialize_u64(self, v: u64) -> Result<Vec<u8>, Error> {
+        Ok(v.se_bytes())
+    }
+    fn serialize_u128(self, v: u128) -> Result<Vec<u8>, Error> {
+        Ok(v.se_bytes())
+    }
+    fn serialize_f32(self, v: f32) -> Result<Vec<u8>, Error> {
+        Ok(v.se_bytes())
+    }
+    fn serialize_f64(self, v: f64) -> Result<Vec<u8>, Error> {
+        Ok(v.se_bytes())
+    }
+    fn serialize_char(self, v: char) -> Result<Vec<u8>, Error> {
+        Ok(v.se_bytes())
+    }
+    fn serialize_str(self, v: &str) -> Result<Vec<u8>, Error> {
+        Ok(v.as_bytes().to_vec())
+    }
+    // Like `Vec<u8>`.
+    fn serialize_bytes(self, v: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(v.to_vec().se_bytes())
+    }
+    fn serialize_none(self) -> Result<Vec<u8>, Error> {
+        Ok(vec![b'N'])
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Vec<u8>, Error> {
+        let mut buf = vec![b'S'];
+        buf.append(&mut value.serialize(Serializer)?);
+        Ok(buf)
+    }
+    fn serialize_unit(self) -> Result<Vec<u8>, Error> {
+        Ok(Vec::new())
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Vec<u8>, Error> {
+        Ok(Vec::new())
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Vec<u8>, Error> {
+        Ok(vec![discriminant(variant_index)?])
+    }
+    // A struct with one field, which isn't prefixed.
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Vec<u8>, Error> {
+        value.serialize(Serializer)
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<Vec<u8>, Error> {
+        let mut buf = vec![discriminant(variant_index)?];
+        buf.append(&mut value.serialize(Serializer)?);
+        Ok(buf)
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound, Error> {
+        Ok(Compound::new(Vec::new(), Framing::Items))
+    }
+    fn serialize_tuple(self, len: usize) -> Result<Compound, Error> {
+        Ok(Compound::new(Vec::new(), Framing::Tuple(len)))
+    }
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Compound, Error> {
+        Ok(Compound::new(Vec::new(), Framing::Fields(len)))
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        len: usize,
+    ) -> Result<Compound, Error> {
+        Ok(Compound::new(
+            vec![discriminant(variant_index)?],
+            Framing::Fields(len),
+        ))
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapCompound, Error> {
+        Ok(MapCompound {
+            entries: Vec::new(),
+            key: None,
+        })
+    }
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Compound, Error> {
+        Ok(Compound::new(Vec::new(), Framing::Fields(len)))
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        len: usize,
+    ) -> Result<Compound, Error> {
+        Ok(Compound::new(
+            vec![discriminant(variant_index)?],
+            Framing::Fields(len),
+        ))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl ser::SerializeSeq for Compound {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Vec<u8>, Error> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeTuple for Compound {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Vec<u8>, Error> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Vec<u8>, Error> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeTupleVariant for Compound {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Vec<u8>, Error> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeStruct for Compound {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Vec<u8>, Error> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeStructVariant for Compound {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.push(value)
+    }
+    fn end(self) -> Result<Vec<u8>, Error> {
+        Compound::end(self)
+    }
+}
+
+/// Entries of a map, each written like a `(K, V)` tuple, sorted by the encoding of their key.
+pub struct MapCompound {
+    /// The encoding of every key, with the entry it starts.
+    entries: Vec<(Vec<u8>, Vec<u8>)>,
+    key: Option<Vec<u8>>,
+}
+
+impl ser::SerializeMap for MapCompound {
+    type Ok = Vec<u8>;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(key.serialize(Serializer)?);
+        Ok(())
+    }
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| <Error as ser::Error>::custom("value without a key"))?;
+
+        let mut entry = Compound::new(Vec::new(), Framing::Tuple(2));
+        entry.items.push(key.clone());
+        entry.push(value)?;
+        self.entries.push((key, entry.end()?));
+        Ok(())
+    }
+    fn end(mut self) -> Result<Vec<u8>, Error> {
+        self.entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut entries = Compound::new(Vec::new(), Framing::Items);
+        entries.items = self.entries.into_iter().map(|(_, entry)| entry).collect();
+        entries.end()
+    }
+}
+
+/// Decodes one value taking up all of `input`, like `Sadby::de_bytes`.
+pub struct Deserializer<'de> {
+    input: &'de [u8],
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn new(input: &'de [u8]) -> Self {
+        Self { input }
+    }
+
+    /// The first `size` bytes, for fixed size types.
+    fn take(&self, size: usize) -> Result<&'de [u8], Error> {
+        self.input
+            .get(..size)
+            .ok_or(Error(SadbyError::UnexpectedToken))
+    }
+
+    fn compound<V: Visitor<'de>>(self, framing: Framing, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(Items {
+            input: self.input,
+            current: 0,
+            index: 0,
+            framing,
+        })
+    }
+}
+
+macro_rules! sadby_serde_numbers {
+    ($( $method:ident => $visit:ident($type:ty) ),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                let size = <$type as Sadby>::FIXED_SIZE.unwrap_or_default();
+                visitor.$visit(<$type>::de_bytes(self.take(size)?)?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(<Error as de::Error>::custom(
+            "the sadby format isn't self-describing",
+        ))
+    }
+
+    sadby_serde_numbers!(
+        deserialize_bool => visit_bool(bool), deserialize_char => visit_char(char),
+        deserialize_i8 => visit_i8(i8), deserialize_i16 => visit_i16(i16),
+        deserialize_i32 => visit_i32(i32), deserialize_i64 => visit_i64(i64),
+        deserialize_i128 => visit_i128(i128), deserialize_u8 => visit_u8(u8),
+        deserialize_u16 => visit_u16(u16), deserialize_u32 => visit_u32(u32),
+        deserialize_u64 => visit_u64(u64), deserialize_u128 => visit_u128(u128),
+        deserialize_f32 => visit_f32(f32), deserialize_f64 => visit_f64(f64)
+    );
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_str(
+            std::str::from_utf8(self.input).map_err(|_| Error(SadbyError::UnexpectedToken))?,
+        )
+    }
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_byte_buf(Vec::<u8>::de_bytes(self.input)?)
+    }
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.take(1)?[0] {
+            b'S' => visitor.visit_some(Deserializer {
+                input: &self.input[1..],
+            }),
+            b'N' => visitor.visit_none(),
+            _ => Err(Error(SadbyError::UnexpectedToken)),
+        }
+    }
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.compound(Framing::Items, visitor)
+    }
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.compound(Framing::Tuple(len), visitor)
+    }
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.compound(Framing::Fields(len), visitor)
+    }
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(Entries {
+            items: Items {
+                input: self.input,
+                current: 0,
+                index: 0,
+                framing: Framing::Items,
+            },
+            value: None,
+        })
+    }
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.compound(Framing::Fields(fields.len()), visitor)
+    }
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let discriminant = self.take(1)?[0];
+        visitor.visit_enum(Enum {
+            discriminant,
+            input: &self.input[1..],
+        })
+    }
+    fn deserialize_identifier<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(<Error as de::Error>::custom(
+            "the sadby format has no identifiers",
+        ))
+    }
+    // Whatever it is, it takes up the bytes it was given.
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// The items of a compound value, each handed out as the bytes it takes up.
+struct Items<'de> {
+    input: &'de [u8],
+    current: usize,
+    index: usize,
+    framing: Framing,
+}
+
+impl<'de> Items<'de> {
+    fn next(&mut self) -> Result<Option<&'de [u8]>, Error> {
+        let (len, prefixed) = match self.framing {
+            Framing::Fields(len) => (Some(len), len != 1),
+            Framing::Tuple(len) => (Some(len), self.index + 1 != len),
+            Framing::Items => (None, true),
+        };
+        let done = match len {
+            Some(len) => self.index >= len,
+            None => self.current >= self.input.len(),
+        };
+        if done {
+            return Ok(None);
+        }
+        self.index += 1;
+
+        if !prefixed {
+            let rest = self
+                .input
+                .get(self.current..)
+                .ok_or(Error(SadbyError::UnexpectedToken))?;
+            self.current = self.input.len();
+            return Ok(Some(rest));
+        }
+
+        let next = self.current
+            + *self
+                .input
+                .get(self.current)
+                .ok_or(Error(SadbyError::UnexpectedToken))? as usize;
+        let bytes = self
+            .input
+            .get(self.current + 1..=next)
+            .ok_or(Error(SadbyError::UnexpectedToken))?;
+        self.current = next + 1;
+        Ok(Some(bytes))
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for Items<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        match self.next()? {
+            Some(input) => Ok(Some(seed.deserialize(Deserializer { input })?)),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        match self.framing {
+            Framing::Fields(len) | Framing::Tuple(len) => Some(len - self.index),
+            Framing::Items => None,
+        }
+    }
+}
+
+/// Entries of a map, each laid out like a `(K, V)` tuple.
+struct Entries<'de> {
+    items: Items<'de>,
+    value: Option<&'de [u8]>,
+}
+
+impl<'de> de::MapAccess<'de> for Entries<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        let Some(entry) = self.items.next()? else {
+            return Ok(None);
+        };
+
+        let mut entry = Items {
+            input: entry,
+            current: 0,
+            index: 0,
+            framing: Framing::Tuple(2),
+        };
+        let key = entry.next()?.unwrap_or_default();
+        self.value = entry.next()?;
+        Ok(Some(seed.deserialize(Deserializer { input: key })?))
+    }
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let input = self
+            .value
+            .take()
+            .ok_or_else(|| <Error as de::Error>::custom("value without a key"))?;
+        seed.deserialize(Deserializer { input })
+    }
+}
+
+/// A variant, picked by its discriminant byte, and the bytes after it.
+struct Enum<'de> {
+    discriminant: u8,
+    input: &'de [u8],
+}
+
+impl<'de> de::EnumAccess<'de> for Enum<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let deserializer: de::value::U32Deserializer<Error> =
+            (self.discriminant as u32).into_deserializer();
+        // An unknown variant is an unknown discriminant, like for derived enums.
+        let variant = seed
+            .deserialize(deserializer)
+            .map_err(|_| Error(SadbyError::UnexpectedToken))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Enum<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(Deserializer { input: self.input })
+    }
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        Deserializer { input: self.input }.compound(Framing::Fields(len), visitor)
+    }
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        Deserializer { input: self.input }.compound(Framing::Fields(fields.len()), visitor)
+    }
+}
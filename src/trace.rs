@@ -0,0 +1,262 @@
+//! Traced decoding, for finding where a payload and the type reading it part ways. `de_bytes`
+//! decodes like `Sadby::de_bytes` while recording what it reads: the types it goes into and comes
+//! out of, fields, length prefixes, variants and the values of primitives, each with where in the
+//! input it was.
+//!
+//! The recording goes through a thread local the derive and the impls in this crate report to,
+//! every hook returns right away when nothing is being traced. Offsets are into the traced input,
+//! bytes that aren't part of it, e.g. decompressed ones, have none.
+
+use super::*;
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::ops::Range;
+use std::thread;
+
+/// Something read while decoding, see `de_bytes`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// Started decoding a `ty` out of `len` bytes at `offset`.
+    Enter {
+        ty: &'static str,
+        offset: Option<usize>,
+        len: usize,
+    },
+    /// Done with the type entered last, `error` is what it failed with, if it did.
+    Leave {
+        ty: &'static str,
+        error: Option<String>,
+    },
+    /// The field `name` of the type being decoded is read out of `len` bytes at `offset`.
+    Field {
+        name: &'static str,
+        offset: Option<usize>,
+        len: usize,
+    },
+    /// A length prefix at `offset`, saying `len` bytes follow.
+    Length { offset: Option<usize>, len: usize },
+    /// The discriminant at `offset` picked the variant `name`.
+    Variant {
+        name: &'static str,
+        offset: Option<usize>,
+    },
+    /// A primitive read out of `len` bytes at `offset`, formatted with `Debug`.
+    Value {
+        offset: Option<usize>,
+        len: usize,
+        value: String,
+    },
+}
+
+struct Tracer {
+    /// Addresses of the traced input, slices of it are recorded with their offset into it.
+    base: Range<usize>,
+    events: Vec<Event>,
+}
+
+thread_local! {
+    /// Checked by every hook before touching `TRACER`, so decoding without tracing stays cheap.
+    static TRACING: Cell<bool> = const { Cell::new(false) };
+    static TRACER: RefCell<Option<Tracer>> = const { RefCell::new(None) };
+}
+
+/// Decodes `input` like `T::de_bytes`, returning what was read along with the result. The events
+/// end where decoding did, so the last ones read show where a failing payload went wrong.
+///
+/// The derive and the impls in this crate fail with `UnexpectedToken` on a payload shorter than it
+/// claims to be. A panic in an impl of your own isn't caught, it goes on once the events so far,
+/// ending with the types it unwound through, are written to stderr.
+pub fn de_bytes<T: Sadby>(input: &[u8]) -> (Result<T, SadbyError>, Vec<Event>) {
+    let start = input.as_ptr() as usize;
+    let session = Session::start(Tracer {
+        base: start..start + input.len(),
+        events: Vec::new(),
+    });
+
+    let result = de::<T>(input, Endian::Little);
+
+    (result, session.finish().events)
+}
+
+/// A running trace, put back to the one it's nested in when dropped, e.g. traced decoding from a
+/// `try_from` conversion traces the inner input on its own.
+struct Session {
+    outer: Option<Tracer>,
+    was_tracing: bool,
+}
+
+impl Session {
+    fn start(tracer: Tracer) -> Self {
+        Self {
+            outer: TRACER.replace(Some(tracer)),
+            was_tracing: TRACING.replace(true),
+        }
+    }
+
+    fn finish(self) -> Tracer {
+        TRACER.take().unwrap()
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        TRACING.set(self.was_tracing);
+        // Only still there when unwinding, `finish` takes it otherwise.
+        if let Some(tracer) = TRACER.replace(self.outer.take())
+            && thread::panicking()
+        {
+            eprint!("{}", render(&tracer.events));
+        }
+    }
+}
+
+/// The events as text, one per line, indented by how deep in the types they are.
+pub fn render(events: &[Event]) -> String {
+    let mut out = String::new();
+    let mut depth = 0usize;
+    for event in events {
+        if let Event::Leave { .. } = event {
+            depth = depth.saturating_sub(1);
+        }
+        out.push_str(&"  ".repeat(depth));
+        out.push_str(&event.to_string());
+        out.push('\n');
+        if let Event::Enter { .. } = event {
+            depth += 1;
+        }
+    }
+
+    out
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let at = |offset: &Option<usize>| match offset {
+            Some(offset) => format!("@{offset}"),
+            None => "@?".to_owned(),
+        };
+        let bytes = |len: &usize| match len {
+            1 => "1 byte".to_owned(),
+            len => format!("{len} bytes"),
+        };
+
+        match self {
+            Event::Enter { ty, offset, len } => write!(f, "{} {ty} ({})", at(offset), bytes(len)),
+            Event::Leave { ty, error: None } => write!(f, "done {ty}"),
+            Event::Leave {
+                ty,
+                error: Some(error),
+            } => write!(f, "failed {ty}: {error}"),
+            Event::Field { name, offset, len } => {
+                write!(f, "{} field {name} ({})", at(offset), bytes(len))
+            }
+            Event::Length { offset, len } => write!(f, "{} length {len}", at(offset)),
+            Event::Variant { name, offset } => write!(f, "{} variant {name}", at(offset)),
+            Event::Value { offset, len, value } => {
+                write!(f, "{} {value} ({})", at(offset), bytes(len))
+            }
+        }
+    }
+}
+
+fn record(event: impl FnOnce(&Range<usize>) -> Event) {
+    TRACER.with_borrow_mut(|tracer| {
+        if let Some(tracer) = tracer {
+            let event = event(&tracer.base);
+            tracer.events.push(event);
+        }
+    });
+}
+
+/// Offset of `bytes` into the traced input, if they're part of it.
+fn offset(base: &Range<usize>, bytes: &[u8]) -> Option<usize> {
+    let start = bytes.as_ptr() as usize;
+    (start >= base.start && start + bytes.len() <= base.end).then(|| start - base.start)
+}
+
+/// Decodes a `T` nested in what's being decoded, recording entering and leaving it.
+#[inline]
+pub fn de<T: Sadby>(input: &[u8], endian: Endian) -> Result<T, SadbyError> {
+    if !TRACING.get() {
+        return T::de_bytes_endian(input, endian);
+    }
+
+    let ty = std::any::type_name::<T>();
+    record(|base| Event::Enter {
+        ty,
+        offset: offset(base, input),
+        len: input.len(),
+    });
+
+    // Recorded on drop so a panic leaves the type too.
+    let mut leave = Leave {
+        ty,
+        error: Some("panicked".to_owned()),
+    };
+    let result = T::de_bytes_endian(input, endian);
+    leave.error = result.as_ref().err().map(|e| format!("{e:?}"));
+
+    result
+}
+
+/// Records leaving `ty` when dropped.
+struct Leave {
+    ty: &'static str,
+    error: Option<String>,
+}
+
+impl Drop for Leave {
+    fn drop(&mut self) {
+        let (ty, error) = (self.ty, self.error.take());
+        record(|_| Event::Leave { ty, error });
+    }
+}
+
+/// Records that the field `name` is read out of `bytes`.
+#[inline]
+pub fn field(name: &'static str, bytes: &[u8]) {
+    if TRACING.get() {
+        record(|base| Event::Field {
+            name,
+            offset: offset(base, bytes),
+            len: bytes.len(),
+        });
+    }
+}
+
+/// Records the length prefix at `input[at]`.
+#[inline]
+pub fn length(input: &[u8], at: usize) {
+    if TRACING.get()
+        && let Some(prefix) = input.get(at..at + 1)
+    {
+        record(|base| Event::Length {
+            offset: offset(base, prefix),
+            len: prefix[0] as usize,
+        });
+    }
+}
+
+/// Records that the discriminant at the start of `input` picked the variant `name`.
+#[inline]
+pub fn variant(name: &'static str, input: &[u8]) {
+    if TRACING.get() {
+        record(|base| Event::Variant {
+            name,
+            offset: offset(base, &input[..input.len().min(1)]),
+        });
+    }
+}
+
+/// Records `value` read out of `bytes`.
+#[inline]
+pub fn value(bytes: &[u8], value: &dyn fmt::Debug) {
+    if TRACING.get() {
+        record(|base| Event::Value {
+            offset: offset(base, bytes),
+            len: bytes.len(),
+            value: format!("{value:?}"),
+        });
+    }
+}
@@ -0,0 +1,254 @@
+//! `Value`, any `Sadby` value as a tree, for code that handles messages without knowing their
+//! types. Conversions go through the described encoding, so a `Value` has the shape of
+//! `described` payloads: structs keep their field names, enums their variant names.
+
+use super::*;
+
+/// A decoded value of any type, one variant per `described::Tag`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Unit,
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    F32(f32),
+    F64(f64),
+    Char(char),
+    Str(String),
+    Bytes(Vec<u8>),
+    None,
+    Some(Box<Value>),
+    Seq(Vec<Value>),
+    Tuple(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+    Struct(Vec<(String, Value)>),
+    /// A `#[sadby(tagged)]` struct, fields are known by their id.
+    StructIds(Vec<(u8, Value)>),
+    /// An enum variant, `value` is a `Struct` of its fields or `Unit`.
+    Variant {
+        discriminant: u8,
+        name: String,
+        value: Box<Value>,
+    },
+}
+
+/// Converts `value` to a `Value`.
+pub fn to_value<T: Sadby>(value: &T) -> Result<Value, SadbyError> {
+    described::from_bytes(&described::to_bytes(value)?)
+}
+
+/// Converts a `Value` back to a `T`, failing like `T::de_described` would if it doesn't fit.
+pub fn from_value<T: Sadby>(value: &Value) -> Result<T, SadbyError> {
+    described::from_bytes(&described::to_bytes(value)?)
+}
+
+impl Value {
+    /// The field called `name`, in a struct or an enum variant with fields.
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Struct(fields) => fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value),
+            Value::Variant { value, .. } => value.field(name),
+            _ => None,
+        }
+    }
+
+    fn read(reader: &mut described::Reader, depth: usize) -> Result<Self, SadbyError> {
+        // Nothing stops a payload from nesting deep enough to overflow the stack otherwise.
+        if depth > described::MAX_DEPTH {
+            return Err(SadbyError::UnexpectedToken);
+        }
+
+        let depth = depth + 1;
+        Ok(match reader.peek()? {
+            described::Tag::Unit => {
+                reader.tag()?;
+                Value::Unit
+            }
+            described::Tag::Bool => Value::Bool(bool::de_described(reader)?),
+            described::Tag::U8 => Value::U8(u8::de_described(reader)?),
+            described::Tag::U16 => Value::U16(u16::de_described(reader)?),
+            described::Tag::U32 => Value::U32(u32::de_described(reader)?),
+            described::Tag::U64 => Value::U64(u64::de_described(reader)?),
+            described::Tag::U128 => Value::U128(u128::de_described(reader)?),
+            described::Tag::I8 => Value::I8(i8::de_described(reader)?),
+            described::Tag::I16 => Value::I16(i16::de_described(reader)?),
+            described::Tag::I32 => Value::I32(i32::de_described(reader)?),
+            described::Tag::I64 => Value::I64(i64::de_described(reader)?),
+            described::Tag::I128 => Value::I128(i128::de_described(reader)?),
+            described::Tag::F32 => Value::F32(f32::de_described(reader)?),
+            described::Tag::F64 => Value::F64(f64::de_described(reader)?),
+            described::Tag::Char => Value::Char(char::de_described(reader)?),
+            described::Tag::Str => Value::Str(String::de_described(reader)?),
+            described::Tag::Bytes => {
+                reader.tag()?;
+                let len = reader.len()?;
+                Value::Bytes(reader.raw(len)?.to_vec())
+            }
+            described::Tag::None => {
+                reader.tag()?;
+                Value::None
+            }
+            described::Tag::Some => {
+                reader.tag()?;
+                Value::Some(Box::new(Value::read(reader, depth)?))
+            }
+            tag @ (described::Tag::Seq | described::Tag::Tuple) => {
+                reader.tag()?;
+                let items = (0..reader.len()?)
+                    .map(|_| Value::read(reader, depth))
+                    .collect::<Result<_, _>>()?;
+                match tag {
+                    described::Tag::Seq => Value::Seq(items),
+                    _ => Value::Tuple(items),
+                }
+            }
+            described::Tag::Map => {
+                reader.tag()?;
+                let entries = (0..reader.len()?)
+                    .map(|_| Ok((Value::read(reader, depth)?, Value::read(reader, depth)?)))
+                    .collect::<Result<_, SadbyError>>()?;
+                Value::Map(entries)
+            }
+            described::Tag::Struct => {
+                reader.tag()?;
+                let fields = (0..reader.len()?)
+                    .map(|_| Ok((reader.name()?.to_owned(), Value::read(reader, depth)?)))
+                    .collect::<Result<_, SadbyError>>()?;
+                Value::Struct(fields)
+            }
+            described::Tag::StructIds => {
+                reader.tag()?;
+                let fields = (0..reader.len()?)
+                    .map(|_| Ok((reader.byte()?, Value::read(reader, depth)?)))
+                    .collect::<Result<_, SadbyError>>()?;
+                Value::StructIds(fields)
+            }
+            described::Tag::Variant => {
+                reader.tag()?;
+                let discriminant =
+                    u8::try_from(reader.len()?).map_err(|_| SadbyError::OutOfRange)?;
+                let name = reader.name()?.to_owned();
+                let value = Box::new(Value::read(reader, depth)?);
+                Value::Variant {
+                    discriminant,
+                    name,
+                    value,
+                }
+            }
+        })
+    }
+}
+
+// Embedded in other types, a `Value` is written as its described encoding.
+impl Sadby for Value {
+    fn se_bytes(&self) -> Vec<u8> {
+        match self.try_se_bytes() {
+            Ok(buf) => buf,
+            Err(e) => panic!("Failed to encode: {e:?}"),
+        }
+    }
+    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
+        let value = described::from_bytes(input)?;
+        trace::value(input, &value);
+        Ok(value)
+    }
+    fn try_se_bytes(&self) -> Result<Vec<u8>, SadbyError> {
+        described::to_bytes(self)
+    }
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        match self {
+            Value::Unit => writer.tag(described::Tag::Unit),
+            Value::Bool(v) => v.se_described(writer)?,
+            Value::U8(v) => v.se_described(writer)?,
+            Value::U16(v) => v.se_described(writer)?,
+            Value::U32(v) => v.se_described(writer)?,
+            Value::U64(v) => v.se_described(writer)?,
+            Value::U128(v) => v.se_described(writer)?,
+            Value::I8(v) => v.se_described(writer)?,
+            Value::I16(v) => v.se_described(writer)?,
+            Value::I32(v) => v.se_described(writer)?,
+            Value::I64(v) => v.se_described(writer)?,
+            Value::I128(v) => v.se_described(writer)?,
+            Value::F32(v) => v.se_described(writer)?,
+            Value::F64(v) => v.se_described(writer)?,
+            Value::Char(v) => v.se_described(writer)?,
+            Value::Str(v) => v.se_described(writer)?,
+            Value::Bytes(bytes) => {
+                writer.tag(described::Tag::Bytes);
+                writer.len(bytes.len());
+                writer.raw(bytes);
+            }
+            Value::None => writer.tag(described::Tag::None),
+            Value::Some(value) => {
+                writer.tag(described::Tag::Some);
+                value.se_described(writer)?;
+            }
+            Value::Seq(items) | Value::Tuple(items) => {
+                writer.tag(match self {
+                    Value::Seq(_) => described::Tag::Seq,
+                    _ => described::Tag::Tuple,
+                });
+                writer.len(items.len());
+                for item in items {
+                    item.se_described(writer)?;
+                }
+            }
+            Value::Map(entries) => {
+                writer.tag(described::Tag::Map);
+                writer.len(entries.len());
+                for (key, value) in entries {
+                    key.se_described(writer)?;
+                    value.se_described(writer)?;
+                }
+            }
+            Value::Struct(fields) => {
+                writer.tag(described::Tag::Struct);
+                writer.len(fields.len());
+                for (name, value) in fields {
+                    writer.name(name);
+                    value.se_described(writer)?;
+                }
+            }
+            Value::StructIds(fields) => {
+                writer.tag(described::Tag::StructIds);
+                writer.len(fields.len());
+                for (id, value) in fields {
+                    writer.byte(*id);
+                    value.se_described(writer)?;
+                }
+            }
+            Value::Variant {
+                discriminant,
+                name,
+                value,
+            } => {
+                writer.tag(described::Tag::Variant);
+                writer.len(*discriminant as usize);
+                writer.name(name);
+                value.se_described(writer)?;
+            }
+        }
+
+        Ok(())
+    }
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        Value::read(reader, 0)
+    }
+}
+
+impl schema::SadbySchema for Value {
+    fn schema() -> schema::Schema {
+        schema::Schema::new::<Self>(schema::Kind::Described)
+    }
+}
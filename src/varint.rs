@@ -0,0 +1,129 @@
+//! Variable length integers behind `Varint<T>` and `#[sadby(varint)]`. Unsigned integers are
+//! written as LEB128, signed ones are zigzag encoded first so small negative numbers stay short.
+
+use super::*;
+
+/// Integers that can be written as a varint.
+pub trait VarintInt: Copy + std::fmt::Debug {
+    /// The value as written on the wire, before LEB128.
+    fn to_wire(self) -> u128;
+    /// Inverse of `to_wire`, `None` if the value doesn't fit the type.
+    fn from_wire(wire: u128) -> Option<Self>;
+}
+
+macro_rules! sadby_varint_unsigned {
+    ($( $type:ty ),*) => {
+        $(
+            impl VarintInt for $type {
+                fn to_wire(self) -> u128 {
+                    self as u128
+                }
+                fn from_wire(wire: u128) -> Option<Self> {
+                    Self::try_from(wire).ok()
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! sadby_varint_signed {
+    ($( $type:ty ),*) => {
+        $(
+            impl VarintInt for $type {
+                fn to_wire(self) -> u128 {
+                    let value = self as i128;
+                    ((value << 1) ^ (value >> 127)) as u128
+                }
+                fn from_wire(wire: u128) -> Option<Self> {
+                    let value = (wire >> 1) as i128 ^ -((wire & 1) as i128);
+                    Self::try_from(value).ok()
+                }
+            }
+        )*
+    };
+}
+
+sadby_varint_unsigned!(u8, u16, u32, u64, u128, usize);
+sadby_varint_signed!(i8, i16, i32, i64, i128, isize);
+
+/// Encodes `value` as a varint, seven bits per byte with the high bit set on all but the last.
+pub fn se<T: VarintInt>(value: &T) -> Vec<u8> {
+    let mut wire = value.to_wire();
+
+    let mut buf = Vec::new();
+    while wire >= 0x80 {
+        buf.push(wire as u8 | 0x80);
+        wire >>= 7;
+    }
+    buf.push(wire as u8);
+
+    buf
+}
+
+/// Decodes a varint taking up all of `input`. Encodings longer than they need to be are
+/// rejected, so every value has exactly one.
+pub fn de<T: VarintInt>(input: &[u8]) -> Result<T, SadbyError> {
+    match take(input)? {
+        (value, []) => Ok(value),
+        _ => Err(SadbyError::UnexpectedToken),
+    }
+}
+
+/// Decodes a varint at the start of `input`, returning it together with the bytes after it.
+pub fn take<T: VarintInt>(input: &[u8]) -> Result<(T, &[u8]), SadbyError> {
+    let mut wire = 0u128;
+
+    for (i, &byte) in input.iter().enumerate() {
+        let shift = 7 * i as u32;
+        let bits = (byte & 0x7f) as u128;
+        if shift >= u128::BITS || (bits << shift) >> shift != bits {
+            return Err(SadbyError::OutOfRange);
+        }
+        wire |= bits << shift;
+
+        if byte & 0x80 == 0 {
+            // A last byte adding nothing to the ones before it.
+            if byte == 0 && i > 0 {
+                return Err(SadbyError::UnexpectedToken);
+            }
+            let value = T::from_wire(wire).ok_or(SadbyError::OutOfRange)?;
+            return Ok((value, &input[i + 1..]));
+        }
+    }
+
+    Err(SadbyError::UnexpectedToken)
+}
+
+/// Encodes `T` as a varint, e.g. `Varint<u64>` takes a single byte for anything below 128.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Varint<T>(pub T);
+
+impl<T> From<T> for Varint<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: VarintInt + Sadby> Sadby for Varint<T> {
+    fn se_bytes(&self) -> Vec<u8> {
+        se(&self.0)
+    }
+    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
+        let value = de(input)?;
+        trace::value(input, &value);
+        Ok(Self(value))
+    }
+    // The described encoding of integers is a varint already.
+    fn se_described(&self, writer: &mut described::Writer) -> Result<(), SadbyError> {
+        self.0.se_described(writer)
+    }
+    fn de_described(reader: &mut described::Reader) -> Result<Self, SadbyError> {
+        Ok(Self(T::de_described(reader)?))
+    }
+}
+
+impl<T: VarintInt + schema::SadbySchema> schema::SadbySchema for Varint<T> {
+    fn schema() -> schema::Schema {
+        T::schema().varint()
+    }
+}
@@ -0,0 +1,78 @@
+use sadby::{Sadby, SadbyError};
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Header {
+    #[sadby(bits = 4)]
+    version: u8,
+    #[sadby(bits = 4)]
+    ihl: u8,
+    #[sadby(bits = 1)]
+    flag: bool,
+    #[sadby(bits = 3)]
+    delta: i8,
+    len: u16,
+    #[sadby(bits = 12)]
+    id: u16,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Flags {
+    #[sadby(bits = 3)]
+    a: u8,
+    #[sadby(bits = 1)]
+    b: bool,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[repr(u8)]
+enum Frame {
+    Bits {
+        #[sadby(bits = 2)]
+        a: u8,
+        #[sadby(bits = 6)]
+        b: u8,
+    },
+    Plain(u32),
+}
+
+#[test]
+fn bit_fields_round_trip() {
+    let value = Header {
+        version: 4,
+        ihl: 5,
+        flag: true,
+        delta: -3,
+        len: 1000,
+        id: 4095,
+    };
+    assert_eq!(Header::de_bytes(&value.se_bytes()), Ok(value));
+
+    for value in [Frame::Bits { a: 3, b: 63 }, Frame::Plain(77)] {
+        assert_eq!(Frame::de_bytes(&value.se_bytes()), Ok(value));
+    }
+}
+
+#[test]
+fn bit_fields_share_bytes() {
+    let value = Flags { a: 7, b: true };
+    assert_eq!(value.se_bytes(), [0b1111]);
+    assert_eq!(Flags::de_bytes(&[0b1111]), Ok(value));
+}
+
+#[test]
+fn values_too_wide_are_out_of_range() {
+    assert_eq!(
+        Flags { a: 8, b: false }.try_se_bytes(),
+        Err(SadbyError::OutOfRange)
+    );
+
+    let value = Header {
+        version: 4,
+        ihl: 5,
+        flag: true,
+        delta: -5,
+        len: 1000,
+        id: 4095,
+    };
+    assert_eq!(value.try_se_bytes(), Err(SadbyError::OutOfRange));
+}
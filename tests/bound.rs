@@ -0,0 +1,102 @@
+use sadby::{Sadby, SadbyError};
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Envelope<T> {
+    body: T,
+    id: u8,
+}
+
+trait Unit {
+    type Repr: std::fmt::Debug + PartialEq;
+}
+
+// Not `Sadby`, only its representation is ever encoded.
+#[derive(Debug, PartialEq)]
+struct Millis;
+
+impl Unit for Millis {
+    type Repr = u16;
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Reading<T: Unit> {
+    value: <T as Unit>::Repr,
+    id: u8,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Series<T: Unit> {
+    first: T::Repr,
+    rest: Vec<T::Repr>,
+}
+
+/// Only says how many items there are, the items themselves come from `Default`.
+#[derive(Debug, PartialEq)]
+struct Count<T>(Vec<T>);
+
+impl<T: Default> Sadby for Count<T> {
+    fn se_bytes(&self) -> Vec<u8> {
+        vec![self.0.len() as u8]
+    }
+    fn de_bytes(input: &[u8]) -> Result<Self, SadbyError> {
+        let len = *input.first().ok_or(SadbyError::UnexpectedToken)?;
+        Ok(Count((0..len).map(|_| T::default()).collect()))
+    }
+}
+
+#[derive(Debug, PartialEq, Default)]
+struct Slot;
+
+// The inferred `T: Sadby` would rule out `Slot`, `Count<T>` only needs `T: Default`.
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(bound = "T: Default")]
+struct Batch<T> {
+    id: u8,
+    count: Count<T>,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Slots<T, U> {
+    #[sadby(bound = "T: Default")]
+    count: Count<T>,
+    extra: U,
+}
+
+#[test]
+fn type_parameters_get_sadby_bounds() {
+    let value = Envelope { body: 5u16, id: 3 };
+    assert_eq!(Envelope::de_bytes(&value.se_bytes()), Ok(value));
+
+    let value = Envelope {
+        body: "nested".to_owned(),
+        id: 4,
+    };
+    assert_eq!(Envelope::de_bytes(&value.se_bytes()), Ok(value));
+}
+
+#[test]
+fn associated_types_get_sadby_bounds() {
+    let value = Reading::<Millis> { value: 300, id: 1 };
+    assert_eq!(Reading::de_bytes(&value.se_bytes()), Ok(value));
+
+    let value = Series::<Millis> {
+        first: 1,
+        rest: vec![2, 300],
+    };
+    assert_eq!(Series::de_bytes(&value.se_bytes()), Ok(value));
+}
+
+#[test]
+fn bound_overrides_inferred_bounds() {
+    let value = Batch {
+        id: 1,
+        count: Count(vec![Slot, Slot]),
+    };
+    assert_eq!(Batch::de_bytes(&value.se_bytes()), Ok(value));
+
+    let value = Slots {
+        count: Count(vec![Slot]),
+        extra: 7u32,
+    };
+    assert_eq!(Slots::de_bytes(&value.se_bytes()), Ok(value));
+}
@@ -0,0 +1,114 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use sadby::{Sadby, SadbyError, canonical};
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Record {
+    map: HashMap<String, u32>,
+    set: HashSet<u16>,
+    float: f64,
+    #[sadby(varint)]
+    varint: u64,
+    tree: BTreeMap<u8, bool>,
+    text: String,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(tagged)]
+struct Tagged {
+    #[sadby(id = 1)]
+    a: u8,
+    #[sadby(id = 2)]
+    b: u8,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[repr(u8)]
+enum Shape {
+    Empty,
+    Square(u8),
+}
+
+#[test]
+fn encoding_is_deterministic() {
+    // Hash maps and sets iterate in a different order every time they're built.
+    let record = || Record {
+        map: (0..12).map(|i| (format!("k{i}"), i)).collect(),
+        set: (0..12).collect(),
+        float: f64::from_bits(0x7ff8_0000_0000_0001),
+        varint: 300,
+        tree: [(1, true), (2, false)].into(),
+        text: "héllo".to_owned(),
+    };
+    let bytes = canonical::to_bytes(&record()).unwrap();
+    for _ in 0..5 {
+        assert_eq!(canonical::to_bytes(&record()), Ok(bytes.clone()));
+    }
+
+    let decoded: Record = canonical::from_bytes(&bytes).unwrap();
+    assert!(decoded.float.is_nan());
+    assert_eq!(decoded.text, "héllo");
+}
+
+#[test]
+fn only_canonical_encoding_normalizes_nans() {
+    let nan = f64::from_bits(0x7ff8_0000_0000_0001);
+    assert_eq!(nan.se_bytes(), 0x7ff8_0000_0000_0001u64.se_bytes());
+    assert_eq!(canonical::to_bytes(&nan), Ok(f64::NAN.se_bytes()));
+    assert_eq!(nan.se_bytes(), 0x7ff8_0000_0000_0001u64.se_bytes());
+}
+
+#[test]
+fn nan_payloads_and_trailing_bytes_are_non_canonical() {
+    let mut bytes = canonical::to_bytes(&f64::NAN).unwrap();
+    bytes[0] ^= 1;
+    assert_eq!(
+        canonical::from_bytes::<f64>(&bytes),
+        Err(SadbyError::NonCanonical)
+    );
+    assert_eq!(
+        canonical::from_bytes::<u16>(&[1, 0, 0]),
+        Err(SadbyError::NonCanonical)
+    );
+}
+
+#[test]
+fn tagged_fields_must_be_in_id_order() {
+    let bytes = Tagged { a: 1, b: 2 }.se_bytes();
+    assert_eq!(canonical::from_bytes(&bytes), Ok(Tagged { a: 1, b: 2 }));
+
+    let swapped = [&bytes[3..], &bytes[..3]].concat();
+    assert_eq!(Tagged::de_bytes(&swapped), Ok(Tagged { a: 1, b: 2 }));
+    assert_eq!(
+        canonical::from_bytes::<Tagged>(&swapped),
+        Err(SadbyError::NonCanonical)
+    );
+}
+
+#[test]
+fn sets_must_be_sorted_and_unique() {
+    assert!(canonical::from_bytes::<HashSet<u16>>(&vec![1u16, 2].se_bytes()).is_ok());
+    assert_eq!(
+        canonical::from_bytes::<HashSet<u16>>(&vec![2u16, 1].se_bytes()),
+        Err(SadbyError::NonCanonical)
+    );
+    assert_eq!(
+        canonical::from_bytes::<HashSet<u16>>(&vec![1u16, 1].se_bytes()),
+        Err(SadbyError::NonCanonical)
+    );
+}
+
+#[test]
+fn btree_maps_are_sorted_by_encoded_key() {
+    // 256 comes after 1, but its little endian encoding sorts first.
+    let map = BTreeMap::from([(1u16, 1u8), (256, 2)]);
+    assert_eq!(map.se_bytes(), vec![(256u16, 2u8), (1, 1)].se_bytes());
+    assert_eq!(canonical::from_bytes(&map.se_bytes()), Ok(map));
+}
+
+#[test]
+fn unit_variants_are_their_discriminant() {
+    assert_eq!(Shape::Empty.se_bytes(), [0]);
+    assert_eq!(Shape::Square(3).se_bytes(), [1, 3]);
+    assert_eq!(canonical::from_bytes(&[0]), Ok(Shape::Empty));
+}
@@ -0,0 +1,87 @@
+use sadby::checksum::{Checksum, Crc32, Crc32c};
+use sadby::{Checked, Sadby, SadbyError};
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(checksum = "crc32", magic = b"R", version = 2)]
+struct Record {
+    a: u32,
+    b: String,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(packed, checksum = "crc32c", endian = "big")]
+struct Packed {
+    a: u32,
+}
+
+#[test]
+fn check_values() {
+    assert_eq!(Checksum::Crc32.compute(b"123456789"), 0xcbf43926);
+    assert_eq!(Checksum::Crc32c.compute(b"123456789"), 0xe3069283);
+}
+
+#[test]
+fn checksummed_containers_round_trip() {
+    let value = Record {
+        a: 5,
+        b: "x".to_owned(),
+    };
+    let bytes = value.se_bytes();
+    assert_eq!(bytes[0], b'R');
+    assert_eq!(Record::de_bytes(&bytes), Ok(value));
+
+    let bytes = Packed { a: 1 }.se_bytes();
+    assert_eq!(Packed::FIXED_SIZE, Some(8));
+    assert_eq!(
+        bytes[4..],
+        Checksum::Crc32c.compute(&[0, 0, 0, 1]).to_be_bytes()
+    );
+    assert_eq!(Packed::de_bytes(&bytes), Ok(Packed { a: 1 }));
+}
+
+#[test]
+fn corrupted_payload_is_bad_checksum() {
+    let mut bytes = Record {
+        a: 5,
+        b: "x".to_owned(),
+    }
+    .se_bytes();
+    bytes[3] ^= 1;
+    assert!(matches!(
+        Record::de_bytes(&bytes),
+        Err(SadbyError::BadChecksum { .. })
+    ));
+
+    let mut bytes = Checked::<u32>::new(7).se_bytes();
+    bytes[0] ^= 1;
+    assert!(matches!(
+        Checked::<u32>::de_bytes(&bytes),
+        Err(SadbyError::BadChecksum { .. })
+    ));
+}
+
+#[test]
+fn checked_wrapper() {
+    let value = Checked::<Vec<u16>>::new(vec![1, 2]);
+    assert_eq!(Checked::de_bytes(&value.se_bytes()), Ok(value));
+    assert_eq!(Checked::<u8>::FIXED_SIZE, Some(5));
+    assert_eq!(
+        Checked::<u8>::de_bytes(&[1, 2]),
+        Err(SadbyError::UnexpectedToken)
+    );
+}
+
+#[test]
+fn checked_algorithms() {
+    let bytes = Checked::<_, Crc32>::new(1u8).se_bytes();
+    assert_eq!(bytes[1..], Checksum::Crc32.compute(&[1]).to_le_bytes());
+    assert_eq!(Checked::<u8>::new(1).se_bytes(), bytes);
+
+    let bytes = Checked::<_, Crc32c>::new(1u8).se_bytes();
+    assert_eq!(bytes[1..], Checksum::Crc32c.compute(&[1]).to_le_bytes());
+    assert_eq!(Checked::<u8, Crc32c>::de_bytes(&bytes), Ok(Checked::new(1)));
+    assert!(matches!(
+        Checked::<u8, Crc32>::de_bytes(&bytes),
+        Err(SadbyError::BadChecksum { .. })
+    ));
+}
@@ -0,0 +1,156 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use sadby::{Sadby, SadbySchema};
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Point {
+    x: u16,
+    name: String,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(version = 1)]
+struct Labeled {
+    name: String,
+    x: u16,
+}
+
+const DOCUMENT: &str = r#"{"x": 300, "name": "hi"}"#;
+/// `DOCUMENT` as a `Point`.
+const POINT: &[u8] = &[2, 44, 1, 2, b'h', b'i'];
+
+/// A directory of its own for `test`, with the schemas the tests use written to it.
+fn dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("sadby-cli-{}-{test}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("point.json"), Point::schema().to_json()).unwrap();
+    std::fs::write(dir.join("labeled.json"), Labeled::schema().to_json()).unwrap();
+    dir
+}
+
+/// Runs the binary with `stdin`, returning its exit code, stdout and stderr.
+fn sadby(args: &[&str], stdin: &[u8]) -> (i32, Vec<u8>, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sadby"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.code().unwrap(),
+        output.stdout,
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[test]
+fn encode_and_decode() {
+    let dir = dir("encode_and_decode");
+    let schema = dir.join("point.json");
+    let schema = schema.to_str().unwrap();
+    assert_eq!(
+        Point {
+            x: 300,
+            name: "hi".to_owned(),
+        }
+        .se_bytes(),
+        POINT
+    );
+
+    let (code, stdout, _) = sadby(&["encode", schema], DOCUMENT.as_bytes());
+    assert_eq!(code, 0);
+    assert_eq!(stdout, POINT);
+
+    let (code, stdout, _) = sadby(&["--hex", "encode", schema, "-"], DOCUMENT.as_bytes());
+    assert_eq!(code, 0);
+    assert_eq!(String::from_utf8(stdout).unwrap().trim(), hex(POINT));
+
+    let payload = dir.join("point.bin");
+    std::fs::write(&payload, POINT).unwrap();
+    let (code, stdout, _) = sadby(&["decode", schema, payload.to_str().unwrap()], b"");
+    assert_eq!(code, 0);
+    let json = String::from_utf8(stdout).unwrap();
+    assert!(json.contains("\"x\": 300"), "{json}");
+    assert!(json.contains("\"name\": \"hi\""), "{json}");
+
+    let (code, stdout, _) = sadby(&["encode", schema], &json.into_bytes());
+    assert_eq!(code, 0);
+    assert_eq!(stdout, POINT);
+}
+
+#[test]
+fn dump_annotates_the_bytes() {
+    let dir = dir("dump_annotates_the_bytes");
+    let schema = dir.join("point.json");
+
+    let (code, stdout, _) = sadby(&["dump", schema.to_str().unwrap()], POINT);
+    assert_eq!(code, 0);
+    let dump = String::from_utf8(stdout).unwrap();
+    assert!(dump.starts_with("000000  "), "{dump}");
+    assert!(dump.contains("x: "), "{dump}");
+    assert!(dump.contains("name: "), "{dump}");
+    assert!(!dump.contains("unread"), "{dump}");
+
+    // Bytes past where decoding stopped are still shown, and the payload error is reported.
+    let mut bytes = POINT.to_vec();
+    bytes[0] = 0xff;
+    let (code, stdout, stderr) = sadby(&["dump", schema.to_str().unwrap()], &bytes);
+    assert_eq!(code, 1);
+    assert!(String::from_utf8(stdout).unwrap().contains("unread"));
+    assert!(stderr.starts_with("error: "), "{stderr}");
+}
+
+#[test]
+fn convert_between_schemas() {
+    let dir = dir("convert_between_schemas");
+    let (from, to) = (dir.join("point.json"), dir.join("labeled.json"));
+
+    let (code, stdout, _) = sadby(
+        &["convert", from.to_str().unwrap(), to.to_str().unwrap()],
+        POINT,
+    );
+    assert_eq!(code, 0);
+    assert_eq!(
+        Labeled::de_bytes(&stdout),
+        Ok(Labeled {
+            name: "hi".to_owned(),
+            x: 300,
+        })
+    );
+}
+
+#[test]
+fn exit_codes() {
+    let dir = dir("exit_codes");
+    let schema = dir.join("point.json");
+    let schema = schema.to_str().unwrap();
+
+    // Usage errors.
+    let (code, _, stderr) = sadby(&[], b"");
+    assert_eq!(code, 2);
+    assert!(stderr.starts_with("Usage: sadby"), "{stderr}");
+    assert_eq!(sadby(&["frobnicate", schema], b"").0, 2);
+
+    // Files that can't be read, schemas that aren't schemas and bad hex.
+    assert_eq!(sadby(&["decode", "/nonexistent/schema.json"], b"").0, 2);
+    assert_eq!(sadby(&["decode", schema, "/nonexistent/payload"], b"").0, 2);
+    std::fs::write(dir.join("bad.json"), "{\"kind\": \"nope\"}").unwrap();
+    assert_eq!(
+        sadby(&["decode", dir.join("bad.json").to_str().unwrap()], b"").0,
+        2
+    );
+    assert_eq!(sadby(&["--hex", "decode", schema], b"abc").0, 2);
+
+    // Payloads and documents that don't fit the schema.
+    assert_eq!(sadby(&["decode", schema], &[2, 1]).0, 1);
+    assert_eq!(sadby(&["encode", schema], br#"{"x": "no"}"#).0, 1);
+}
@@ -0,0 +1,200 @@
+use std::process::Command;
+
+use sadby::SadbySchema;
+use sadby::schema::Schema;
+use sadby::schema::compat::{Break, check};
+
+mod v1 {
+    use sadby::Sadby;
+
+    #[derive(Sadby)]
+    #[sadby(version = 1)]
+    pub struct Msg {
+        pub a: u32,
+        pub b: String,
+        pub c: u16,
+    }
+
+    #[derive(Sadby)]
+    #[repr(u8)]
+    pub enum Event {
+        A,
+        B(u8),
+        C,
+    }
+
+    #[derive(Sadby)]
+    #[sadby(tagged)]
+    pub struct Tagged {
+        #[sadby(id = 1)]
+        pub a: u8,
+        #[sadby(id = 2)]
+        pub b: u8,
+    }
+
+    #[derive(Sadby)]
+    pub struct Encodings {
+        #[sadby(varint)]
+        pub n: u32,
+        pub x: u16,
+    }
+}
+
+mod v2 {
+    use sadby::Sadby;
+
+    #[derive(Sadby)]
+    #[sadby(version = 2)]
+    pub struct Msg {
+        pub b: String,
+        pub a: u64,
+        pub c: u32,
+        #[sadby(since = 2)]
+        pub d: u8,
+        pub e: u8,
+    }
+
+    #[derive(Sadby)]
+    #[repr(u8)]
+    pub enum Event {
+        A,
+        B(u8) = 5,
+        D,
+    }
+
+    #[derive(Sadby)]
+    #[sadby(tagged)]
+    pub struct Tagged {
+        #[sadby(id = 1)]
+        pub a: u8,
+        #[sadby(id = 2)]
+        pub z: u8,
+        #[sadby(id = 3)]
+        pub c: u8,
+    }
+
+    #[derive(Sadby)]
+    pub struct Encodings {
+        #[sadby(varint)]
+        pub n: u64,
+        #[sadby(endian = "big")]
+        pub x: u16,
+    }
+}
+
+fn show(breaks: &[Break]) -> Vec<String> {
+    breaks.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn unchanged_schemas_are_compatible() {
+    for schema in [
+        v1::Msg::schema(),
+        v2::Msg::schema(),
+        v2::Event::schema(),
+        v2::Tagged::schema(),
+        v2::Encodings::schema(),
+    ] {
+        assert!(check(&schema, &schema).is_empty());
+    }
+}
+
+#[test]
+fn struct_changes() {
+    let breaks = show(&check(&v1::Msg::schema(), &v2::Msg::schema()));
+    assert!(
+        breaks.iter().any(|b| b.contains("b: field moved")),
+        "{breaks:?}"
+    );
+    assert!(
+        breaks
+            .iter()
+            .any(|b| b.contains("c: size changed from 2 to 4")),
+        "{breaks:?}"
+    );
+    assert!(
+        breaks
+            .iter()
+            .any(|b| b.starts_with("backward: e: field added")),
+        "{breaks:?}"
+    );
+    // `d` is gated on the version, old payloads don't carry it and that's fine.
+    assert!(!breaks.iter().any(|b| b.contains(" d:")), "{breaks:?}");
+
+    let breaks = show(&check(&v2::Msg::schema(), &v1::Msg::schema()));
+    assert!(
+        breaks
+            .iter()
+            .any(|b| b.starts_with("forward: d: field removed")),
+        "{breaks:?}"
+    );
+}
+
+#[test]
+fn enum_changes() {
+    let breaks = show(&check(&v1::Event::schema(), &v2::Event::schema()));
+    assert!(
+        breaks.iter().any(|b| b.contains("B: variant renumbered")),
+        "{breaks:?}"
+    );
+    assert!(
+        breaks
+            .iter()
+            .any(|b| b.starts_with("backward: C: variant removed")),
+        "{breaks:?}"
+    );
+    assert!(
+        breaks
+            .iter()
+            .any(|b| b.starts_with("forward: D: variant added")),
+        "{breaks:?}"
+    );
+}
+
+#[test]
+fn reused_ids_and_changed_encodings() {
+    let breaks = show(&check(&v1::Tagged::schema(), &v2::Tagged::schema()));
+    assert_eq!(breaks, ["backward and forward: z: id 2 reused, it was `b`"]);
+
+    let breaks = check(&v1::Encodings::schema(), &v2::Encodings::schema());
+    assert_eq!(breaks.len(), 2, "{breaks:?}");
+}
+
+/// Runs `sadby-compat` on the two schemas, returning its exit code and stdout.
+fn sadby_compat(test: &str, old: Schema, new: Schema) -> (i32, String) {
+    let dir = std::env::temp_dir().join(format!("sadby-compat-{}-{test}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (old_path, new_path) = (dir.join("old.json"), dir.join("new.json"));
+    std::fs::write(&old_path, old.to_json()).unwrap();
+    std::fs::write(&new_path, new.to_json()).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sadby-compat"))
+        .args([&old_path, &new_path])
+        .output()
+        .unwrap();
+    (
+        output.status.code().unwrap(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn binary_exit_codes() {
+    let (code, stdout) = sadby_compat("same", v1::Event::schema(), v1::Event::schema());
+    assert_eq!((code, stdout.trim()), (0, "compatible"));
+
+    let (code, stdout) = sadby_compat("changed", v1::Tagged::schema(), v2::Tagged::schema());
+    assert_eq!(code, 1);
+    assert_eq!(
+        stdout.trim(),
+        "backward and forward: z: id 2 reused, it was `b`"
+    );
+
+    let status = Command::new(env!("CARGO_BIN_EXE_sadby-compat"))
+        .arg("/nonexistent/old.json")
+        .arg("/nonexistent/new.json")
+        .output()
+        .unwrap()
+        .status;
+    assert_eq!(status.code(), Some(2));
+}
@@ -0,0 +1,94 @@
+use sadby::{Compressed, Sadby, SadbyError, compress};
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Log {
+    id: u16,
+    #[sadby(compress)]
+    lines: Vec<String>,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Small {
+    id: u16,
+    #[sadby(compress(limit = 16))]
+    data: String,
+}
+
+fn lines(count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("line {i}")).collect()
+}
+
+#[test]
+fn round_trip() {
+    let raw = b"abcabcabcabcabcabcabcabc hello hello hello".repeat(10);
+    let compressed = compress::se(&raw);
+    assert!(compressed.len() < raw.len());
+    assert_eq!(compress::de(&compressed, raw.len()), Ok(raw));
+
+    let log = Log {
+        id: 1,
+        lines: vec!["started".to_owned(); 40],
+    };
+    assert_eq!(Log::de_bytes(&log.se_bytes()), Ok(log));
+
+    let value = Compressed(lines(30));
+    assert_eq!(Compressed::de_bytes(&value.se_bytes()), Ok(value));
+}
+
+#[test]
+fn field_too_large_for_its_prefix() {
+    let log = Log {
+        id: 1,
+        lines: lines(200),
+    };
+    assert_eq!(log.try_se_bytes(), Err(SadbyError::OutOfRange));
+}
+
+#[test]
+fn size_limit() {
+    let raw = vec![7u8; 1000];
+    assert_eq!(
+        compress::de(&compress::se(&raw), 999),
+        Err(SadbyError::TooLarge {
+            size: 1000,
+            limit: 999
+        })
+    );
+
+    let small = Small {
+        id: 1,
+        data: "a".repeat(16),
+    };
+    assert_eq!(Small::de_bytes(&small.se_bytes()), Ok(small));
+
+    let bytes = Small {
+        id: 1,
+        data: "a".repeat(17),
+    }
+    .se_bytes();
+    assert_eq!(
+        Small::de_bytes(&bytes),
+        Err(SadbyError::TooLarge {
+            size: 17,
+            limit: 16
+        })
+    );
+}
+
+#[test]
+fn claimed_size_must_match() {
+    // Claims 64 MiB, holds one literal.
+    let mut bytes = sadby::varint::se(&compress::DEFAULT_LIMIT);
+    bytes.extend([0x10, b'a']);
+    assert_eq!(
+        compress::de(&bytes, compress::DEFAULT_LIMIT),
+        Err(SadbyError::UnexpectedToken)
+    );
+
+    let mut bytes = compress::se(b"hello");
+    bytes.pop();
+    assert_eq!(
+        compress::de(&bytes, compress::DEFAULT_LIMIT),
+        Err(SadbyError::UnexpectedToken)
+    );
+}
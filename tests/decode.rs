@@ -0,0 +1,250 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use sadby::schema::{Kind, Schema};
+use sadby::value::{from_value, to_value};
+use sadby::{Checked, Sadby, SadbyError, SadbySchema, Value, Varint};
+
+#[derive(Sadby, Debug, PartialEq, Clone)]
+#[sadby(magic = b"MS", version = 2, checksum = "crc32c", endian = "big")]
+struct Msg {
+    id: u32,
+    #[sadby(varint)]
+    n: i64,
+    #[sadby(since = 2)]
+    tags: Vec<String>,
+    #[sadby(bits = 3)]
+    a: u8,
+    #[sadby(bits = 5)]
+    b: i8,
+    #[sadby(bits = 1)]
+    f: bool,
+    #[sadby(constant = 7)]
+    c: u16,
+    body: Event,
+    map: BTreeMap<u8, Option<[u16; 2]>>,
+    #[sadby(compress)]
+    text: String,
+    tuple: (u8, String, f64),
+    ch: char,
+    array: [String; 2],
+    value: Value,
+    checked: Checked<i16>,
+    wide: Varint<u128>,
+}
+
+#[derive(Sadby, Debug, PartialEq, Clone, Default)]
+#[repr(u8)]
+enum Event {
+    #[default]
+    A = 3,
+    B(u8, String),
+    C {
+        x: i8,
+    },
+}
+
+#[derive(Sadby, Debug, PartialEq, Default)]
+#[sadby(packed)]
+struct Packed(
+    u8,
+    [u16; 2],
+    #[sadby(bits = 4)] u8,
+    #[sadby(bits = 4)] u8,
+    f32,
+);
+
+#[derive(Sadby, Debug, PartialEq, Default)]
+#[sadby(tagged)]
+struct Tagged {
+    #[sadby(id = 4)]
+    a: f32,
+    #[sadby(id = 9)]
+    b: Option<String>,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Node {
+    value: u8,
+    kids: Vec<Node>,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(magic = b"MS", checksum = "crc32c")]
+struct Framed {
+    id: u32,
+    name: String,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(version = 3)]
+struct Old {
+    a: u8,
+    #[sadby(since = 3)]
+    b: u16,
+    c: i128,
+}
+
+/// Checks decoding with a schema read back from JSON matches decoding with the type.
+fn same<T: SadbySchema + std::fmt::Debug>(value: &T) {
+    let bytes = value.se_bytes();
+    let schema = Schema::from_json(&T::schema().to_json()).unwrap();
+    let expected = to_value(&T::de_bytes(&bytes).unwrap()).unwrap();
+    assert_eq!(schema.decode(&bytes), Ok(expected), "{value:?}");
+}
+
+#[test]
+fn decodes_like_the_type() {
+    let msg = Msg {
+        id: 0xdeadbeef,
+        n: -300,
+        tags: vec!["x".to_owned(), "yz".to_owned()],
+        a: 5,
+        b: -9,
+        f: true,
+        c: 7,
+        body: Event::B(1, "hi".to_owned()),
+        map: BTreeMap::from([(1, Some([2, 3])), (4, None)]),
+        text: "z".repeat(30),
+        tuple: (1, "t".to_owned(), 1.5),
+        ch: 'q',
+        array: ["a".to_owned(), "bc".to_owned()],
+        value: Value::Seq(vec![Value::U8(1), Value::Str("v".to_owned())]),
+        checked: Checked::new(-2),
+        wide: Varint(u128::MAX),
+    };
+    same(&msg);
+    same(&Msg {
+        body: Event::C { x: -1 },
+        ..msg.clone()
+    });
+    same(&Msg {
+        body: Event::A,
+        ..msg.clone()
+    });
+    same(&Packed(1, [2, 3], 4, 15, -0.5));
+    same(&Tagged {
+        a: 2.0,
+        b: Some("b".to_owned()),
+    });
+    same(&Node {
+        value: 1,
+        kids: vec![Node {
+            value: 2,
+            kids: vec![],
+        }],
+    });
+    same(&HashMap::from([
+        (1u16, "a".to_owned()),
+        (2, "b".to_owned()),
+    ]));
+    same(&HashSet::from([5u64, 1, 3]));
+    same(&(1i8,));
+    same(&[Some(1u8), None]);
+}
+
+#[test]
+fn encodes_like_the_type() {
+    let schema = Event::schema();
+    for event in [Event::A, Event::B(1, "hi".to_owned()), Event::C { x: -1 }] {
+        let bytes = event.se_bytes();
+        assert_eq!(schema.encode(&schema.decode(&bytes).unwrap()), Ok(bytes));
+    }
+}
+
+#[test]
+fn missing_fields_are_left_out() {
+    let old = [
+        2u8, 1, 7, 16, 5, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    let value = Old::schema().decode(&old).unwrap();
+    assert_eq!(
+        value,
+        Value::Struct(vec![
+            ("a".to_owned(), Value::U8(7)),
+            ("c".to_owned(), Value::I128(5)),
+        ])
+    );
+    assert_eq!(from_value::<Old>(&value), Old::de_bytes(&old));
+
+    assert_eq!(
+        Tagged::schema().decode(&[4, 4, 0, 0, 0, 64, 77, 1, 0]),
+        Ok(Value::StructIds(vec![(4, Value::F32(2.0))]))
+    );
+}
+
+#[test]
+fn errors_match_de_bytes() {
+    let bytes = Framed {
+        id: 1,
+        name: "x".to_owned(),
+    }
+    .se_bytes();
+    let schema = Framed::schema();
+
+    let mut bad = bytes.clone();
+    bad[0] = b'X';
+    assert_eq!(
+        schema.decode(&bad).unwrap_err(),
+        Framed::de_bytes(&bad).unwrap_err()
+    );
+
+    let mut bad = bytes.clone();
+    bad[5] ^= 1;
+    assert_eq!(
+        schema.decode(&bad).unwrap_err(),
+        Framed::de_bytes(&bad).unwrap_err()
+    );
+
+    let input = [0x80, 0x02];
+    assert_eq!(
+        <Varint<u8>>::schema().decode(&input),
+        Err(SadbyError::OutOfRange)
+    );
+    assert_eq!(
+        <Varint<u8>>::schema().decode(&input).unwrap_err(),
+        <Varint<u8>>::de_bytes(&input).unwrap_err()
+    );
+
+    assert_eq!(
+        Packed::schema().decode(&[1, 2]),
+        Err(SadbyError::UnexpectedToken)
+    );
+    assert_eq!(
+        Event::schema().decode(&[9]),
+        Err(SadbyError::UnexpectedToken)
+    );
+    assert_eq!(
+        bool::schema().decode(&[2]),
+        Err(SadbyError::UnexpectedToken)
+    );
+
+    let dangling = Schema {
+        fixed_size: None,
+        kind: Kind::Ref("Nope".to_owned()),
+    };
+    assert!(matches!(dangling.decode(&[]), Err(SadbyError::Custom(_))));
+}
+
+#[test]
+fn annotate_names_every_range() {
+    let bytes = Tagged { a: 2.0, b: None }.se_bytes();
+    let (notes, value) = Tagged::schema().annotate(&bytes);
+    assert_eq!(value, Tagged::schema().decode(&bytes));
+
+    assert_eq!(notes[0].range, 0..bytes.len());
+    assert_eq!(notes[0].path, "");
+    for note in &notes[1..] {
+        assert!(note.range.end <= bytes.len(), "{note:?}");
+    }
+    assert!(notes.iter().any(|n| n.path == "a"), "{notes:#?}");
+    assert!(
+        notes
+            .windows(2)
+            .all(|w| w[0].range.start <= w[1].range.start),
+        "{notes:#?}"
+    );
+
+    let (notes, value) = Tagged::schema().annotate(&bytes[..4]);
+    assert_eq!(value, Err(SadbyError::UnexpectedToken));
+    assert!(!notes.is_empty());
+}
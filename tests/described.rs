@@ -0,0 +1,163 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use sadby::{BigEndian, Checked, Compressed, Sadby, SadbyError, Varint, described};
+
+fn non_negative(v: &i32) -> Result<(), &'static str> {
+    if *v >= 0 { Ok(()) } else { Err("negative") }
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Record {
+    a: u32,
+    b: String,
+    c: Option<Vec<i16>>,
+    d: HashMap<u8, (bool, char)>,
+    e: Varint<u64>,
+    f: Checked<u32>,
+    g: [u8; 3],
+    h: Compressed<String>,
+    i: BigEndian<u16>,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Tuple(u8, i128, Box<[u128]>);
+
+#[derive(Sadby, Debug, PartialEq)]
+#[repr(u8)]
+enum Event {
+    A = 3,
+    B(u8, String),
+    C { x: i8, y: BTreeSet<u16> },
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(transparent)]
+struct Wrapper(u16);
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Validated {
+    #[sadby(validate = "non_negative")]
+    value: i32,
+    id: u8,
+}
+
+mod old {
+    use sadby::Sadby;
+
+    #[derive(Sadby, Debug, PartialEq)]
+    pub struct Versioned {
+        pub a: u8,
+        pub extra: Vec<String>,
+        pub b: String,
+    }
+
+    #[derive(Sadby, Debug, PartialEq)]
+    pub struct Only {
+        pub a: u8,
+    }
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(version = 2)]
+struct Versioned {
+    b: String,
+    a: u8,
+    #[sadby(since = 2)]
+    n: Vec<u8>,
+}
+
+#[test]
+fn values_round_trip() {
+    let record = Record {
+        a: 7,
+        b: "hi".to_owned(),
+        c: Some(vec![-1, 300]),
+        d: [(1, (true, 'é')), (2, (false, 'x'))].into(),
+        e: Varint(1 << 40),
+        f: Checked::new(5),
+        g: [1, 2, 3],
+        h: Compressed("aaaaaaaaaaaa".to_owned()),
+        i: BigEndian(0x102),
+    };
+    let bytes = described::to_bytes(&record).unwrap();
+    assert_eq!(described::from_bytes(&bytes), Ok(record));
+
+    // Every prefix of a valid payload is cut short somewhere.
+    for len in 0..bytes.len() {
+        assert!(described::from_bytes::<Record>(&bytes[..len]).is_err());
+    }
+
+    let tuple = Tuple(1, -5, vec![u128::MAX].into());
+    let bytes = described::to_bytes(&tuple).unwrap();
+    assert_eq!(described::from_bytes(&bytes), Ok(tuple));
+
+    for event in [
+        Event::A,
+        Event::B(4, "x".to_owned()),
+        Event::C {
+            x: -3,
+            y: [1, 2].into(),
+        },
+    ] {
+        let bytes = described::to_bytes(&event).unwrap();
+        assert_eq!(described::from_bytes(&bytes), Ok(event));
+    }
+
+    let map = BTreeMap::from([(1u16, vec![1u8])]);
+    let bytes = described::to_bytes(&map).unwrap();
+    assert_eq!(described::from_bytes(&bytes), Ok(map));
+}
+
+#[test]
+fn transparent_is_its_field() {
+    assert_eq!(described::to_bytes(&Wrapper(3)), described::to_bytes(&3u16));
+    let bytes = described::to_bytes(&Wrapper(3)).unwrap();
+    assert_eq!(described::from_bytes(&bytes), Ok(Wrapper(3)));
+}
+
+#[test]
+fn fields_match_by_name() {
+    let old = old::Versioned {
+        a: 1,
+        extra: vec!["q".to_owned()],
+        b: "b".to_owned(),
+    };
+    let bytes = described::to_bytes(&old).unwrap();
+    assert_eq!(
+        described::from_bytes(&bytes),
+        Ok(Versioned {
+            b: "b".to_owned(),
+            a: 1,
+            n: vec![],
+        })
+    );
+
+    let bytes = described::to_bytes(&old::Only { a: 1 }).unwrap();
+    assert_eq!(
+        described::from_bytes::<Versioned>(&bytes),
+        Err(SadbyError::UnexpectedToken)
+    );
+}
+
+#[test]
+fn malformed_input_is_an_error() {
+    let mut bytes = described::to_bytes(&7u32).unwrap();
+    assert!(described::from_bytes::<String>(&bytes).is_err());
+    bytes.push(0);
+    assert!(described::from_bytes::<u32>(&bytes).is_err());
+
+    let deep = vec![described::Tag::Some as u8; 1000];
+    assert!(described::Reader::new(&deep).skip().is_err());
+}
+
+#[test]
+fn validation_runs() {
+    let bytes = described::to_bytes(&Validated { value: -1, id: 0 }).unwrap();
+    assert_eq!(
+        described::from_bytes::<Validated>(&bytes),
+        Err(SadbyError::Invalid {
+            path: "value".to_owned(),
+            message: "negative".to_owned(),
+        })
+    );
+}
@@ -0,0 +1,75 @@
+use sadby::{BigEndian, LittleEndian, Sadby};
+
+#[derive(Sadby, Debug, PartialEq, Clone, Copy)]
+struct Point {
+    x: u16,
+    y: i32,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(endian = "big")]
+struct Net {
+    a: u16,
+    #[sadby(endian = "little")]
+    b: u16,
+    point: Point,
+    list: Vec<u32>,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(packed, endian = "big")]
+struct Header {
+    #[sadby(bits = 4)]
+    a: u8,
+    #[sadby(bits = 4)]
+    b: u8,
+    len: u32,
+}
+
+#[test]
+fn wrappers_pick_the_byte_order() {
+    let point = Point {
+        x: 0x0102,
+        y: 0x03040506,
+    };
+    assert_eq!(point.se_bytes(), [2, 2, 1, 4, 6, 5, 4, 3]);
+    assert_eq!(LittleEndian(point).se_bytes(), point.se_bytes());
+
+    let bytes = BigEndian(point).se_bytes();
+    assert_eq!(bytes, [2, 1, 2, 4, 3, 4, 5, 6]);
+    assert_eq!(BigEndian::<Point>::de_bytes(&bytes).map(|p| p.0), Ok(point));
+
+    assert_eq!(BigEndian(1.0f32).se_bytes(), 1.0f32.to_be_bytes());
+}
+
+#[test]
+fn field_endian_overrides_container() {
+    let point = Point {
+        x: 0x0102,
+        y: 0x03040506,
+    };
+    let value = Net {
+        a: 0x0102,
+        b: 0x0102,
+        point,
+        list: vec![1],
+    };
+    let bytes = value.se_bytes();
+    assert_eq!(bytes[..6], [2, 1, 2, 2, 2, 1]);
+    assert_eq!(Net::de_bytes(&bytes), Ok(value));
+}
+
+#[test]
+fn packed_big_endian() {
+    let value = Header { a: 1, b: 2, len: 7 };
+    let bytes = value.se_bytes();
+    assert_eq!(bytes, [0x21, 0, 0, 0, 7]);
+    assert_eq!(Header::de_bytes(&bytes), Ok(value));
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn uuid_is_big_endian_already() {
+    let uuid = uuid::Uuid::from_u128(0x0102);
+    assert_eq!(BigEndian(uuid).se_bytes(), uuid.as_bytes());
+}
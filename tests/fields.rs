@@ -0,0 +1,53 @@
+use sadby::{Sadby, SadbyError};
+
+#[rustfmt::skip]
+#[derive(Sadby, Debug, PartialEq)]
+struct Wide(
+    u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8,
+    u8, u8, u8, u8, u8, u16,
+);
+
+#[rustfmt::skip]
+#[derive(Sadby, Debug, PartialEq)]
+#[repr(u8)]
+enum Event {
+    Wide(
+        u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8, u8,
+        u8, u8, u8, u8, u8, u8, u8,
+    ),
+    // Named like the identifiers the derive used to pick for unnamed fields.
+    Named { a: u8, b: u16, z: String },
+}
+
+#[test]
+fn wide_tuple_struct() {
+    #[rustfmt::skip]
+    let value = Wide(
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+        25, 26, 27, 28, 300,
+    );
+    let bytes = value.se_bytes();
+    assert_eq!(bytes.len(), 29 * 2 + 3);
+    assert_eq!(Wide::de_bytes(&bytes), Ok(value));
+    assert_eq!(
+        Wide::de_bytes(&bytes[..bytes.len() - 1]),
+        Err(SadbyError::UnexpectedToken)
+    );
+}
+
+#[test]
+fn wide_tuple_variant() {
+    #[rustfmt::skip]
+    let value = Event::Wide(
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+        25, 26, 27, 28, 29,
+    );
+    assert_eq!(Event::de_bytes(&value.se_bytes()), Ok(value));
+
+    let value = Event::Named {
+        a: 1,
+        b: 2,
+        z: "x".to_owned(),
+    };
+    assert_eq!(Event::de_bytes(&value.se_bytes()), Ok(value));
+}
@@ -0,0 +1,209 @@
+#![no_implicit_prelude]
+
+extern crate sadby as renamed;
+extern crate std;
+
+mod facade {
+    pub extern crate sadby;
+}
+
+// Names the generated code must not pick up from the caller's scope.
+mod shadow {
+    #![allow(dead_code, non_camel_case_types)]
+
+    struct Vec;
+    struct Some;
+    struct None;
+    struct Ok;
+    struct Err;
+    struct Result;
+    struct Option;
+    struct String;
+    trait Sadby {}
+    struct SadbyError;
+    mod sadby {}
+
+    fn check(v: &u8) -> ::core::result::Result<(), &'static str> {
+        if *v > 0 {
+            ::core::result::Result::Ok(())
+        } else {
+            ::core::result::Result::Err("zero")
+        }
+    }
+
+    #[derive(crate::facade::sadby::Sadby, Debug, PartialEq, Clone)]
+    #[sadby(
+        crate = "crate::facade::sadby",
+        version = 1,
+        magic = b"H",
+        checksum = "crc32",
+        endian = "big"
+    )]
+    pub struct Attrs {
+        #[sadby(validate = "check")]
+        pub a: u8,
+        #[sadby(since = 1)]
+        pub b: ::std::vec::Vec<u16>,
+        #[sadby(bits = 3)]
+        pub c: u8,
+        #[sadby(constant = 4)]
+        pub d: u32,
+    }
+
+    #[derive(crate::facade::sadby::Sadby, Debug, PartialEq)]
+    #[sadby(crate = "crate::facade::sadby", tagged)]
+    pub struct Tagged<X: ::core::default::Default> {
+        #[sadby(id = 1)]
+        pub x: X,
+    }
+
+    #[derive(crate::facade::sadby::Sadby, Debug, PartialEq)]
+    #[sadby(crate = "crate::facade::sadby", packed)]
+    pub struct Packed(pub u8, pub [u8; 2]);
+
+    #[derive(crate::facade::sadby::Sadby, Debug, PartialEq, Clone, Copy)]
+    #[sadby(crate = "crate::facade::sadby")]
+    #[repr(u8)]
+    pub enum Event {
+        X = 0,
+        Y(u8, u16),
+    }
+
+    #[derive(crate::facade::sadby::Sadby, Debug, PartialEq, Clone, Copy)]
+    #[sadby(crate = "crate::facade::sadby", into = "u8", try_from = "u8")]
+    pub struct Proxy(pub u8);
+
+    impl ::core::convert::From<Proxy> for u8 {
+        fn from(proxy: Proxy) -> u8 {
+            proxy.0
+        }
+    }
+
+    impl ::core::convert::TryFrom<u8> for Proxy {
+        type Error = &'static str;
+
+        fn try_from(v: u8) -> ::core::result::Result<Self, &'static str> {
+            ::core::result::Result::Ok(Proxy(v))
+        }
+    }
+}
+
+// Fields named like the locals of the generated code.
+mod locals {
+    #[derive(crate::renamed::Sadby, Debug, PartialEq)]
+    #[sadby(crate = "crate::renamed", version = 1)]
+    pub struct Positional {
+        pub buf: u8,
+        pub input: u16,
+        pub current: u8,
+        pub next: u8,
+        pub bytes: ::std::vec::Vec<u8>,
+        pub len: u8,
+        pub endian: u8,
+        pub r#type: u8,
+        #[sadby(since = 1)]
+        pub version: u8,
+    }
+
+    #[derive(crate::renamed::Sadby, Debug, PartialEq)]
+    #[sadby(crate = "crate::renamed", tagged)]
+    pub struct Tagged {
+        #[sadby(id = 0)]
+        pub buf: u8,
+        #[sadby(id = 1)]
+        pub input: u16,
+        #[sadby(id = 2)]
+        pub current: u8,
+        #[sadby(id = 3)]
+        pub next: u8,
+        #[sadby(id = 4)]
+        pub bytes: u8,
+        #[sadby(id = 5)]
+        pub len: u8,
+    }
+
+    #[derive(crate::renamed::Sadby, Debug, PartialEq)]
+    #[sadby(crate = "crate::renamed", packed)]
+    pub struct Packed {
+        pub input: u8,
+        pub current: u16,
+        pub next: u8,
+    }
+
+    #[derive(crate::renamed::Sadby, Debug, PartialEq)]
+    #[sadby(crate = "crate::renamed")]
+    #[repr(u8)]
+    pub enum Event {
+        A { buf: u8, input: u16, bytes: u8 },
+    }
+}
+
+use ::core::result::Result::Ok;
+use ::std::assert_eq;
+use renamed::Sadby;
+
+#[test]
+fn derive_ignores_shadowed_names() {
+    let value = shadow::Attrs {
+        a: 1,
+        b: ::std::vec![1, 2],
+        c: 5,
+        d: 4,
+    };
+    assert_eq!(
+        shadow::Attrs::de_bytes(&value.se_bytes()),
+        Ok(::core::clone::Clone::clone(&value))
+    );
+
+    let value = shadow::Tagged { x: 7u32 };
+    assert_eq!(shadow::Tagged::de_bytes(&value.se_bytes()), Ok(value));
+
+    let value = shadow::Packed(1, [2, 3]);
+    assert_eq!(shadow::Packed::de_bytes(&value.se_bytes()), Ok(value));
+
+    let value = shadow::Event::Y(1, 2);
+    assert_eq!(shadow::Event::de_bytes(&value.se_bytes()), Ok(value));
+
+    let value = shadow::Proxy(3);
+    assert_eq!(shadow::Proxy::de_bytes(&value.se_bytes()), Ok(value));
+}
+
+#[test]
+fn fields_named_like_generated_locals() {
+    let value = locals::Positional {
+        buf: 1,
+        input: 300,
+        current: 3,
+        next: 4,
+        bytes: ::std::vec![5, 6],
+        len: 7,
+        endian: 8,
+        r#type: 9,
+        version: 10,
+    };
+    assert_eq!(locals::Positional::de_bytes(&value.se_bytes()), Ok(value));
+
+    let value = locals::Tagged {
+        buf: 1,
+        input: 300,
+        current: 3,
+        next: 4,
+        bytes: 5,
+        len: 6,
+    };
+    assert_eq!(locals::Tagged::de_bytes(&value.se_bytes()), Ok(value));
+
+    let value = locals::Packed {
+        input: 1,
+        current: 300,
+        next: 3,
+    };
+    assert_eq!(locals::Packed::de_bytes(&value.se_bytes()), Ok(value));
+
+    let value = locals::Event::A {
+        buf: 1,
+        input: 300,
+        bytes: 3,
+    };
+    assert_eq!(locals::Event::de_bytes(&value.se_bytes()), Ok(value));
+}
@@ -0,0 +1,74 @@
+use sadby::{Sadby, SadbyError};
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(magic = b"SDBY", version = 1)]
+struct File {
+    #[sadby(constant = 0xCAFE)]
+    marker: u16,
+    body: String,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(packed, magic = b"PK", endian = "big")]
+struct Packed(#[sadby(constant = 0xCAFE)] u16, u8);
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(magic = b"E")]
+#[repr(u8)]
+enum Event {
+    A(#[sadby(constant = 7)] u8, u8) = 0,
+}
+
+/// `File { marker: 1, body: "hi" }`, with the constant written instead of the 1.
+const FILE: &[u8] = b"SDBY\x01\x02\xfe\xca\x02hi";
+
+#[test]
+fn constants_are_written_whatever_the_field_holds() {
+    let file = File {
+        marker: 1,
+        body: "hi".to_owned(),
+    };
+    assert_eq!(file.se_bytes(), FILE);
+    assert_eq!(File::de_bytes(FILE).map(|f| f.marker), Ok(0xCAFE));
+
+    let bytes = Packed(0, 3).se_bytes();
+    assert_eq!(Packed::FIXED_SIZE, Some(5));
+    assert_eq!(bytes, b"PK\xca\xfe\x03");
+    assert_eq!(Packed::de_bytes(&bytes), Ok(Packed(0xCAFE, 3)));
+
+    assert_eq!(
+        Event::de_bytes(&Event::A(0, 1).se_bytes()),
+        Ok(Event::A(7, 1))
+    );
+}
+
+#[test]
+fn wrong_magic_is_bad_magic() {
+    let mut bytes = FILE.to_vec();
+    bytes[0] = b'X';
+    assert_eq!(
+        File::de_bytes(&bytes),
+        Err(SadbyError::BadMagic {
+            expected: b"SDBY".to_vec(),
+            found: b"XDBY".to_vec(),
+        })
+    );
+    assert!(matches!(
+        File::de_bytes(b"SD"),
+        Err(SadbyError::BadMagic { .. })
+    ));
+}
+
+#[test]
+fn wrong_constant_is_bad_constant() {
+    let mut bytes = FILE.to_vec();
+    bytes[6] = 0;
+    assert_eq!(
+        File::de_bytes(&bytes),
+        Err(SadbyError::BadConstant { field: "marker" })
+    );
+    assert_eq!(
+        Packed::de_bytes(b"PK\xca\xff\x03"),
+        Err(SadbyError::BadConstant { field: "0" })
+    );
+}
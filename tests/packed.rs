@@ -0,0 +1,148 @@
+use sadby::{Sadby, SadbyError};
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(packed)]
+struct Ipv4Header {
+    #[sadby(bits = 4)]
+    ihl: u8,
+    #[sadby(bits = 4)]
+    version: u8,
+    tos: u8,
+    total_len: u16,
+    id: u16,
+    flags_frag: u16,
+    ttl: u8,
+    proto: u8,
+    checksum: u16,
+    src: [u8; 4],
+    dst: [u8; 4],
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(packed)]
+struct Record {
+    magic: [u8; 2],
+    header: Ipv4Header,
+    words: [u16; 3],
+    pair: (u8, u32),
+    flag: bool,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(packed)]
+struct Generic<T, const N: usize> {
+    items: [T; N],
+    tail: u8,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(packed, version = 2)]
+struct Versioned {
+    a: u8,
+    #[sadby(since = 2)]
+    b: u16,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(transparent)]
+struct Id(#[sadby(varint)] u32);
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(transparent)]
+struct Port(#[sadby(endian = "big")] u16);
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(packed)]
+struct Endpoint {
+    port: Port,
+    addr: [u8; 4],
+}
+
+// `Id` has no fixed size, so it can only sit next to a packed container.
+#[derive(Sadby, Debug, PartialEq)]
+struct Session {
+    id: Id,
+    endpoint: Endpoint,
+}
+
+#[test]
+fn packed_has_no_prefixes() {
+    let header = Ipv4Header {
+        ihl: 5,
+        version: 4,
+        tos: 0,
+        total_len: 20,
+        id: 1,
+        flags_frag: 0,
+        ttl: 64,
+        proto: 6,
+        checksum: 0xabcd,
+        src: [10, 0, 0, 1],
+        dst: [10, 0, 0, 2],
+    };
+    let bytes = header.se_bytes();
+    assert_eq!(Ipv4Header::FIXED_SIZE, Some(20));
+    assert_eq!(bytes.len(), 20);
+    assert_eq!(bytes[0], 0x45);
+    assert_eq!(Ipv4Header::de_bytes(&bytes), Ok(header));
+    assert_eq!(
+        Ipv4Header::de_bytes(&bytes[..19]),
+        Err(SadbyError::UnexpectedToken)
+    );
+}
+
+#[test]
+fn fixed_size_adds_up_nested_fields() {
+    assert_eq!(Record::FIXED_SIZE, Some(2 + 20 + 6 + 6 + 1));
+    assert_eq!(Generic::<u32, 2>::FIXED_SIZE, Some(9));
+    assert_eq!(Versioned::FIXED_SIZE, Some(4));
+    assert_eq!(String::FIXED_SIZE, None);
+
+    let value = Record {
+        magic: *b"RC",
+        header: Ipv4Header::de_bytes(&[0x45; 20]).unwrap(),
+        words: [1, 2, 3],
+        pair: (1, 2),
+        flag: true,
+    };
+    let bytes = value.se_bytes();
+    assert_eq!(Some(bytes.len()), Record::FIXED_SIZE);
+    assert_eq!(Record::de_bytes(&bytes), Ok(value));
+
+    let value = Generic::<u32, 2> {
+        items: [1, 2],
+        tail: 3,
+    };
+    assert_eq!(Generic::de_bytes(&value.se_bytes()), Ok(value));
+}
+
+#[test]
+fn older_packed_versions_skip_newer_fields() {
+    let value = Versioned { a: 1, b: 2 };
+    assert_eq!(Versioned::de_bytes(&value.se_bytes()), Ok(value));
+    assert_eq!(Versioned::de_bytes(&[1, 9]), Ok(Versioned { a: 9, b: 0 }));
+}
+
+#[test]
+fn transparent_fields_keep_their_encoded_size() {
+    assert_eq!(Id::FIXED_SIZE, None);
+    assert_eq!(Port::FIXED_SIZE, Some(2));
+    assert_eq!(Endpoint::FIXED_SIZE, Some(6));
+
+    let value = Endpoint {
+        port: Port(0x1f90),
+        addr: [127, 0, 0, 1],
+    };
+    assert_eq!(value.se_bytes(), [0x1f, 0x90, 127, 0, 0, 1]);
+    assert_eq!(Endpoint::de_bytes(&value.se_bytes()), Ok(value));
+
+    let value = Session {
+        id: Id(300),
+        endpoint: Endpoint {
+            port: Port(80),
+            addr: [10, 0, 0, 1],
+        },
+    };
+    assert_eq!(Id(300).se_bytes(), [0xac, 0x02]);
+    assert_eq!(Session::de_bytes(&value.se_bytes()), Ok(value));
+}
@@ -0,0 +1,63 @@
+use sadby::{Sadby, SadbyError};
+
+#[derive(Sadby, Debug, PartialEq, Clone, Copy)]
+#[sadby(from = "u8", into = "u8")]
+struct Level(u8);
+
+impl From<u8> for Level {
+    fn from(value: u8) -> Self {
+        Level(value.min(9))
+    }
+}
+
+impl From<Level> for u8 {
+    fn from(level: Level) -> Self {
+        level.0
+    }
+}
+
+#[derive(Sadby, Debug, PartialEq, Clone)]
+#[sadby(into = "u32", try_from = "u32")]
+struct Even {
+    inner: u32,
+}
+
+impl From<Even> for u32 {
+    fn from(even: Even) -> Self {
+        even.inner
+    }
+}
+
+impl TryFrom<u32> for Even {
+    type Error = String;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        if value.is_multiple_of(2) {
+            Ok(Even { inner: value })
+        } else {
+            Err(format!("{value} is odd"))
+        }
+    }
+}
+
+#[test]
+fn from_proxy_round_trips() {
+    assert_eq!(Level(3).se_bytes(), 3u8.se_bytes());
+    assert_eq!(Level::de_bytes(&Level(3).se_bytes()), Ok(Level(3)));
+    assert_eq!(Level::de_bytes(&200u8.se_bytes()), Ok(Level(9)));
+}
+
+#[test]
+fn try_from_proxy_round_trips() {
+    let even = Even { inner: 4 };
+    assert_eq!(even.se_bytes(), 4u32.se_bytes());
+    assert_eq!(Even::de_bytes(&even.se_bytes()), Ok(even));
+}
+
+#[test]
+fn try_from_error_is_custom() {
+    assert_eq!(
+        Even::de_bytes(&5u32.se_bytes()),
+        Err(SadbyError::Custom("5 is odd".to_owned()))
+    );
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use sadby::schema::{Body, Kind, Layout, Schema};
+use sadby::{Endian, Sadby, SadbySchema};
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(magic = b"MS", version = 2, checksum = "crc32c", endian = "big")]
+struct Msg<T> {
+    id: u32,
+    #[sadby(varint)]
+    n: u64,
+    #[sadby(since = 2)]
+    tags: Vec<String>,
+    #[sadby(bits = 3)]
+    a: u8,
+    #[sadby(bits = 5)]
+    b: u8,
+    #[sadby(constant = 7)]
+    c: u16,
+    body: T,
+    map: HashMap<u8, Option<[u16; 2]>>,
+    #[sadby(compress)]
+    text: String,
+    marker: PhantomData<T>,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[repr(u8)]
+enum Event {
+    A = 3,
+    B(u8, String),
+    C { x: i8 },
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(packed)]
+struct Packed(u8, [u16; 2]);
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Node {
+    value: u8,
+    kids: Vec<Node>,
+}
+
+#[derive(Sadby, Debug, PartialEq, Clone)]
+#[sadby(from = "u8", into = "u8")]
+struct Proxy(u8);
+
+impl From<u8> for Proxy {
+    fn from(value: u8) -> Self {
+        Proxy(value)
+    }
+}
+
+impl From<Proxy> for u8 {
+    fn from(proxy: Proxy) -> Self {
+        proxy.0
+    }
+}
+
+#[test]
+fn derive_describes_the_layout() {
+    let schema = Msg::<Event>::schema();
+    let Kind::Endian {
+        endian: Endian::Big,
+        inner,
+    } = &schema.kind
+    else {
+        panic!("expected a big endian schema, got {schema:?}");
+    };
+    let Kind::Container(container) = &inner.kind else {
+        panic!("expected a container, got {inner:?}");
+    };
+    assert_eq!(container.magic.as_deref(), Some(&b"MS"[..]));
+    assert_eq!(container.version, Some(2));
+
+    let Body::Struct {
+        layout: Layout::Positional,
+        fields,
+    } = &container.body
+    else {
+        panic!("expected a positional struct, got {:?}", container.body);
+    };
+    assert_eq!(
+        fields[0].schema,
+        Schema {
+            fixed_size: Some(4),
+            kind: Kind::Endian {
+                endian: Endian::Big,
+                inner: Box::new(u32::schema()),
+            },
+        }
+    );
+    assert_eq!(fields[1].schema.kind, Kind::Varint(Box::new(u64::schema())));
+    assert_eq!(fields[2].since, Some(2));
+    assert_eq!(fields[3].bits, Some(3));
+    assert!(fields[5].constant);
+
+    let Kind::Endian { inner, .. } = &fields[6].schema.kind else {
+        panic!("expected the body to inherit the byte order");
+    };
+    let Kind::Container(event) = &inner.kind else {
+        panic!("expected a container, got {inner:?}");
+    };
+    let Body::Enum { variants, .. } = &event.body else {
+        panic!("expected an enum, got {:?}", event.body);
+    };
+    let discriminants = variants.iter().map(|v| v.discriminant).collect::<Vec<_>>();
+    assert_eq!(discriminants, [3, 4, 5]);
+}
+
+#[test]
+fn fixed_sizes_proxies_and_recursion() {
+    assert_eq!(Packed::schema().fixed_size, Some(5));
+
+    let Kind::Container(proxy) = Proxy::schema().kind else {
+        panic!("expected a container");
+    };
+    assert_eq!(proxy.body, Body::Proxy(Box::new(u8::schema())));
+
+    let json = Node::schema().to_json();
+    assert!(json.contains("\"kind\": \"ref\""), "{json}");
+}
+
+#[test]
+fn json_round_trips() {
+    for schema in [
+        Msg::<Event>::schema(),
+        Event::schema(),
+        Packed::schema(),
+        Node::schema(),
+        Proxy::schema(),
+    ] {
+        let json = schema.to_json();
+        assert_eq!(json, schema.to_json());
+        assert_eq!(Schema::from_json(&json), Ok(schema));
+    }
+
+    assert!(Schema::from_json("{\"kind\": \"nope\"}").is_err());
+}
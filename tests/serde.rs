@@ -0,0 +1,122 @@
+#![cfg(feature = "serde")]
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use ::serde::{Deserialize, Serialize, de::DeserializeOwned};
+use sadby::serde::{from_bytes, to_bytes};
+use sadby::{Sadby, SadbyError, SerdeAsSadby};
+
+#[derive(Sadby, Serialize, Deserialize, Debug, PartialEq)]
+struct Msg {
+    id: u32,
+    name: String,
+    note: Option<i16>,
+    items: Vec<(u8, String)>,
+    kind: Kind,
+    one: One,
+}
+
+#[derive(Sadby, Serialize, Deserialize, Debug, PartialEq)]
+struct One {
+    x: u64,
+}
+
+#[derive(Sadby, Serialize, Deserialize, Debug, PartialEq)]
+#[repr(u8)]
+enum Kind {
+    Empty,
+    Num(u16),
+    Pair(u8, String),
+    Named { x: i8, y: Option<String> },
+}
+
+#[derive(Sadby, Serialize, Deserialize, Debug, PartialEq)]
+struct Sets {
+    #[serde(serialize_with = "sadby::serde::sorted_set")]
+    set: HashSet<u16>,
+}
+
+/// Both encodings give the same bytes, and each decodes what the other wrote.
+fn same<T: Sadby + Serialize + DeserializeOwned + PartialEq + std::fmt::Debug>(value: &T) {
+    let bytes = to_bytes(value).unwrap();
+    assert_eq!(bytes, value.se_bytes(), "{value:?}");
+    assert_eq!(&from_bytes::<T>(&bytes).unwrap(), value);
+    assert_eq!(&T::de_bytes(&bytes).unwrap(), value);
+}
+
+#[test]
+fn matches_the_derive() {
+    for kind in [
+        Kind::Empty,
+        Kind::Num(300),
+        Kind::Pair(1, "p".to_owned()),
+        Kind::Named {
+            x: -1,
+            y: Some("y".to_owned()),
+        },
+    ] {
+        same(&Msg {
+            id: 1,
+            name: "name".to_owned(),
+            note: Some(-3),
+            items: vec![(1, "a".to_owned()), (2, "b".to_owned())],
+            kind,
+            one: One { x: 9 },
+        });
+    }
+    same(&(1u8, 2u16, "s".to_owned()));
+    same(&Some(vec![Some(1u32), None]));
+}
+
+#[test]
+fn unit_variant_is_its_discriminant() {
+    assert_eq!(Kind::Empty.se_bytes(), [0]);
+    same(&Kind::Empty);
+}
+
+#[test]
+fn maps_are_sorted_by_encoded_key() {
+    let map = (0..50u16)
+        .map(|i| (i * 300, i.to_string()))
+        .collect::<HashMap<_, _>>();
+    same(&map);
+    same(&map.into_iter().collect::<BTreeMap<_, _>>());
+
+    let sets = Sets {
+        set: (0..50u16).map(|i| i * 300).collect(),
+    };
+    same(&sets);
+}
+
+#[test]
+fn serde_as_sadby() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Default)]
+    struct Foreign {
+        name: String,
+        tags: Vec<String>,
+    }
+    #[derive(Sadby, Debug, PartialEq)]
+    struct Holder {
+        id: u8,
+        foreign: SerdeAsSadby<Foreign>,
+    }
+
+    let holder = Holder {
+        id: 1,
+        foreign: SerdeAsSadby(Foreign {
+            name: "n".to_owned(),
+            tags: vec!["a".to_owned()],
+        }),
+    };
+    assert_eq!(Holder::de_bytes(&holder.se_bytes()), Ok(holder));
+}
+
+#[test]
+fn errors() {
+    assert_eq!(from_bytes::<Kind>(&[9]), Err(SadbyError::UnexpectedToken));
+    assert_eq!(from_bytes::<u32>(&[1, 2]), Err(SadbyError::UnexpectedToken));
+    assert_eq!(
+        to_bytes(&("x".repeat(256), 1u8)),
+        Err(SadbyError::OutOfRange)
+    );
+}
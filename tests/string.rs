@@ -0,0 +1,17 @@
+use sadby::{Sadby, SadbyError};
+
+#[test]
+fn strings_are_utf8() {
+    let text = "héllo wörld".to_owned();
+    assert_eq!(text.se_bytes(), text.as_bytes());
+    assert_eq!(String::de_bytes(text.as_bytes()), Ok(text));
+}
+
+#[test]
+fn invalid_utf8_is_an_error() {
+    assert_eq!(String::de_bytes(&[0xff]), Err(SadbyError::UnexpectedToken));
+    assert_eq!(
+        String::de_bytes(&[b'a', 0xc3]),
+        Err(SadbyError::UnexpectedToken)
+    );
+}
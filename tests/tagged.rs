@@ -0,0 +1,123 @@
+use sadby::{Sadby, SadbyError};
+
+mod old {
+    use sadby::Sadby;
+
+    #[derive(Sadby, Debug, PartialEq)]
+    #[sadby(tagged)]
+    pub struct Msg {
+        #[sadby(id = 1)]
+        pub a: u16,
+        #[sadby(id = 2)]
+        pub name: String,
+    }
+}
+
+mod new {
+    use sadby::Sadby;
+
+    #[derive(Sadby, Debug, PartialEq)]
+    #[sadby(tagged)]
+    pub struct Msg {
+        #[sadby(id = 2)]
+        pub name: String,
+        #[sadby(id = 1)]
+        pub a: u16,
+        #[sadby(id = 3)]
+        pub flags: Vec<u8>,
+    }
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(tagged)]
+struct Generic<X> {
+    #[sadby(id = 1)]
+    x: X,
+    #[sadby(id = 2)]
+    id: u8,
+}
+
+#[test]
+fn round_trip() {
+    let msg = old::Msg {
+        a: 0x0102,
+        name: "hi".to_owned(),
+    };
+    let bytes = msg.se_bytes();
+    assert_eq!(bytes, [1, 2, 2, 1, 2, 2, b'h', b'i']);
+    assert_eq!(old::Msg::de_bytes(&bytes), Ok(msg));
+}
+
+#[test]
+fn unknown_fields_are_skipped() {
+    let msg = new::Msg {
+        name: "hi".to_owned(),
+        a: 5,
+        flags: vec![1, 2],
+    };
+    assert_eq!(
+        old::Msg::de_bytes(&msg.se_bytes()),
+        Ok(old::Msg {
+            a: 5,
+            name: "hi".to_owned()
+        })
+    );
+}
+
+#[test]
+fn missing_fields_are_defaulted() {
+    let msg = old::Msg {
+        a: 5,
+        name: "hi".to_owned(),
+    };
+    assert_eq!(
+        new::Msg::de_bytes(&msg.se_bytes()),
+        Ok(new::Msg {
+            name: "hi".to_owned(),
+            a: 5,
+            flags: Vec::new()
+        })
+    );
+    assert_eq!(
+        old::Msg::de_bytes(&[]),
+        Ok(old::Msg {
+            a: 0,
+            name: String::new()
+        })
+    );
+}
+
+#[test]
+fn generic_fields_are_defaulted() {
+    let value = Generic {
+        x: vec![1u16, 2],
+        id: 3,
+    };
+    assert_eq!(Generic::de_bytes(&value.se_bytes()), Ok(value));
+
+    assert_eq!(
+        Generic::<String>::de_bytes(&[2, 1, 3]),
+        Ok(Generic {
+            x: String::new(),
+            id: 3
+        })
+    );
+}
+
+#[test]
+fn truncated_input_is_an_error() {
+    let bytes = new::Msg {
+        name: "hi".to_owned(),
+        a: 5,
+        flags: vec![1, 2],
+    }
+    .se_bytes();
+
+    for len in [1, bytes.len() - 1] {
+        assert_eq!(
+            old::Msg::de_bytes(&bytes[..len]),
+            Err(SadbyError::UnexpectedToken),
+            "{len}"
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use sadby::trace::{self, Event};
+use sadby::{Sadby, SadbyError};
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Outer {
+    id: u8,
+    inner: Inner,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Inner {
+    a: u16,
+    names: Vec<String>,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[repr(u8)]
+enum Kind {
+    Empty,
+    Num { n: u32 },
+}
+
+/// An `Outer` with `id: 1, a: 2` and the names `"ok"` and `"no"`.
+const OUTER: &[u8] = &[1, 1, 10, 2, 2, 0, 6, 2, b'o', b'k', 2, b'n', b'o'];
+
+/// Every type entered is left again, in order.
+fn balanced(events: &[Event]) {
+    let mut open = Vec::new();
+    for event in events {
+        match event {
+            Event::Enter { ty, .. } => open.push(*ty),
+            Event::Leave { ty, .. } => assert_eq!(open.pop(), Some(*ty)),
+            _ => {}
+        }
+    }
+    assert!(open.is_empty(), "{open:?}");
+}
+
+#[test]
+fn records_what_was_read() {
+    let (result, events) = trace::de_bytes::<Outer>(OUTER);
+    assert_eq!(
+        result,
+        Ok(Outer {
+            id: 1,
+            inner: Inner {
+                a: 2,
+                names: vec!["ok".to_owned(), "no".to_owned()],
+            },
+        })
+    );
+    balanced(&events);
+
+    assert!(matches!(
+        events[0],
+        Event::Enter {
+            offset: Some(0),
+            len: 13,
+            ..
+        }
+    ));
+    assert!(events.contains(&Event::Field {
+        name: "a",
+        offset: Some(4),
+        len: 2
+    }));
+    assert!(events.contains(&Event::Value {
+        offset: Some(11),
+        len: 2,
+        value: r#""no""#.to_owned()
+    }));
+
+    let (_, events) = trace::de_bytes::<Kind>(&Kind::Num { n: 7 }.se_bytes());
+    assert!(events.contains(&Event::Variant {
+        name: "Num",
+        offset: Some(0)
+    }));
+}
+
+#[test]
+fn ends_where_decoding_failed() {
+    let mut bytes = OUTER.to_vec();
+    bytes[11] = 0xff;
+
+    let (result, events) = trace::de_bytes::<Outer>(&bytes);
+    assert_eq!(result, Err(SadbyError::UnexpectedToken));
+    balanced(&events);
+    assert_eq!(
+        events.last(),
+        Some(&Event::Leave {
+            ty: std::any::type_name::<Outer>(),
+            error: Some("UnexpectedToken".to_owned())
+        })
+    );
+    assert!(trace::render(&events).contains("failed alloc::string::String: UnexpectedToken"));
+}
+
+#[test]
+fn short_input_is_an_error() {
+    for len in 0..OUTER.len() {
+        let (result, events) = trace::de_bytes::<Outer>(&OUTER[..len]);
+        assert!(result.is_err(), "{len}");
+        balanced(&events);
+    }
+
+    assert_eq!(Kind::de_bytes(&[]), Err(SadbyError::UnexpectedToken));
+    assert_eq!(Outer::de_bytes(&[]), Err(SadbyError::UnexpectedToken));
+    assert_eq!(Kind::de_bytes(&[1, 1]), Err(SadbyError::UnexpectedToken));
+}
+
+#[test]
+fn panics_go_through() {
+    struct Panics;
+    impl Sadby for Panics {
+        fn se_bytes(&self) -> Vec<u8> {
+            Vec::new()
+        }
+        fn de_bytes(_input: &[u8]) -> Result<Self, SadbyError> {
+            panic!("bad impl")
+        }
+    }
+
+    let result = std::panic::catch_unwind(|| trace::de_bytes::<(u8, Panics)>(&[1, 1, 0]));
+    assert!(result.is_err());
+
+    // The trace ended with the panic, the next one starts afresh.
+    let (result, events) = trace::de_bytes::<u16>(&[1, 0]);
+    assert_eq!(result, Ok(1));
+    assert!(matches!(
+        events[0],
+        Event::Enter {
+            ty: "u16",
+            offset: Some(0),
+            ..
+        }
+    ));
+    balanced(&events);
+}
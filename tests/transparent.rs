@@ -0,0 +1,41 @@
+use sadby::Sadby;
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(transparent)]
+struct Meters(f64);
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(transparent)]
+struct Name {
+    inner: String,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Single(u32);
+
+#[test]
+fn transparent_encodes_like_its_field() {
+    assert_eq!(Meters(2.5).se_bytes(), 2.5f64.se_bytes());
+    assert_eq!(Meters::de_bytes(&2.5f64.se_bytes()), Ok(Meters(2.5)));
+
+    assert_eq!(
+        Name {
+            inner: "hello".to_owned()
+        }
+        .se_bytes(),
+        b"hello"
+    );
+    assert_eq!(
+        Name::de_bytes(b"hello"),
+        Ok(Name {
+            inner: "hello".to_owned()
+        })
+    );
+}
+
+#[test]
+fn single_field_struct_round_trips() {
+    let bytes = Single(7).se_bytes();
+    assert_eq!(bytes, 7u32.se_bytes());
+    assert_eq!(Single::de_bytes(&bytes), Ok(Single(7)));
+}
@@ -0,0 +1,51 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use sadby::Sadby;
+
+trait Unit {
+    type Repr;
+}
+
+struct Millis;
+
+impl Unit for Millis {
+    type Repr = u16;
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Fields<const N: usize> {
+    pair: (u8, u16),
+    nested: Vec<Option<[u8; 4]>>,
+    assoc: <Millis as Unit>::Repr,
+    array: [u8; N],
+    qualified: std::vec::Vec<(u8, String, u32)>,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Maps {
+    hash: HashMap<u8, String>,
+    set: BTreeSet<u16>,
+    tree: BTreeMap<String, Vec<u8>>,
+}
+
+#[test]
+fn any_field_type_syntax_round_trips() {
+    let value = Fields::<3> {
+        pair: (1, 300),
+        nested: vec![Some([1, 2, 3, 4]), None],
+        assoc: 7,
+        array: [9, 8, 7],
+        qualified: vec![(1, "hi".to_owned(), 5)],
+    };
+    assert_eq!(Fields::de_bytes(&value.se_bytes()), Ok(value));
+}
+
+#[test]
+fn multi_parameter_generics_round_trip() {
+    let value = Maps {
+        hash: [(1, "x".to_owned()), (2, "yy".to_owned())].into(),
+        set: [1, 2, 3].into(),
+        tree: [("k".to_owned(), vec![1, 2])].into(),
+    };
+    assert_eq!(Maps::de_bytes(&value.se_bytes()), Ok(value));
+}
@@ -0,0 +1,103 @@
+use sadby::{Sadby, SadbyError};
+
+fn non_empty(s: &str) -> Result<(), &'static str> {
+    if s.is_empty() {
+        Err("must not be empty")
+    } else {
+        Ok(())
+    }
+}
+
+fn small(v: &u8) -> Result<(), String> {
+    if *v < 10 {
+        Ok(())
+    } else {
+        Err(format!("{v} is too big"))
+    }
+}
+
+fn consistent(header: &Header) -> Result<(), &'static str> {
+    if header.len as usize == header.items.len() {
+        Ok(())
+    } else {
+        Err("len mismatch")
+    }
+}
+
+#[derive(Sadby, Debug, PartialEq, Clone)]
+#[sadby(validate = "consistent")]
+struct Header {
+    len: u8,
+    items: Vec<u8>,
+}
+
+#[derive(Sadby, Debug, PartialEq, Clone)]
+struct Outer {
+    #[sadby(validate = "non_empty")]
+    name: String,
+    header: Header,
+    #[sadby(bits = 4, validate = "small")]
+    a: u8,
+    #[sadby(bits = 4)]
+    b: u8,
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[repr(u8)]
+enum Event {
+    A(#[sadby(validate = "small")] u8, u8) = 0,
+}
+
+#[test]
+fn failed_checks_are_invalid() {
+    let valid = Outer {
+        name: "n".to_owned(),
+        header: Header {
+            len: 1,
+            items: vec![1],
+        },
+        a: 1,
+        b: 2,
+    };
+    assert_eq!(Outer::de_bytes(&valid.se_bytes()), Ok(valid.clone()));
+
+    let cases = [
+        (
+            Outer {
+                name: String::new(),
+                ..valid.clone()
+            },
+            "name",
+            "must not be empty",
+        ),
+        (
+            Outer {
+                header: Header {
+                    len: 2,
+                    items: vec![1],
+                },
+                ..valid.clone()
+            },
+            "header",
+            "len mismatch",
+        ),
+        (Outer { a: 11, ..valid }, "a", "11 is too big"),
+    ];
+    for (value, path, message) in cases {
+        assert_eq!(
+            Outer::de_bytes(&value.se_bytes()),
+            Err(SadbyError::Invalid {
+                path: path.to_owned(),
+                message: message.to_owned(),
+            })
+        );
+    }
+
+    assert_eq!(
+        Event::de_bytes(&Event::A(12, 0).se_bytes()),
+        Err(SadbyError::Invalid {
+            path: "0".to_owned(),
+            message: "12 is too big".to_owned(),
+        })
+    );
+}
@@ -0,0 +1,108 @@
+use std::collections::{BTreeMap, HashMap};
+
+use sadby::value::{from_value, to_value};
+use sadby::{Sadby, SadbyError, Value, described};
+
+#[derive(Sadby, Debug, PartialEq, Clone)]
+struct Record {
+    a: u32,
+    b: String,
+    c: Option<Vec<i16>>,
+    d: BTreeMap<u8, (bool, char)>,
+    e: Event,
+    p: Value,
+}
+
+#[derive(Sadby, Debug, PartialEq, Clone)]
+#[repr(u8)]
+enum Event {
+    A = 3,
+    B(u8, String),
+    C { x: i8 },
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(tagged)]
+struct Tagged {
+    #[sadby(id = 4)]
+    a: f32,
+}
+
+#[test]
+fn to_value_builds_the_tree() {
+    let record = Record {
+        a: 7,
+        b: "hi".to_owned(),
+        c: Some(vec![-1, 300]),
+        d: [(1, (true, 'é'))].into(),
+        e: Event::C { x: -2 },
+        p: Value::Seq(vec![Value::U8(1)]),
+    };
+    let value = to_value(&record).unwrap();
+    assert_eq!(value.field("a"), Some(&Value::U32(7)));
+    assert_eq!(
+        value.field("c"),
+        Some(&Value::Some(Box::new(Value::Seq(vec![
+            Value::I16(-1),
+            Value::I16(300)
+        ]))))
+    );
+    assert_eq!(
+        value.field("e"),
+        Some(&Value::Variant {
+            discriminant: 5,
+            name: "C".to_owned(),
+            value: Box::new(Value::Struct(vec![("x".to_owned(), Value::I8(-2))])),
+        })
+    );
+    assert_eq!(value.field("e").unwrap().field("x"), Some(&Value::I8(-2)));
+    assert_eq!(value.field("p"), Some(&Value::Seq(vec![Value::U8(1)])));
+
+    assert_eq!(Value::de_bytes(&value.se_bytes()), Ok(value.clone()));
+    assert_eq!(from_value(&value), Ok(record.clone()));
+
+    // Edited trees convert back as long as the types still fit.
+    let Value::Struct(mut fields) = value else {
+        panic!("expected a struct");
+    };
+    fields[0].1 = Value::U32(9);
+    assert_eq!(
+        from_value(&Value::Struct(fields.clone())),
+        Ok(Record { a: 9, ..record })
+    );
+    fields[0].1 = Value::U16(9);
+    assert_eq!(
+        from_value::<Record>(&Value::Struct(fields)),
+        Err(SadbyError::UnexpectedToken)
+    );
+}
+
+#[test]
+fn layouts() {
+    assert_eq!(
+        to_value(&Tagged { a: 1.5 }),
+        Ok(Value::StructIds(vec![(4, Value::F32(1.5))]))
+    );
+    assert_eq!(
+        to_value(&Event::A),
+        Ok(Value::Variant {
+            discriminant: 3,
+            name: "A".to_owned(),
+            value: Box::new(Value::Unit),
+        })
+    );
+    assert_eq!(
+        to_value(&Event::B(1, "x".to_owned())).map(|v| from_value(&v)),
+        Ok(Ok(Event::B(1, "x".to_owned())))
+    );
+    assert_eq!(
+        to_value(&HashMap::from([(1u8, None::<u8>)])),
+        Ok(Value::Map(vec![(Value::U8(1), Value::None)]))
+    );
+}
+
+#[test]
+fn deep_nesting_is_an_error() {
+    let deep = vec![described::Tag::Some as u8; 1000];
+    assert!(described::from_bytes::<Value>(&deep).is_err());
+}
@@ -0,0 +1,95 @@
+use sadby::{Sadby, SadbyError, Varint};
+
+#[derive(Sadby, Debug, PartialEq)]
+struct Record {
+    #[sadby(varint)]
+    id: u64,
+    #[sadby(varint)]
+    delta: i32,
+    name: String,
+    #[sadby(varint, constant = 300)]
+    marker: u16,
+}
+
+#[test]
+fn unsigned_is_leb128() {
+    assert_eq!(Varint(0u64).se_bytes(), [0]);
+    assert_eq!(Varint(127u64).se_bytes(), [127]);
+    assert_eq!(Varint(128u64).se_bytes(), [0x80, 1]);
+    assert_eq!(Varint(300u16).se_bytes(), [0xac, 2]);
+    assert_eq!(Varint(u64::MAX).se_bytes().len(), 10);
+}
+
+#[test]
+fn signed_is_zigzag() {
+    assert_eq!(Varint(-1i32).se_bytes(), [1]);
+    assert_eq!(Varint(1i32).se_bytes(), [2]);
+    assert_eq!(Varint(-64i8).se_bytes(), [127]);
+}
+
+#[test]
+fn extremes_round_trip() {
+    for value in [i64::MIN, i64::MAX, 0, -1, 12345] {
+        let bytes = Varint(value).se_bytes();
+        assert_eq!(Varint::de_bytes(&bytes), Ok(Varint(value)));
+    }
+    for value in [u128::MAX, 0] {
+        let bytes = Varint(value).se_bytes();
+        assert_eq!(Varint::de_bytes(&bytes), Ok(Varint(value)));
+    }
+}
+
+#[test]
+fn overlong_and_trailing_bytes_are_rejected() {
+    assert_eq!(
+        Varint::<u64>::de_bytes(&[0x80, 0]),
+        Err(SadbyError::UnexpectedToken)
+    );
+    assert_eq!(
+        Varint::<u64>::de_bytes(&[0x80]),
+        Err(SadbyError::UnexpectedToken)
+    );
+    assert_eq!(
+        Varint::<u64>::de_bytes(&[1, 2]),
+        Err(SadbyError::UnexpectedToken)
+    );
+}
+
+#[test]
+fn overflow_is_out_of_range() {
+    assert_eq!(
+        Varint::<u8>::de_bytes(&[0x80, 2]),
+        Err(SadbyError::OutOfRange)
+    );
+    assert_eq!(
+        Varint::<u128>::de_bytes(&[0xff; 19]),
+        Err(SadbyError::OutOfRange)
+    );
+
+    // 18 full groups hold 126 bits, so the last one may only add two more.
+    let mut bytes = [0xff; 19];
+    bytes[18] = 0x04;
+    assert_eq!(
+        Varint::<u128>::de_bytes(&bytes),
+        Err(SadbyError::OutOfRange)
+    );
+}
+
+#[test]
+fn varint_fields() {
+    let value = Record {
+        id: 5,
+        delta: -3,
+        name: "x".to_owned(),
+        marker: 0,
+    };
+    let bytes = value.se_bytes();
+    assert_eq!(bytes, [1, 5, 1, 5, 1, b'x', 2, 0xac, 2]);
+    assert_eq!(
+        Record::de_bytes(&bytes),
+        Ok(Record {
+            marker: 300,
+            ..value
+        })
+    );
+}
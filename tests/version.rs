@@ -0,0 +1,126 @@
+use sadby::schema::compat::{self, Direction};
+use sadby::{Sadby, SadbyError, SadbySchema};
+
+mod v1 {
+    use sadby::Sadby;
+
+    #[derive(Sadby, Debug, PartialEq)]
+    #[sadby(version = 1)]
+    pub struct Msg {
+        pub a: u32,
+    }
+
+    #[derive(Sadby, Debug, PartialEq)]
+    #[sadby(version = 1)]
+    #[repr(u8)]
+    pub enum Event {
+        Ping { id: u16 },
+    }
+}
+
+mod v2 {
+    use sadby::Sadby;
+
+    #[derive(Sadby, Debug, PartialEq)]
+    #[sadby(version = 2)]
+    pub struct Msg {
+        pub a: u32,
+        #[sadby(since = 2)]
+        pub b: u16,
+    }
+
+    #[derive(Sadby, Debug, PartialEq)]
+    #[sadby(version = 2)]
+    #[repr(u8)]
+    pub enum Event {
+        Ping {
+            id: u16,
+            #[sadby(since = 2)]
+            ttl: u8,
+        },
+    }
+}
+
+#[derive(Sadby, Debug, PartialEq)]
+#[sadby(version = 2)]
+struct Generic<X> {
+    a: u8,
+    #[sadby(since = 2)]
+    x: X,
+}
+
+#[test]
+fn lone_field_is_prefixed() {
+    assert_eq!(v1::Msg { a: 7 }.se_bytes(), [1, 4, 7, 0, 0, 0]);
+    assert_eq!(v1::Msg::de_bytes(&[1, 4, 7, 0, 0, 0]), Ok(v1::Msg { a: 7 }));
+}
+
+#[test]
+fn old_payload_defaults_new_fields() {
+    let old = v1::Msg { a: 7 }.se_bytes();
+    assert_eq!(v2::Msg::de_bytes(&old), Ok(v2::Msg { a: 7, b: 0 }));
+
+    let old = v1::Event::Ping { id: 3 }.se_bytes();
+    assert_eq!(
+        v2::Event::de_bytes(&old),
+        Ok(v2::Event::Ping { id: 3, ttl: 0 })
+    );
+
+    assert_eq!(
+        Generic::<Vec<u16>>::de_bytes(&[1, 1, 7]),
+        Ok(Generic {
+            a: 7,
+            x: Vec::new()
+        })
+    );
+}
+
+#[test]
+fn round_trip() {
+    let msg = v2::Msg { a: 7, b: 9 };
+    assert_eq!(msg.se_bytes(), [2, 4, 7, 0, 0, 0, 2, 9, 0]);
+    assert_eq!(v2::Msg::de_bytes(&msg.se_bytes()), Ok(msg));
+}
+
+#[test]
+fn newer_payload_is_rejected() {
+    let new = v2::Msg { a: 7, b: 9 }.se_bytes();
+    assert_eq!(
+        v1::Msg::de_bytes(&new),
+        Err(SadbyError::NewerVersion {
+            found: 2,
+            supported: 1
+        })
+    );
+    assert_eq!(
+        v2::Msg::de_bytes(&[9, 4, 7, 0, 0, 0, 2, 9, 0]),
+        Err(SadbyError::NewerVersion {
+            found: 9,
+            supported: 2
+        })
+    );
+}
+
+#[test]
+fn schema_decodes_like_the_derive() {
+    let old = v1::Msg { a: 7 }.se_bytes();
+    let new = v2::Msg { a: 7, b: 9 }.se_bytes();
+    let schema = v2::Msg::schema();
+
+    assert_eq!(schema.encode(&schema.decode(&new).unwrap()), Ok(new));
+    assert!(schema.decode(&old).is_ok());
+    assert_eq!(
+        v1::Msg::schema().decode(&[2, 4, 7, 0, 0, 0, 2, 9, 0]),
+        Err(SadbyError::NewerVersion {
+            found: 2,
+            supported: 1
+        })
+    );
+}
+
+#[test]
+fn compat_reports_only_the_newer_version() {
+    let breaks = compat::check(&v1::Msg::schema(), &v2::Msg::schema());
+    assert_eq!(breaks.len(), 1, "{breaks:?}");
+    assert_eq!(breaks[0].direction, Direction::Forward);
+}